@@ -0,0 +1,140 @@
+// Allowance inventory command
+//
+// Usage:
+//   cargo run -- approvals [--lookback N]                 # list open allowances
+//   cargo run -- approvals revoke [--dry-run] [--lookback N]
+//
+// Candidates are the spenders recorded in APPROVAL_LEDGER_PATH plus Approval
+// logs emitted by the worker wallets in the last N blocks (default 50000),
+// and the known Li.Fi spenders for every token found there.
+
+use std::error::Error;
+
+use alloy_primitives::{Address, U256};
+use worker::chain::{self, Chain, EvmChain};
+use worker::module::erc20::approvals::{self, ApprovalLedger, OpenAllowance};
+use worker::module::lifi;
+
+use crate::di::AppContext;
+
+const DEFAULT_LOOKBACK_BLOCKS: u64 = 50_000;
+
+struct Options {
+    revoke: bool,
+    dry_run: bool,
+    lookback_blocks: u64,
+}
+
+fn parse_options(args: &[String]) -> Result<Options, Box<dyn Error>> {
+    let mut options = Options {
+        revoke: false,
+        dry_run: false,
+        lookback_blocks: DEFAULT_LOOKBACK_BLOCKS,
+    };
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "revoke" => options.revoke = true,
+            "--dry-run" => options.dry_run = true,
+            "--lookback" => {
+                let value = args.next().ok_or("--lookback needs a block count")?;
+                options.lookback_blocks = value.parse()?;
+            }
+            other => return Err(format!("Unknown argument: {}", other).into()),
+        }
+    }
+    Ok(options)
+}
+
+/// Scans every configured chain and optionally revokes what it finds.
+pub fn run(context: &AppContext, args: &[String]) -> Result<(), Box<dyn Error>> {
+    let options = parse_options(args)?;
+    let accounts = context.chain.accounts().to_vec();
+    let owners: Vec<Address> = accounts.iter().map(|account| account.address()).collect();
+    if owners.is_empty() {
+        return Err("No wallets configured".into());
+    }
+    let ledger = ApprovalLedger::from_env().entries()?;
+
+    let runtime = tokio::runtime::Runtime::new()?;
+    runtime.block_on(async {
+        let mut total = 0;
+        for chain_config in &context.config.chains {
+            let name = chain_config.name.clone();
+            let chain = match EvmChain::new(chain_config.clone(), accounts.clone()) {
+                Ok(chain) => chain,
+                Err(e) => {
+                    println!("[{}] Invalid chain config: {}, skipping", name, e);
+                    continue;
+                }
+            };
+            match scan_chain(&chain, &owners, &ledger, &options).await {
+                Ok(count) => total += count,
+                Err(e) => println!("[{}] Allowance scan failed: {}", name, e),
+            }
+        }
+        println!("{} open allowance(s) found", total);
+    });
+    Ok(())
+}
+
+async fn scan_chain(
+    chain: &dyn Chain,
+    owners: &[Address],
+    ledger: &[approvals::LedgerEntry],
+    options: &Options,
+) -> Result<usize, Box<dyn Error>> {
+    chain::verify_chain_id(chain).await?;
+    let candidates =
+        approvals::approval_candidates(chain, owners, ledger, lifi::KNOWN_SPENDERS, options.lookback_blocks).await?;
+    let open = approvals::scan_allowances(chain, &candidates).await?;
+    println!(
+        "[{}] {} candidate(s), {} open allowance(s)",
+        chain.config().name,
+        candidates.len(),
+        open.len()
+    );
+    for allowance in &open {
+        print_allowance(chain, allowance);
+    }
+
+    if options.revoke && !open.is_empty() {
+        let results = approvals::revoke_allowances(chain, &open, options.dry_run).await;
+        for (allowance, result) in open.iter().zip(results) {
+            match result {
+                Ok(Some(receipt)) => println!(
+                    "[{}] Revoked {:#x} on {:#x} in {:#x}",
+                    chain.config().name,
+                    allowance.spender,
+                    allowance.token,
+                    receipt.transaction_hash
+                ),
+                Ok(None) => {}
+                Err(e) => println!(
+                    "[{}] Failed to revoke {:#x} on {:#x}: {}",
+                    chain.config().name,
+                    allowance.spender,
+                    allowance.token,
+                    e
+                ),
+            }
+        }
+    }
+    Ok(open.len())
+}
+
+fn print_allowance(chain: &dyn Chain, allowance: &OpenAllowance) {
+    let amount = if allowance.amount == U256::MAX {
+        "unlimited".to_string()
+    } else {
+        allowance.amount.to_string()
+    };
+    println!(
+        "[{}]   owner {:#x} token {:#x} spender {:#x} amount {}",
+        chain.config().name,
+        allowance.owner,
+        allowance.token,
+        allowance.spender,
+        amount
+    );
+}
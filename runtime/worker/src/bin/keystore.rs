@@ -0,0 +1,90 @@
+// Keystore management tool for worker wallets
+//
+// Usage:
+//   cargo run --bin keystore -- create [DIR]   # generate a new key
+//   cargo run --bin keystore -- import [DIR]   # encrypt an existing private key
+//   cargo run --bin keystore -- list [DIR]     # show keystore addresses
+//
+// DIR defaults to WALLET_KEYSTORE_DIR, then ./keystore.
+// The passphrase is read from WALLET_KEYSTORE_PASSWORD or prompted for.
+
+use dotenv::dotenv;
+use std::env;
+use std::error::Error;
+use std::path::PathBuf;
+use worker::chain::signer::keystore;
+
+fn keystore_dir(arg: Option<&String>) -> PathBuf {
+    arg.cloned()
+        .or_else(|| env::var("WALLET_KEYSTORE_DIR").ok())
+        .unwrap_or_else(|| "keystore".to_string())
+        .into()
+}
+
+/// Asks for a new passphrase twice unless WALLET_KEYSTORE_PASSWORD is set.
+fn new_password() -> Result<String, Box<dyn Error>> {
+    if let Ok(password) = env::var("WALLET_KEYSTORE_PASSWORD") {
+        return Ok(password);
+    }
+
+    let password = rpassword::prompt_password("New keystore passphrase: ")?;
+    let confirm = rpassword::prompt_password("Repeat passphrase: ")?;
+    if password != confirm {
+        return Err("Passphrases do not match".into());
+    }
+    if password.is_empty() {
+        return Err("Passphrase must not be empty".into());
+    }
+    Ok(password)
+}
+
+fn run(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let command = args.get(1).map(|s| s.as_str()).unwrap_or("list");
+    let dir = keystore_dir(args.get(2));
+
+    match command {
+        "create" => {
+            let password = new_password()?;
+            let (address, path) = keystore::create_keystore(&dir, &password)?;
+            println!("✅ Created {:#x} -> {}", address, path.display());
+        }
+        "import" => {
+            let private_key = rpassword::prompt_password("Private key (hex): ")?;
+            let password = new_password()?;
+            let (address, path) = keystore::import_private_key(&dir, private_key.trim(), &password)?;
+            println!("✅ Imported {:#x} -> {}", address, path.display());
+        }
+        "list" => {
+            let paths = keystore::keystore_paths(&dir)?;
+            if paths.is_empty() {
+                println!("No keystores found in {}", dir.display());
+            }
+            for path in paths {
+                match keystore::read_keystore_file(&path) {
+                    Ok(file) => println!(
+                        "0x{} ({}, kdf: {})",
+                        file.address.as_deref().unwrap_or("<no address>"),
+                        path.display(),
+                        file.crypto.kdf
+                    ),
+                    Err(e) => println!("❌ {}: {}", path.display(), e),
+                }
+            }
+        }
+        other => {
+            return Err(format!("Unknown command '{}' (expected create, import or list)", other).into());
+        }
+    }
+
+    Ok(())
+}
+
+fn main() {
+    dotenv().ok();
+
+    let args: Vec<String> = env::args().collect();
+    if let Err(e) = run(&args) {
+        println!("❌ {}", e);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,179 @@
+// Shared helpers for the test programs: result reporting and a local HTTP mock
+//
+// Every test program pulls this in with `mod support;`, so helpers that one
+// program does not use are expected.
+#![allow(dead_code)]
+
+pub mod node;
+
+use std::future::Future;
+use std::sync::Arc;
+
+use serde_json::{json, Value};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Prints the result of one check and clears `passed` on failure.
+pub fn check(passed: &mut bool, name: &str, ok: bool, detail: String) {
+    if ok {
+        println!("✅ {}: {}", name, detail);
+    } else {
+        println!("❌ {}: {}", name, detail);
+        *passed = false;
+    }
+}
+
+/// A request received by the mock server.
+#[derive(Clone, Debug, Default)]
+pub struct Request {
+    /// Path with the query string, e.g. `/status?txHash=0x..`
+    pub path: String,
+    /// Request line and headers, lowercased.
+    pub headers: String,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    /// Body as JSON (`Null` if it is not JSON).
+    pub fn json(&self) -> Value {
+        serde_json::from_slice(&self.body).unwrap_or_default()
+    }
+
+    /// Value of a header; `name` must be lowercase.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.lines().find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            (key == name).then(|| value.trim())
+        })
+    }
+}
+
+/// A response returned by a mock handler.
+#[derive(Clone, Debug)]
+pub struct Response {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl Response {
+    /// 200 with a JSON body.
+    pub fn json(body: Value) -> Self {
+        Self::text(200, body.to_string())
+    }
+
+    pub fn text(status: u16, body: impl Into<String>) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: body.into(),
+        }
+    }
+
+    pub fn with_status(mut self, status: u16) -> Self {
+        self.status = status;
+        self
+    }
+
+    pub fn with_header(mut self, name: &str, value: impl ToString) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+/// JSON-RPC reply to `request`: `reply` holds either `result` or `error`.
+pub fn rpc_reply(request: &Value, mut reply: Value) -> Response {
+    reply["jsonrpc"] = "2.0".into();
+    reply["id"] = request["id"].clone();
+    Response::json(reply)
+}
+
+/// JSON-RPC error object with the given code.
+pub fn rpc_error(code: i64, message: &str) -> Value {
+    json!({"error": {"code": code, "message": message}})
+}
+
+/// Starts a mock HTTP server on a free local port and returns its base URL.
+/// Every connection carries one request and is closed after the response.
+pub async fn serve<F>(handler: F) -> String
+where
+    F: Fn(Request) -> Response + Send + Sync + 'static,
+{
+    let handler = Arc::new(handler);
+    serve_async(move |request| {
+        let handler = handler.clone();
+        async move { handler(request) }
+    })
+    .await
+}
+
+/// Like [`serve`], for handlers that need to await (e.g. to delay responses).
+pub async fn serve_async<F, Fut>(handler: F) -> String
+where
+    F: Fn(Request) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Response> + Send + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind mock server");
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let handler = Arc::new(handler);
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let handler = handler.clone();
+            tokio::spawn(async move {
+                let _ = handle_connection(stream, handler.as_ref()).await;
+            });
+        }
+    });
+    url
+}
+
+async fn handle_connection<F, Fut>(mut stream: TcpStream, handler: &F) -> std::io::Result<()>
+where
+    F: Fn(Request) -> Fut,
+    Fut: Future<Output = Response>,
+{
+    // Read headers and body (Content-Length only, which is what reqwest sends)
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let request = loop {
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            return Ok(());
+        }
+        buffer.extend_from_slice(&chunk[..n]);
+        let Some(header_end) = buffer.windows(4).position(|window| window == b"\r\n\r\n") else {
+            continue;
+        };
+        let headers = String::from_utf8_lossy(&buffer[..header_end]).to_lowercase();
+        let content_length = headers
+            .lines()
+            .find_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                (name == "content-length").then(|| value.trim().parse::<usize>().ok())?
+            })
+            .unwrap_or(0);
+        if buffer.len() >= header_end + 4 + content_length {
+            // Paths keep their case; only the header names and values are lowercased
+            let request_line = String::from_utf8_lossy(&buffer[..header_end]).lines().next().unwrap_or_default().to_string();
+            break Request {
+                path: request_line.split_whitespace().nth(1).unwrap_or_default().to_string(),
+                headers,
+                body: buffer[header_end + 4..header_end + 4 + content_length].to_vec(),
+            };
+        }
+    };
+
+    let response = handler(request).await;
+    let reason = reqwest::StatusCode::from_u16(response.status)
+        .ok()
+        .and_then(|status| status.canonical_reason())
+        .unwrap_or("Mock");
+    let mut head = format!("HTTP/1.1 {} {}\r\nContent-Type: application/json\r\n", response.status, reason);
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str(&format!("Content-Length: {}\r\nConnection: close\r\n\r\n", response.body.len()));
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(response.body.as_bytes()).await?;
+    stream.shutdown().await
+}
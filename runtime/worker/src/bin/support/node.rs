@@ -0,0 +1,584 @@
+// Stateful mock EVM node for the test programs
+//
+// Accepts signed legacy and EIP-1559 transactions, keeps them in a mempool
+// (pending or queued behind a nonce gap), mines them one per block and answers
+// the reads the chain layer needs: blocks, receipts, nonces, fees, balances and
+// ERC-20 balanceOf/allowance. Mined calls apply native transfers, ERC-20
+// approve/transfer and an optional hook for anything else. Its latest blocks
+// can be replaced by a fork to simulate a reorg.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use alloy_primitives::{keccak256, Address, Bytes, B256, U256};
+use alloy_rlp::{Decodable, Header};
+use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+use serde_json::{json, Value};
+use worker::chain::tx::{TxFees, TypedTransaction};
+
+use super::{rpc_error, rpc_reply, Response};
+
+/// `balanceOf(address)`
+const BALANCE_OF_SELECTOR: [u8; 4] = [0x70, 0xa0, 0x82, 0x31];
+/// `allowance(address,address)`
+const ALLOWANCE_SELECTOR: [u8; 4] = [0xdd, 0x62, 0xed, 0x3e];
+/// `approve(address,uint256)`
+const APPROVE_SELECTOR: [u8; 4] = [0x09, 0x5e, 0xa7, 0xb3];
+/// `transfer(address,uint256)`
+const TRANSFER_SELECTOR: [u8; 4] = [0xa9, 0x05, 0x9c, 0xbb];
+
+pub const TRANSFER_GAS: u64 = 21_000;
+/// Block the node starts at.
+const GENESIS_HEAD: u64 = 100;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mining {
+    /// Every transaction that becomes executable is mined in its own block right away.
+    Instant,
+    /// Transactions stay in the mempool until `MockNode::mine`.
+    Manual,
+}
+
+/// A transaction the node accepted, with its recovered sender.
+#[derive(Clone, Debug)]
+pub struct NodeTx {
+    pub hash: B256,
+    pub from: Address,
+    pub tx: TypedTransaction,
+}
+
+impl NodeTx {
+    /// Fee cap per gas (legacy gas price or `max_fee_per_gas`).
+    pub fn max_fee(&self) -> U256 {
+        match self.tx.fees {
+            TxFees::Legacy { gas_price } => gas_price,
+            TxFees::Eip1559 { max_fee_per_gas, .. } => max_fee_per_gas,
+        }
+    }
+
+    fn effective_gas_price(&self, base_fee: U256) -> U256 {
+        match self.tx.fees {
+            TxFees::Legacy { gas_price } => gas_price,
+            TxFees::Eip1559 {
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+            } => max_fee_per_gas.min(base_fee + max_priority_fee_per_gas),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct MinedTx {
+    pub tx: NodeTx,
+    pub block: u64,
+    pub success: bool,
+    pub gas_used: u64,
+    pub gas_price: U256,
+}
+
+/// Extra effect of a mined call (e.g. a router paying out a swap); returns
+/// whether the call succeeded. Runs after the native and ERC-20 effects.
+pub type MinedHook = Box<dyn Fn(&mut NodeState, &NodeTx) -> bool + Send>;
+
+pub struct NodeState {
+    pub chain_id: u64,
+    pub mining: Mining,
+    pub head: u64,
+    pub base_fee: U256,
+    pub priority_fee: U256,
+    /// Gas used by (and estimated for) calls with calldata.
+    pub gas_estimate: u64,
+    pub balances: HashMap<Address, U256>,
+    /// Mined transaction count per sender.
+    pub nonces: HashMap<Address, u64>,
+    pub pool: Vec<NodeTx>,
+    pub mined: HashMap<B256, MinedTx>,
+    /// ERC-20 balances by `(token, owner)`.
+    pub token_balances: HashMap<(Address, Address), U256>,
+    /// ERC-20 allowances by `(token, owner, spender)`.
+    pub allowances: HashMap<(Address, Address, Address), U256>,
+    /// Calls to these contracts revert with the given data.
+    pub reverts: HashMap<Address, Bytes>,
+    /// Replies (`result` or `error` object) that replace the default handling of a method.
+    pub replies: HashMap<String, Value>,
+    /// Every JSON-RPC request received, in order.
+    pub requests: Vec<Value>,
+    pub on_mined: Option<MinedHook>,
+    /// First block number of every fork `reorg` switched to, oldest first.
+    pub forks: Vec<u64>,
+}
+
+impl NodeState {
+    fn new(chain_id: u64) -> Self {
+        Self {
+            chain_id,
+            mining: Mining::Instant,
+            head: GENESIS_HEAD,
+            base_fee: U256::from(1_000_000_000u64),
+            priority_fee: U256::from(100_000_000u64),
+            gas_estimate: 60_000,
+            balances: HashMap::new(),
+            nonces: HashMap::new(),
+            pool: Vec::new(),
+            mined: HashMap::new(),
+            token_balances: HashMap::new(),
+            allowances: HashMap::new(),
+            reverts: HashMap::new(),
+            replies: HashMap::new(),
+            requests: Vec::new(),
+            on_mined: None,
+            forks: Vec::new(),
+        }
+    }
+
+    pub fn nonce(&self, address: Address) -> u64 {
+        self.nonces.get(&address).copied().unwrap_or_default()
+    }
+
+    /// Mined nonces plus the pooled transactions that directly follow them.
+    pub fn pending_nonce(&self, address: Address) -> u64 {
+        let mut next = self.nonce(address);
+        while self.pool.iter().any(|tx| tx.from == address && tx.tx.nonce == next) {
+            next += 1;
+        }
+        next
+    }
+
+    pub fn balance(&self, address: Address) -> U256 {
+        self.balances.get(&address).copied().unwrap_or_default()
+    }
+
+    pub fn token_balance(&self, token: Address, owner: Address) -> U256 {
+        self.token_balances.get(&(token, owner)).copied().unwrap_or_default()
+    }
+
+    /// Moves `amount` of `token` (the zero address for the native token); `false` if `from` has too little.
+    pub fn transfer(&mut self, token: Address, from: Address, to: Address, amount: U256) -> bool {
+        let balance_of = |state: &Self, owner| {
+            if token == Address::ZERO {
+                state.balance(owner)
+            } else {
+                state.token_balance(token, owner)
+            }
+        };
+        let from_balance = balance_of(self, from);
+        if from_balance < amount {
+            return false;
+        }
+        let to_balance = balance_of(self, to);
+        if token == Address::ZERO {
+            self.balances.insert(from, from_balance - amount);
+            self.balances.insert(to, to_balance + amount);
+        } else {
+            self.token_balances.insert((token, from), from_balance - amount);
+            self.token_balances.insert((token, to), to_balance + amount);
+        }
+        true
+    }
+
+    /// Mines every executable pooled transaction, one block each.
+    pub fn mine(&mut self) {
+        while let Some(index) = self
+            .pool
+            .iter()
+            .position(|tx| tx.tx.nonce == self.nonce(tx.from))
+        {
+            let tx = self.pool.remove(index);
+            self.execute(tx);
+        }
+    }
+
+    /// Adds `count` empty blocks.
+    pub fn advance(&mut self, count: u64) {
+        self.head += count;
+    }
+
+    /// Replaces the last `depth` blocks with a fork of the same height. Their
+    /// transactions are included again at the same numbers, so only the block
+    /// hashes in blocks and receipts change.
+    pub fn reorg(&mut self, depth: u64) {
+        self.forks.push(self.head + 1 - depth.max(1));
+    }
+
+    /// Deterministic block hash that encodes the block number and the fork it is on.
+    pub fn block_hash(&self, number: u64) -> B256 {
+        let fork = self.forks.iter().filter(|from| **from <= number).count();
+        let mut hash = B256::repeat_byte(0xb1);
+        if fork > 0 {
+            hash[23] = fork as u8;
+        }
+        hash[24..].copy_from_slice(&number.to_be_bytes());
+        hash
+    }
+
+    fn execute(&mut self, tx: NodeTx) {
+        self.head += 1;
+        let gas_price = tx.effective_gas_price(self.base_fee);
+        let gas_used = if tx.tx.data.is_empty() {
+            TRANSFER_GAS
+        } else {
+            self.gas_estimate.min(tx.tx.gas_limit)
+        };
+        *self.nonces.entry(tx.from).or_default() += 1;
+        let fee = gas_price * U256::from(gas_used);
+        let balance = self.balance(tx.from);
+        self.balances.insert(tx.from, balance.saturating_sub(fee));
+
+        let to = tx.tx.to.unwrap_or_default();
+        let mut success = !self.reverts.contains_key(&to) && self.transfer(Address::ZERO, tx.from, to, tx.tx.value);
+        if success && tx.tx.data.len() >= 68 {
+            let data = &tx.tx.data;
+            let account = Address::from_slice(&data[16..36]);
+            let amount = U256::from_be_slice(&data[36..68]);
+            if data[..4] == APPROVE_SELECTOR {
+                self.allowances.insert((to, tx.from, account), amount);
+            } else if data[..4] == TRANSFER_SELECTOR {
+                success = self.transfer(to, tx.from, account, amount);
+            }
+        }
+        if success {
+            if let Some(hook) = self.on_mined.take() {
+                success = hook(self, &tx);
+                self.on_mined = Some(hook);
+            }
+        }
+
+        self.mined.insert(
+            tx.hash,
+            MinedTx {
+                tx,
+                block: self.head,
+                success,
+                gas_used,
+                gas_price,
+            },
+        );
+    }
+
+    fn send_raw_transaction(&mut self, raw: &Value) -> Value {
+        let raw: Bytes = raw.as_str().and_then(|hex| hex.parse().ok()).unwrap_or_default();
+        let Some(tx) = decode_transaction(&raw) else {
+            return rpc_error(-32000, "invalid transaction");
+        };
+        if tx.tx.chain_id != self.chain_id {
+            return rpc_error(-32000, "invalid chain id");
+        }
+        if self.mined.contains_key(&tx.hash) || self.pool.iter().any(|pooled| pooled.hash == tx.hash) {
+            return rpc_error(-32000, "already known");
+        }
+        if tx.tx.nonce < self.nonce(tx.from) {
+            return rpc_error(-32000, "nonce too low");
+        }
+        if let Some(index) = self
+            .pool
+            .iter()
+            .position(|pooled| pooled.from == tx.from && pooled.tx.nonce == tx.tx.nonce)
+        {
+            // Like geth: a replacement must raise the fee cap by 10%
+            if tx.max_fee() * U256::from(100) < self.pool[index].max_fee() * U256::from(110) {
+                return rpc_error(-32000, "replacement transaction underpriced");
+            }
+            self.pool.remove(index);
+        }
+
+        let hash = tx.hash;
+        self.pool.push(tx);
+        if self.mining == Mining::Instant {
+            self.mine();
+        }
+        json!({"result": hash})
+    }
+
+    fn call(&self, call: &Value, estimate: bool) -> Value {
+        let to = parse_address(&call["to"]);
+        let data: Bytes = call["input"]
+            .as_str()
+            .or(call["data"].as_str())
+            .and_then(|hex| hex.parse().ok())
+            .unwrap_or_default();
+        if let Some(revert) = self.reverts.get(&to) {
+            return json!({"error": {"code": 3, "message": "execution reverted", "data": revert}});
+        }
+
+        if estimate {
+            let needed = if data.is_empty() { TRANSFER_GAS } else { self.gas_estimate };
+            let allowance = call["gas"].as_str().and_then(|gas| gas.parse::<U256>().ok());
+            return match allowance {
+                // Like a real node: the estimate is searched below the call's own gas
+                Some(gas) if gas < U256::from(needed) => {
+                    rpc_error(-32000, &format!("gas required exceeds allowance ({})", gas))
+                }
+                _ => json!({"result": format!("{:#x}", needed)}),
+            };
+        }
+
+        let word = |index: usize| data.get(4 + 32 * index + 12..4 + 32 * index + 32).map(Address::from_slice);
+        let output = match data.get(..4) {
+            Some(selector) if selector == BALANCE_OF_SELECTOR => word(0).map(|owner| self.token_balance(to, owner)),
+            Some(selector) if selector == ALLOWANCE_SELECTOR => match (word(0), word(1)) {
+                (Some(owner), Some(spender)) => {
+                    Some(self.allowances.get(&(to, owner, spender)).copied().unwrap_or_default())
+                }
+                _ => None,
+            },
+            _ => None,
+        };
+        match output {
+            Some(value) => json!({"result": format!("0x{}", hex::encode(value.to_be_bytes::<32>()))}),
+            None => json!({"result": "0x"}),
+        }
+    }
+
+    fn fee_history(&self, params: &Value) -> Value {
+        let count = params[0]
+            .as_str()
+            .and_then(|count| u64::from_str_radix(count.trim_start_matches("0x"), 16).ok())
+            .or(params[0].as_u64())
+            .unwrap_or(1)
+            .min(self.head);
+        let percentiles = params[2].as_array().map_or(0, |percentiles| percentiles.len());
+        let reward: Vec<Vec<String>> = (0..count)
+            .map(|_| vec![format!("{:#x}", self.priority_fee); percentiles])
+            .collect();
+        json!({"result": {
+            "oldestBlock": format!("{:#x}", self.head + 1 - count),
+            "baseFeePerGas": vec![format!("{:#x}", self.base_fee); count as usize + 1],
+            "gasUsedRatio": vec![0.5; count as usize],
+            "reward": reward,
+        }})
+    }
+
+    fn block(&self, number: u64) -> Value {
+        if number > self.head {
+            return Value::Null;
+        }
+        json!({
+            "number": format!("{:#x}", number),
+            "hash": self.block_hash(number),
+            "parentHash": if number == 0 { B256::ZERO } else { self.block_hash(number - 1) },
+            "timestamp": format!("{:#x}", number * 12),
+            "gasLimit": "0x1c9c380",
+            "gasUsed": "0x0",
+            "baseFeePerGas": format!("{:#x}", self.base_fee),
+        })
+    }
+
+    fn block_by_tag(&self, tag: &Value) -> Value {
+        match tag.as_str() {
+            Some("latest") | Some("pending") | Some("safe") | Some("finalized") | None => self.block(self.head),
+            Some("earliest") => self.block(0),
+            Some(number) => match u64::from_str_radix(number.trim_start_matches("0x"), 16) {
+                Ok(number) => self.block(number),
+                Err(_) => Value::Null,
+            },
+        }
+    }
+
+    fn transaction(&self, hash: B256) -> Value {
+        let (tx, block) = match (self.mined.get(&hash), self.pool.iter().find(|tx| tx.hash == hash)) {
+            (Some(mined), _) => (&mined.tx, Some(mined.block)),
+            (None, Some(pooled)) => (pooled, None),
+            (None, None) => return Value::Null,
+        };
+        json!({
+            "hash": tx.hash,
+            "nonce": format!("{:#x}", tx.tx.nonce),
+            "from": tx.from,
+            "to": tx.tx.to,
+            "value": format!("{:#x}", tx.tx.value),
+            "gas": format!("{:#x}", tx.tx.gas_limit),
+            "input": tx.tx.data,
+            "blockNumber": block.map(|number| format!("{:#x}", number)),
+            "blockHash": block.map(|number| self.block_hash(number)),
+        })
+    }
+
+    fn receipt(&self, hash: B256) -> Value {
+        let Some(mined) = self.mined.get(&hash) else {
+            return Value::Null;
+        };
+        json!({
+            "transactionHash": hash,
+            "blockNumber": format!("{:#x}", mined.block),
+            "blockHash": self.block_hash(mined.block),
+            "from": mined.tx.from,
+            "to": mined.tx.tx.to,
+            "status": if mined.success { "0x1" } else { "0x0" },
+            "gasUsed": format!("{:#x}", mined.gas_used),
+            "effectiveGasPrice": format!("{:#x}", mined.gas_price),
+            "logs": [],
+        })
+    }
+
+    fn respond(&mut self, request: &Value) -> Value {
+        self.requests.push(request.clone());
+        let method = request["method"].as_str().unwrap_or_default();
+        if let Some(reply) = self.replies.get(method) {
+            return reply.clone();
+        }
+        let params = &request["params"];
+        let result = match method {
+            "eth_chainId" => json!(format!("{:#x}", self.chain_id)),
+            "eth_blockNumber" => json!(format!("{:#x}", self.head)),
+            "eth_getBlockByNumber" => self.block_by_tag(&params[0]),
+            "eth_getBlockByHash" => {
+                let hash = parse_hash(&params[0]);
+                let number = u64::from_be_bytes(hash[24..].try_into().unwrap());
+                // Blocks of abandoned forks are gone
+                match self.block_hash(number) == hash {
+                    true => self.block(number),
+                    false => Value::Null,
+                }
+            }
+            "eth_gasPrice" => json!(format!("{:#x}", self.base_fee + self.priority_fee)),
+            "eth_maxPriorityFeePerGas" => json!(format!("{:#x}", self.priority_fee)),
+            "eth_feeHistory" => return self.fee_history(params),
+            "eth_getTransactionCount" => {
+                let address = parse_address(&params[0]);
+                let nonce = match params[1].as_str() {
+                    Some("pending") => self.pending_nonce(address),
+                    _ => self.nonce(address),
+                };
+                json!(format!("{:#x}", nonce))
+            }
+            "eth_getBalance" => json!(format!("{:#x}", self.balance(parse_address(&params[0])))),
+            "eth_getCode" => json!("0x"),
+            "eth_call" => return self.call(&params[0], false),
+            "eth_estimateGas" => return self.call(&params[0], true),
+            "eth_sendRawTransaction" => return self.send_raw_transaction(&params[0]),
+            "eth_getTransactionByHash" => self.transaction(parse_hash(&params[0])),
+            "eth_getTransactionReceipt" => self.receipt(parse_hash(&params[0])),
+            _ => return rpc_error(-32601, "method not found"),
+        };
+        json!({ "result": result })
+    }
+}
+
+/// Handle to a running mock node; clones share the same chain.
+#[derive(Clone)]
+pub struct MockNode {
+    pub url: String,
+    state: Arc<Mutex<NodeState>>,
+}
+
+impl MockNode {
+    pub async fn start(chain_id: u64) -> Self {
+        let state = Arc::new(Mutex::new(NodeState::new(chain_id)));
+        let served = state.clone();
+        let url = super::serve(move |request| {
+            let request = request.json();
+            let reply = served.lock().unwrap().respond(&request);
+            rpc_reply(&request, reply)
+        })
+        .await;
+        Self { url, state }
+    }
+
+    pub fn state(&self) -> MutexGuard<'_, NodeState> {
+        self.state.lock().unwrap()
+    }
+
+    /// Answers `request` as if it came over HTTP (e.g. from another mock in front of the node).
+    pub fn handle(&self, request: &Value) -> Response {
+        let reply = self.state().respond(request);
+        rpc_reply(request, reply)
+    }
+
+    pub fn mine(&self) {
+        self.state().mine();
+    }
+
+    /// Params of every `method` request received so far.
+    pub fn requests(&self, method: &str) -> Vec<Value> {
+        self.state()
+            .requests
+            .iter()
+            .filter(|request| request["method"] == method)
+            .map(|request| request["params"].clone())
+            .collect()
+    }
+}
+
+fn parse_address(value: &Value) -> Address {
+    value.as_str().and_then(|address| address.parse().ok()).unwrap_or_default()
+}
+
+fn parse_hash(value: &Value) -> B256 {
+    value.as_str().and_then(|hash| hash.parse().ok()).unwrap_or_default()
+}
+
+/// Decodes a raw legacy (EIP-155) or EIP-1559 envelope and recovers its sender.
+pub fn decode_transaction(raw: &[u8]) -> Option<NodeTx> {
+    let typed = *raw.first()? == 0x02;
+    let mut buf = if typed { &raw[1..] } else { raw };
+    let header = Header::decode(&mut buf).ok()?;
+    if !header.list {
+        return None;
+    }
+
+    let to = |buf: &mut &[u8]| -> Option<Option<Address>> {
+        let bytes = Bytes::decode(buf).ok()?;
+        match bytes.len() {
+            0 => Some(None),
+            20 => Some(Some(Address::from_slice(&bytes))),
+            _ => None,
+        }
+    };
+    let (tx, y_parity, r, s) = if typed {
+        let chain_id = u64::decode(&mut buf).ok()?;
+        let nonce = u64::decode(&mut buf).ok()?;
+        let max_priority_fee_per_gas = U256::decode(&mut buf).ok()?;
+        let max_fee_per_gas = U256::decode(&mut buf).ok()?;
+        let gas_limit = u64::decode(&mut buf).ok()?;
+        let to = to(&mut buf)?;
+        let value = U256::decode(&mut buf).ok()?;
+        let data = Bytes::decode(&mut buf).ok()?;
+        let access_list = Header::decode(&mut buf).ok()?;
+        buf = buf.get(access_list.payload_length..)?;
+        let y_parity = u8::decode(&mut buf).ok()? == 1;
+        let tx = TypedTransaction {
+            chain_id,
+            nonce,
+            gas_limit,
+            fees: TxFees::Eip1559 {
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+            },
+            to,
+            value,
+            data,
+        };
+        (tx, y_parity, U256::decode(&mut buf).ok()?, U256::decode(&mut buf).ok()?)
+    } else {
+        let nonce = u64::decode(&mut buf).ok()?;
+        let gas_price = U256::decode(&mut buf).ok()?;
+        let gas_limit = u64::decode(&mut buf).ok()?;
+        let to = to(&mut buf)?;
+        let value = U256::decode(&mut buf).ok()?;
+        let data = Bytes::decode(&mut buf).ok()?;
+        // EIP-155: v = chain_id * 2 + 35 + y_parity
+        let v = u64::decode(&mut buf).ok()?.checked_sub(35)?;
+        let tx = TypedTransaction {
+            chain_id: v / 2,
+            nonce,
+            gas_limit,
+            fees: TxFees::Legacy { gas_price },
+            to,
+            value,
+            data,
+        };
+        (tx, v % 2 == 1, U256::decode(&mut buf).ok()?, U256::decode(&mut buf).ok()?)
+    };
+
+    let signature = Signature::from_scalars(r.to_be_bytes::<32>(), s.to_be_bytes::<32>()).ok()?;
+    let recovery_id = RecoveryId::new(y_parity, false);
+    let key = VerifyingKey::recover_from_prehash(tx.signature_hash().as_slice(), &signature, recovery_id).ok()?;
+    let point = key.to_encoded_point(false);
+    let from = Address::from_slice(&keccak256(&point.as_bytes()[1..])[12..]);
+
+    Some(NodeTx {
+        hash: keccak256(raw),
+        from,
+        tx,
+    })
+}
@@ -0,0 +1,129 @@
+// Test program for signing and watch-only wallet accounts
+//
+// Parses WALLET_SECRETS_EVM into signing and watch-only accounts, checks that
+// a chain hands out signers only for keys it holds and refuses to send for a
+// watch-only address, and signs the EIP-712 example message. No network
+// access needed.
+//
+// Usage: cargo run --bin test_accounts
+
+mod support;
+
+use alloy_primitives::{address, b256, keccak256, Address, Bytes, U256};
+use worker::chain::rpc::EndpointConfig;
+use worker::chain::signer::Eip712Domain;
+use worker::chain::tx::{send_transaction, SendOptions, TxError, TxFees, TypedTransaction};
+use worker::chain::{ethereum, parse_wallet_secrets_evm, Chain, EvmChain, LocalSigner, Signer, WalletAccount};
+
+use support::check;
+
+/// Throwaway keys; hold no funds.
+const TEST_KEY: &str = "0x0123456789012345678901234567890123456789012345678901234567890123";
+const OTHER_KEY: &str = "0x4646464646464646464646464646464646464646464646464646464646464646";
+const WATCHED: Address = Address::repeat_byte(0x22);
+
+fn chain(accounts: Vec<WalletAccount>) -> EvmChain {
+    let mut config = ethereum::default_config();
+    // Never contacted: every case here fails or succeeds before the first request
+    config.rpc_endpoints = vec![EndpointConfig::new("http://127.0.0.1:9")];
+    EvmChain::new(config, accounts).expect("valid config")
+}
+
+fn kinds(accounts: &[WalletAccount]) -> Vec<(Address, bool)> {
+    accounts.iter().map(|account| (account.address(), account.is_watch_only())).collect()
+}
+
+#[tokio::main]
+async fn main() {
+    let mut passed = true;
+    let signer = LocalSigner::from_hex(TEST_KEY).expect("test key");
+    let other = LocalSigner::from_hex(OTHER_KEY).expect("test key");
+
+    // 1. Keys become signers, addresses watch-only accounts, anything else is skipped
+    std::env::set_var("WALLET_SECRETS_EVM", format!(" {:#x}, {} ,, not-a-key", WATCHED, TEST_KEY));
+    let accounts = parse_wallet_secrets_evm();
+    check(
+        &mut passed,
+        "WALLET_SECRETS_EVM",
+        accounts.as_ref().is_ok_and(|accounts| {
+            kinds(accounts) == [(WATCHED, true), (signer.address(), false)]
+                && accounts[0].signer().is_none()
+                && accounts[1].signer().is_some()
+        }),
+        format!("{:?}", accounts.as_ref().map(|accounts| kinds(accounts)).map_err(|e| e.to_string())),
+    );
+    std::env::set_var("WALLET_SECRETS_EVM", "not-a-key, 0x1234");
+    let invalid = parse_wallet_secrets_evm();
+    check(&mut passed, "No valid entries", invalid.is_err(), format!("{:?}", invalid.err().map(|e| e.to_string())));
+
+    // 2. The chain hands out signers only for signing accounts
+    let mixed = chain(accounts.unwrap_or_default());
+    let primary = mixed.primary_signer().map(|signer| signer.address());
+    check(
+        &mut passed,
+        "Signer lookup",
+        mixed.signer(WATCHED).is_none()
+            && mixed.signer(signer.address()).is_some()
+            && mixed.signer(other.address()).is_none()
+            && matches!(primary, Ok(address) if address == signer.address()),
+        format!("primary {:?}", primary.map_err(|e| e.to_string())),
+    );
+    let watch_only = chain(vec![WalletAccount::WatchOnly(WATCHED)]);
+    let primary = watch_only.primary_signer();
+    check(
+        &mut passed,
+        "Watch-only chain",
+        primary.is_err(),
+        format!("{:?}", primary.err().map(|e| e.to_string())),
+    );
+
+    // 3. Holding the key elsewhere does not let a watch-only account send
+    let watched_key = chain(vec![WalletAccount::WatchOnly(other.address())]);
+    let tx = TypedTransaction {
+        chain_id: ethereum::CHAIN_ID,
+        nonce: 0,
+        gas_limit: 21_000,
+        fees: TxFees::Legacy {
+            gas_price: U256::from(1_000_000_000u64),
+        },
+        to: Some(WATCHED),
+        value: U256::from(1),
+        data: Bytes::new(),
+    };
+    let refused = send_transaction(&watched_key, &other, &tx, &SendOptions::default()).await;
+    check(
+        &mut passed,
+        "Send from watch-only",
+        matches!(&refused, Err(TxError::InvalidRequest(reason)) if reason.contains("not a signing account")),
+        format!("{:?}", refused.err().map(|e| e.to_string())),
+    );
+
+    // 4. The EIP-712 example: Mail from Cow to Bob
+    let cow = LocalSigner::from_hex(&keccak256("cow").to_string()).expect("cow key");
+    let domain = Eip712Domain {
+        name: Some("Ether Mail".to_string()),
+        version: Some("1".to_string()),
+        chain_id: Some(1),
+        verifying_contract: Some(address!("CcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC")),
+        salt: None,
+    };
+    let mail_hash = b256!("c52c0ee5d84264471806290a3f2c4cecfc5490626bf912d01f240d7a274b371e");
+    let signature = cow.sign_typed_data(&domain, &mail_hash);
+    check(
+        &mut passed,
+        "EIP-712 example",
+        cow.address() == address!("CD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826")
+            && domain.separator() == b256!("f2cee375fa42b42143804025fc449deafd50cc031ca257e0b194a650a912090f")
+            && domain.signing_hash(&mail_hash) == b256!("be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2")
+            && signature.as_ref().is_ok_and(|signature| {
+                signature.r == U256::from_be_bytes(b256!("4355c47d63924e8a72e509b65029052eb6c299d53a04e167c5775fd466751c9d").0)
+                    && signature.s == U256::from_be_bytes(b256!("07299936d304c153f6443dfa05f40ff007d72911b6f72307f996231605b91562").0)
+                    && signature.y_parity
+            }),
+        format!("{:?}", signature.map_err(|e| e.to_string())),
+    );
+
+    if !passed {
+        std::process::exit(1);
+    }
+}
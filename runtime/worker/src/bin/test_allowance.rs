@@ -0,0 +1,155 @@
+// Test program for ERC-20 approvals before a route step
+//
+// Runs ensure_allowance against a mock node and checks that nothing is sent
+// for native tokens or a sufficient allowance, that exact and infinite modes
+// approve the right amount, that a nonzero allowance is reset to zero first
+// (as USDT requires), that approvals are recorded in the ledger and that the
+// inventory checks the known Li.Fi spenders for every recorded token. No
+// network access needed.
+//
+// Usage: cargo run --bin test_allowance
+
+mod support;
+
+use std::sync::Arc;
+
+use alloy_primitives::{Address, Bytes, U256};
+use worker::chain::rpc::EndpointConfig;
+use worker::chain::{ethereum, EvmChain, LocalSigner, Signer, WalletAccount};
+use worker::module::erc20::approvals::{self, ApprovalLedger};
+use worker::module::erc20::{self, ApprovalMode, NATIVE_TOKEN_PLACEHOLDER};
+use worker::module::lifi::{KNOWN_SPENDERS, LIFI_DIAMOND};
+
+use support::check;
+use support::node::{decode_transaction, MockNode};
+
+/// Throwaway key; holds no funds.
+const TEST_KEY: &str = "0x0123456789012345678901234567890123456789012345678901234567890123";
+const TOKEN: Address = Address::repeat_byte(0x70);
+const SPENDER: Address = Address::repeat_byte(0x5e);
+
+/// `approve` amounts broadcast since the `from`th raw transaction.
+fn approvals_sent(node: &MockNode, from: usize) -> Vec<U256> {
+    node.requests("eth_sendRawTransaction")[from..]
+        .iter()
+        .filter_map(|params| params[0].as_str().and_then(|raw| raw.parse::<Bytes>().ok()))
+        .filter_map(|raw| decode_transaction(&raw))
+        .filter(|tx| tx.tx.to == Some(TOKEN) && tx.tx.data.get(..4) == Some(&[0x09, 0x5e, 0xa7, 0xb3][..]))
+        .map(|tx| U256::from_be_slice(&tx.tx.data[36..68]))
+        .collect()
+}
+
+#[tokio::main]
+async fn main() {
+    let mut passed = true;
+    let node = MockNode::start(ethereum::CHAIN_ID).await;
+    let signer: Arc<dyn Signer> = Arc::new(LocalSigner::from_hex(TEST_KEY).expect("test key"));
+    let owner = signer.address();
+    node.state().balances.insert(owner, U256::from(10u64).pow(U256::from(18)));
+
+    let mut config = ethereum::default_config();
+    config.rpc_endpoints = vec![EndpointConfig::new(&node.url)];
+    config.confirmations = 1;
+    let chain = EvmChain::new(config, vec![WalletAccount::Signer(signer.clone())]).expect("valid config");
+
+    let dir = std::env::temp_dir().join(format!("test_allowance_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("create temp dir");
+    let ledger_path = dir.join("approvals.jsonl");
+    std::env::set_var("APPROVAL_LEDGER_PATH", &ledger_path);
+    let ledger = ApprovalLedger::new(&ledger_path);
+    let allowance = || node.state().allowances.get(&(TOKEN, owner, SPENDER)).copied().unwrap_or_default();
+
+    // 1. Native tokens and zero amounts need no approval
+    let native = erc20::ensure_allowance(&chain, signer.as_ref(), NATIVE_TOKEN_PLACEHOLDER, SPENDER, U256::from(1), ApprovalMode::Exact).await;
+    let zero = erc20::ensure_allowance(&chain, signer.as_ref(), TOKEN, SPENDER, U256::ZERO, ApprovalMode::Exact).await;
+    check(
+        &mut passed,
+        "Nothing to approve",
+        native.as_ref().is_ok_and(|receipts| receipts.is_empty())
+            && zero.as_ref().is_ok_and(|receipts| receipts.is_empty())
+            && node.requests("eth_sendRawTransaction").is_empty(),
+        format!("{:?}; {:?}", native.map(|r| r.len()).map_err(|e| e.to_string()), zero.map(|r| r.len()).map_err(|e| e.to_string())),
+    );
+
+    // 2. Exact mode approves the required amount and records it
+    let exact = erc20::ensure_allowance(&chain, signer.as_ref(), TOKEN, SPENDER, U256::from(1_000), ApprovalMode::Exact).await;
+    let entries = ledger.entries().map_err(|e| e.to_string());
+    check(
+        &mut passed,
+        "Exact approval",
+        exact.as_ref().is_ok_and(|receipts| receipts.len() == 1)
+            && approvals_sent(&node, 0) == [U256::from(1_000)]
+            && allowance() == U256::from(1_000)
+            && entries.as_ref().is_ok_and(|entries| {
+                entries.len() == 1
+                    && entries[0].spender == SPENDER
+                    && entries[0].owner == owner
+                    && entries[0].amount == U256::from(1_000)
+            }),
+        format!("{:?}, allowance {}, ledger {:?}", exact.map(|r| r.len()).map_err(|e| e.to_string()), allowance(), entries.map(|e| e.len())),
+    );
+
+    // 3. A sufficient allowance is left alone
+    let sent = node.requests("eth_sendRawTransaction").len();
+    let enough = erc20::ensure_allowance(&chain, signer.as_ref(), TOKEN, SPENDER, U256::from(600), ApprovalMode::Infinite).await;
+    check(
+        &mut passed,
+        "Sufficient allowance",
+        enough.as_ref().is_ok_and(|receipts| receipts.is_empty()) && approvals_sent(&node, sent).is_empty(),
+        format!("{:?}", enough.map(|r| r.len()).map_err(|e| e.to_string())),
+    );
+
+    // 4. A nonzero allowance that is too low is reset to zero before the infinite approval
+    let infinite = erc20::ensure_allowance(&chain, signer.as_ref(), TOKEN, SPENDER, U256::from(5_000), ApprovalMode::Infinite).await;
+    let entries = ledger.entries().map_err(|e| e.to_string());
+    check(
+        &mut passed,
+        "Reset then infinite approval",
+        infinite.as_ref().is_ok_and(|receipts| receipts.len() == 2)
+            && approvals_sent(&node, sent) == [U256::ZERO, U256::MAX]
+            && allowance() == U256::MAX
+            && entries.as_ref().is_ok_and(|entries| entries.len() == 2 && entries[1].amount == U256::MAX),
+        format!("sent {:?}, allowance {}, ledger {:?}", approvals_sent(&node, sent), allowance(), entries.map(|e| e.len())),
+    );
+
+    // 5. Route actions approve exactly unless they ask for infinite approval
+    check(
+        &mut passed,
+        "Mode from route action",
+        ApprovalMode::from_infinite_approval(None) == ApprovalMode::Exact
+            && ApprovalMode::from_infinite_approval(Some(false)) == ApprovalMode::Exact
+            && ApprovalMode::from_infinite_approval(Some(true)) == ApprovalMode::Infinite,
+        format!("{:?}", ApprovalMode::from_infinite_approval(Some(true))),
+    );
+
+    // 6. The inventory also checks the Li.Fi Diamond for every recorded token, even
+    //    when the approval predates the ledger
+    node.state().allowances.insert((TOKEN, owner, LIFI_DIAMOND), U256::from(77));
+    let recorded = ledger.entries().unwrap_or_default();
+    let candidates = approvals::approval_candidates(&chain, &[owner], &recorded, KNOWN_SPENDERS, 0).await;
+    let open = match &candidates {
+        Ok(candidates) => approvals::scan_allowances(&chain, candidates).await.map_err(|e| e.to_string()),
+        Err(e) => Err(e.to_string()),
+    };
+    check(
+        &mut passed,
+        "Known spenders",
+        candidates.as_ref().is_ok_and(|candidates| {
+            candidates.contains(&(TOKEN, owner, SPENDER)) && candidates.contains(&(TOKEN, owner, LIFI_DIAMOND))
+        }) && open.as_ref().is_ok_and(|open| {
+            open.iter()
+                .any(|allowance| allowance.spender == LIFI_DIAMOND && allowance.amount == U256::from(77))
+        }),
+        format!(
+            "{:?}; open {:?}",
+            candidates.map(|c| c.len()).map_err(|e| e.to_string()),
+            open.map(|open| open.iter().map(|a| (a.spender, a.amount)).collect::<Vec<_>>())
+        ),
+    );
+
+    let _ = std::fs::remove_dir_all(&dir);
+    if !passed {
+        std::process::exit(1);
+    }
+}
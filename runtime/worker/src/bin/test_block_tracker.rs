@@ -0,0 +1,189 @@
+// Test program for the reorg-aware block tracker
+//
+// Starts a local mock JSON-RPC node whose chain can be rewritten between
+// polls, then checks the events and canonical window of a BlockTracker, and
+// that a chain without WebSocket endpoints polls for its head.
+// No network access needed.
+//
+// Usage: cargo run --bin test_block_tracker
+
+mod support;
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use alloy_primitives::B256;
+use worker::chain::rpc::{EndpointConfig, RpcClient, TransactionReceipt};
+use worker::chain::{ethereum, BlockEvent, BlockTracker, Chain, EvmChain};
+
+use support::check;
+
+/// Canonical chain served by the mock node: block hashes indexed by number.
+/// Every fork that ever existed stays fetchable by hash, like on a real node.
+#[derive(Default)]
+struct MockChain {
+    canonical: Vec<B256>,
+    parents: Vec<(B256, u64, B256)>,
+}
+
+impl MockChain {
+    /// Replaces everything above `from - 1` with `count` blocks of fork `fork`.
+    fn build(&mut self, from: u64, count: u64, fork: u8) {
+        self.canonical.truncate(from as usize);
+        for number in from..from + count {
+            let mut hash = B256::ZERO;
+            hash[0] = fork;
+            hash[31] = number as u8;
+            let parent = if number == 0 { B256::ZERO } else { self.canonical[number as usize - 1] };
+            self.canonical.push(hash);
+            self.parents.push((hash, number, parent));
+        }
+    }
+
+    fn block_json(&self, hash: B256) -> serde_json::Value {
+        match self.parents.iter().find(|(h, _, _)| *h == hash) {
+            Some((hash, number, parent)) => serde_json::json!({
+                "number": format!("{:#x}", number),
+                "hash": format!("{:#x}", hash),
+                "parentHash": format!("{:#x}", parent),
+                "timestamp": "0x0",
+                "gasLimit": "0x1c9c380",
+                "gasUsed": "0x0",
+            }),
+            None => serde_json::Value::Null,
+        }
+    }
+}
+
+async fn spawn_mock_node(chain: Arc<Mutex<MockChain>>) -> String {
+    support::serve(move |request| {
+        let request = request.json();
+        let chain = chain.lock().unwrap();
+        let result = match request["method"].as_str() {
+            Some("eth_getBlockByNumber") => chain.block_json(*chain.canonical.last().unwrap()),
+            Some("eth_getBlockByHash") => {
+                let hash: B256 = request["params"][0].as_str().unwrap_or_default().parse().unwrap_or_default();
+                chain.block_json(hash)
+            }
+            _ => serde_json::Value::Null,
+        };
+        support::rpc_reply(&request, serde_json::json!({"result": result}))
+    })
+    .await
+}
+
+fn drain(events: &mut tokio::sync::broadcast::Receiver<BlockEvent>) -> Vec<BlockEvent> {
+    let mut drained = Vec::new();
+    while let Ok(event) = events.try_recv() {
+        drained.push(event);
+    }
+    drained
+}
+
+#[tokio::main]
+async fn main() {
+    let mut passed = true;
+    let chain = Arc::new(Mutex::new(MockChain::default()));
+    chain.lock().unwrap().build(0, 10, 0xa);
+    let url = spawn_mock_node(chain.clone()).await;
+    let rpc = RpcClient::new(&url);
+    let tracker = BlockTracker::new(3);
+    let mut events = tracker.subscribe();
+
+    // 1. First poll seeds the window with the head only
+    tracker.poll(&rpc).await.expect("poll");
+    let seeded = drain(&mut events);
+    check(
+        &mut passed,
+        "Initial head",
+        tracker.head().map(|(number, _)| number) == Some(9) && seeded.len() == 1,
+        format!("head {:?}, {} event(s)", tracker.head().map(|(n, _)| n), seeded.len()),
+    );
+
+    // 2. Skipped blocks are backfilled through parent hashes
+    chain.lock().unwrap().build(10, 3, 0xa);
+    tracker.poll(&rpc).await.expect("poll");
+    let new_blocks: Vec<u64> = drain(&mut events)
+        .iter()
+        .filter_map(|event| match event {
+            BlockEvent::NewBlock { number, .. } => Some(*number),
+            _ => None,
+        })
+        .collect();
+    check(
+        &mut passed,
+        "Gap backfill",
+        new_blocks == [10, 11, 12],
+        format!("new blocks {:?}", new_blocks),
+    );
+
+    // 3. A two-block reorg is reported with the orphaned hashes
+    let old_11 = tracker.canonical_hash(11).unwrap();
+    let old_12 = tracker.canonical_hash(12).unwrap();
+    chain.lock().unwrap().build(11, 3, 0xb);
+    tracker.poll(&rpc).await.expect("poll");
+    let reorg = drain(&mut events);
+    check(
+        &mut passed,
+        "Reorg detection",
+        reorg.first()
+            == Some(&BlockEvent::Reorg {
+                common_ancestor: 10,
+                orphaned: vec![(11, old_11), (12, old_12)],
+            })
+            && reorg.len() == 4,
+        format!("{:?}", reorg.first()),
+    );
+
+    // 4. Receipts on orphaned blocks are recognised
+    let receipt: TransactionReceipt = serde_json::from_value(serde_json::json!({
+        "transactionHash": format!("{:#x}", B256::repeat_byte(1)),
+        "blockNumber": "0xc",
+        "blockHash": format!("{:#x}", old_12),
+        "from": "0x0000000000000000000000000000000000000001",
+        "to": null,
+        "status": "0x1",
+        "gasUsed": "0x5208",
+    }))
+    .unwrap();
+    check(
+        &mut passed,
+        "Orphaned receipt",
+        reorg[0].orphans(&receipt) && tracker.is_canonical(12, old_12) == Some(false),
+        format!("canonical at 12: {:?}", tracker.canonical_hash(12)),
+    );
+
+    // 5. Confirmation depth follows the new head
+    check(
+        &mut passed,
+        "Confirmation depth",
+        tracker.depth(11) == 3 && tracker.is_final(11) && !tracker.is_final(12),
+        format!("depth of 11: {}, of 12: {}", tracker.depth(11), tracker.depth(12)),
+    );
+
+    // 6. Re-polling the same head emits nothing
+    tracker.poll(&rpc).await.expect("poll");
+    let repeated = drain(&mut events);
+    check(&mut passed, "Idempotent poll", repeated.is_empty(), format!("{} event(s)", repeated.len()));
+
+    // 7. Without ws_urls, run polls the head once per block time
+    let mut config = ethereum::default_config();
+    config.rpc_endpoints = vec![EndpointConfig::new(&url)];
+    config.ws_urls = Vec::new();
+    config.block_time = Duration::from_millis(50);
+    let polled = EvmChain::new(config, Vec::new()).expect("valid config");
+    let _ = tokio::time::timeout(Duration::from_millis(200), polled.blocks().run(&polled)).await;
+    chain.lock().unwrap().build(14, 2, 0xb);
+    let _ = tokio::time::timeout(Duration::from_millis(200), polled.blocks().run(&polled)).await;
+    let head = *chain.lock().unwrap().canonical.last().unwrap();
+    check(
+        &mut passed,
+        "Polling without WebSocket",
+        polled.subscriptions().is_none() && polled.blocks().head() == Some((15, head)),
+        format!("head {:?}", polled.blocks().head()),
+    );
+
+    if !passed {
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,192 @@
+// Test program for chain id consistency checks
+//
+// Starts local mock JSON-RPC servers that report a fixed eth_chainId and checks
+// the startup verification, the send path guard and the Li.Fi response check
+// against an Ethereum mainnet chain. No network access needed.
+//
+// Usage: cargo run --bin test_chain_id
+
+mod support;
+
+use std::sync::Arc;
+
+use alloy_primitives::{Address, Bytes, U256};
+use serde_json::{json, Value};
+use worker::chain::rpc::EndpointConfig;
+use worker::chain::tx::{send_transaction, SendOptions, TxError, TxFees, TypedTransaction};
+use worker::chain::{self, ethereum, EvmChain, LocalSigner, Signer, WalletAccount};
+use worker::module::lifi::build_tx::TransactionResponse;
+
+use support::check;
+
+/// Throwaway keys; hold no funds.
+const TEST_KEY: &str = "0x0123456789012345678901234567890123456789012345678901234567890123";
+const OTHER_KEY: &str = "0x0000000000000000000000000000000000000000000000000000000000000001";
+
+async fn spawn_mock(chain_id: u64) -> String {
+    support::serve(move |request| {
+        let request = request.json();
+        let reply = match request["method"].as_str() {
+            Some("eth_chainId") => json!({"result": format!("{:#x}", chain_id)}),
+            _ => support::rpc_error(-32601, "method not found"),
+        };
+        support::rpc_reply(&request, reply)
+    })
+    .await
+}
+
+fn mock_chain(urls: &[&str], signer: Arc<dyn Signer>) -> EvmChain {
+    let mut config = ethereum::default_config();
+    config.rpc_endpoints = urls.iter().map(|url| EndpointConfig::new(url)).collect();
+    EvmChain::new(config, vec![WalletAccount::Signer(signer)]).expect("valid config")
+}
+
+fn token(chain_id: u64) -> Value {
+    json!({
+        "address": "0x0000000000000000000000000000000000000000",
+        "chainId": chain_id,
+        "symbol": "ETH",
+        "decimals": 18,
+        "name": "ETH",
+        "coinKey": "ETH",
+        "logoURI": "",
+        "priceUSD": "0"
+    })
+}
+
+/// Minimal stepTransaction response for a swap from `from_chain` to `to_chain`.
+fn lifi_response(chain_id: u64, from_chain: u64, to_chain: u64, from: Address) -> TransactionResponse {
+    let tool = json!({"key": "mock", "name": "Mock", "logoURI": ""});
+    let estimate = json!({
+        "fromAmount": "1",
+        "toAmount": "1",
+        "toAmountMin": "1",
+        "tool": "mock",
+        "approvalAddress": "0x0000000000000000000000000000000000000000",
+        "executionDuration": 0,
+        "feeCosts": [],
+        "gasCosts": []
+    });
+    serde_json::from_value(json!({
+        "type": "lifi",
+        "id": "test",
+        "tool": "mock",
+        "toolDetails": tool,
+        "action": {
+            "fromToken": token(from_chain),
+            "fromAmount": "1",
+            "toToken": token(to_chain),
+            "fromChainId": from_chain,
+            "toChainId": to_chain,
+            "slippage": 0.005
+        },
+        "estimate": estimate,
+        "includedSteps": [],
+        "integrator": "test",
+        "referrer": "",
+        "transactionRequest": {
+            "data": "0x",
+            "to": "0x0000000000000000000000000000000000000001",
+            "value": "0x0",
+            "from": format!("{:#x}", from),
+            "chainId": chain_id,
+            "gasLimit": "0x5208",
+            "gasPrice": "0x1"
+        }
+    }))
+    .expect("valid Li.Fi fixture")
+}
+
+#[tokio::main]
+async fn main() {
+    let mut passed = true;
+    let signer: Arc<dyn Signer> = Arc::new(LocalSigner::from_hex(TEST_KEY).expect("test key"));
+    let other: Arc<dyn Signer> = Arc::new(LocalSigner::from_hex(OTHER_KEY).expect("other key"));
+    let mainnet = spawn_mock(ethereum::CHAIN_ID).await;
+    let arbitrum = spawn_mock(42161).await;
+
+    // 1. Every endpoint serves the configured chain
+    let chain = mock_chain(&[&mainnet, &mainnet], signer.clone());
+    let verified = chain::verify_chain_id(&chain).await;
+    check(&mut passed, "Matching endpoints", verified.is_ok(), format!("{:?}", verified.err()));
+
+    // 2. One endpoint points at another network
+    let mixed = mock_chain(&[&mainnet, &arbitrum], signer.clone());
+    let verified = chain::verify_chain_id(&mixed).await;
+    check(
+        &mut passed,
+        "Mismatching endpoint",
+        verified.as_ref().is_err_and(|e| e.to_string().contains(&arbitrum) && !e.to_string().contains(&mainnet)),
+        verified.err().map(|e| e.to_string()).unwrap_or_default(),
+    );
+
+    // 3. Unreachable endpoints are tolerated only if another one confirms the id
+    let unreachable = "http://127.0.0.1:1";
+    let partly = chain::verify_chain_id(&mock_chain(&[unreachable, &mainnet], signer.clone())).await;
+    let nothing = chain::verify_chain_id(&mock_chain(&[unreachable], signer.clone())).await;
+    check(
+        &mut passed,
+        "Unreachable endpoints",
+        partly.is_ok() && nothing.is_err(),
+        nothing.err().map(|e| e.to_string()).unwrap_or_default(),
+    );
+
+    // 4. The send path refuses other chains and foreign signers before touching the RPC
+    let tx = TypedTransaction {
+        chain_id: ethereum::CHAIN_ID,
+        nonce: 0,
+        gas_limit: 21_000,
+        fees: TxFees::Legacy { gas_price: U256::from(1) },
+        to: Some(Address::repeat_byte(1)),
+        value: U256::ZERO,
+        data: Bytes::new(),
+    };
+    let wrong_chain = send_transaction(
+        &chain,
+        signer.as_ref(),
+        &TypedTransaction { chain_id: 42161, ..tx.clone() },
+        &SendOptions::default(),
+    )
+    .await;
+    let foreign_signer = send_transaction(&chain, other.as_ref(), &tx, &SendOptions::default()).await;
+    check(
+        &mut passed,
+        "Send path guard",
+        matches!(wrong_chain, Err(TxError::ChainMismatch { expected: 42161, actual: 1, .. }))
+            && matches!(foreign_signer, Err(TxError::InvalidRequest(_))),
+        format!(
+            "{}; {}",
+            wrong_chain.err().map(|e| e.to_string()).unwrap_or_default(),
+            foreign_signer.err().map(|e| e.to_string()).unwrap_or_default()
+        ),
+    );
+
+    // 5. Li.Fi responses must match the sending chain, the signer and their own token chains
+    let from = signer.address();
+    let same_chain = lifi_response(1, 1, 1, from).verify_for(&chain, from);
+    let cross_chain = lifi_response(1, 1, 42161, from).verify_for(&chain, from);
+    let other_request_chain = lifi_response(42161, 1, 1, from).verify_for(&chain, from);
+    let other_from = lifi_response(1, 1, 1, other.address()).verify_for(&chain, from);
+    let mut token_mismatch = lifi_response(1, 1, 42161, from);
+    token_mismatch.action.to_token.chain_id = 10;
+    let token_mismatch = token_mismatch.verify_for(&chain, from);
+    check(
+        &mut passed,
+        "Li.Fi response check",
+        same_chain.is_ok()
+            && cross_chain.is_ok()
+            && matches!(other_request_chain, Err(TxError::ChainMismatch { actual: 42161, .. }))
+            && matches!(other_from, Err(TxError::SenderMismatch { .. }))
+            && matches!(token_mismatch, Err(TxError::ChainMismatch { expected: 42161, actual: 10, .. })),
+        format!(
+            "{:?}; {:?}; {:?}",
+            other_request_chain.err().map(|e| e.to_string()),
+            other_from.err().map(|e| e.to_string()),
+            token_mismatch.err().map(|e| e.to_string())
+        ),
+    );
+
+    if !passed {
+        std::process::exit(1);
+    }
+}
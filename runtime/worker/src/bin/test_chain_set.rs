@@ -0,0 +1,90 @@
+// Test program for the set of enabled chains
+//
+// Builds a ChainSet from offline EvmChains and checks lookups by id and by
+// `[chains.<key>]` name, the error for chains that are not enabled, and that
+// the primary chain cannot be replaced. No network access needed.
+//
+// Usage: cargo run --bin test_chain_set
+
+mod support;
+
+use std::sync::Arc;
+
+use worker::chain::rpc::EndpointConfig;
+use worker::chain::{ethereum, hyperevm, Chain, ChainSet, EvmChain};
+
+use support::check;
+
+const BASE_CHAIN_ID: u64 = 8453;
+
+fn chain(key: &str, chain_id: u64) -> Arc<dyn Chain> {
+    let mut config = ethereum::default_config();
+    config.key = key.to_string();
+    config.chain_id = chain_id;
+    // Never contacted: the set only reads chain configs
+    config.rpc_endpoints = vec![EndpointConfig::new("http://127.0.0.1:9")];
+    Arc::new(EvmChain::new(config, Vec::new()).expect("valid config"))
+}
+
+fn key(chain: Option<Arc<dyn Chain>>) -> Option<String> {
+    chain.map(|chain| chain.config().key.clone())
+}
+
+fn main() {
+    let mut passed = true;
+
+    // 1. A new set holds only the primary chain
+    let mut chains = ChainSet::new(chain("ethereum", ethereum::CHAIN_ID));
+    check(
+        &mut passed,
+        "Primary only",
+        chains.len() == 1
+            && chains.chain_ids() == [ethereum::CHAIN_ID]
+            && chains.primary().config().key == "ethereum"
+            && !chains.contains(hyperevm::CHAIN_ID),
+        format!("{:?}", chains.chain_ids()),
+    );
+
+    // 2. Chains are kept in chain id order and found by id or key
+    chains.insert(chain("base", BASE_CHAIN_ID));
+    chains.insert(chain("hyperevm", hyperevm::CHAIN_ID));
+    check(
+        &mut passed,
+        "Lookup",
+        chains.chain_ids() == [ethereum::CHAIN_ID, hyperevm::CHAIN_ID, BASE_CHAIN_ID]
+            && key(chains.get(hyperevm::CHAIN_ID)).as_deref() == Some("hyperevm")
+            && key(chains.by_key("base")).as_deref() == Some("base")
+            && chains.by_key("arbitrum").is_none()
+            && chains.get(42_161).is_none(),
+        format!("{:?}", chains.iter().map(|chain| chain.config().key.clone()).collect::<Vec<_>>()),
+    );
+
+    // 3. require names the missing chain and how to enable it
+    let missing = chains.require(42_161);
+    let found = chains.require(BASE_CHAIN_ID);
+    check(
+        &mut passed,
+        "Require",
+        matches!(&missing, Err(e) if e.to_string().contains("42161") && e.to_string().contains("enabled_chains"))
+            && found.is_ok_and(|chain| chain.config().chain_id == BASE_CHAIN_ID),
+        format!("{:?}", missing.err().map(|e| e.to_string())),
+    );
+
+    // 4. Other chains can be replaced, the primary one cannot
+    chains.insert(chain("base_archive", BASE_CHAIN_ID));
+    chains.insert(chain("ethereum_backup", ethereum::CHAIN_ID));
+    check(
+        &mut passed,
+        "Replace",
+        chains.len() == 3
+            && key(chains.get(BASE_CHAIN_ID)).as_deref() == Some("base_archive")
+            && chains.by_key("base").is_none()
+            && chains.primary().config().key == "ethereum"
+            && chains.by_key("ethereum_backup").is_none(),
+        format!("{:?}", chains.iter().map(|chain| chain.config().key.clone()).collect::<Vec<_>>()),
+    );
+
+    if !passed {
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,241 @@
+// Test program for config.toml parsing and the built-in chain presets
+//
+// Loads a config without [chains] sections to get the ethereum/hyperevm
+// presets, then one that overrides preset fields, adds a chain and contains
+// invalid chain sections (missing keys, values that do not fit their type,
+// negative or non-finite fee caps), sets a [lifi] section and checks what Config
+// ends up with. No network access needed.
+//
+// Usage: cargo run --bin test_config
+
+mod support;
+#[path = "../di.rs"]
+#[allow(dead_code)]
+mod di;
+
+use std::time::Duration;
+
+use alloy_primitives::U256;
+use worker::chain::gas::FeeStrategy;
+use worker::chain::rpc::EndpointConfig;
+use worker::chain::{ethereum, hyperevm, ChainConfig};
+use worker::module::lifi::LifiConfig;
+
+use di::Config;
+use support::check;
+
+const TOP_LEVEL: &str = r#"
+log_level = "info"
+strategy_type = "atomic_arb"
+chain_type = "ethereum"
+environment_type = "local"
+"#;
+
+const CONFIG: &str = r#"
+log_level = "info"
+strategy_type = "atomic_arb"
+chain_type = "hyperevm"
+environment_type = "local"
+enabled_chains = ["ethereum", "base"]
+
+[chains.ethereum]
+rpc_urls = ["http://127.0.0.1:8545", { url = "http://127.0.0.1:8546", rate_limit = 5 }]
+confirmations = 3
+fee_strategy = "fast"
+max_fee_per_gas_gwei = 1.5
+
+[chains.hyperevm]
+hypercore_api_url = ""
+big_block_time_ms = 30000
+
+[chains.base]
+chain_id = 8453
+rpc_url = "http://127.0.0.1:8547"
+fee_strategy = 75
+multicall_address = ""
+
+[chains.no_chain_id]
+rpc_url = "http://127.0.0.1:8548"
+
+[chains.negative_chain_id]
+chain_id = -1
+rpc_url = "http://127.0.0.1:8548"
+
+[chains.no_rpc]
+chain_id = 10
+
+[chains.bad_multicall]
+chain_id = 11
+rpc_url = "http://127.0.0.1:8548"
+multicall_address = "0x1234"
+
+[chains.big_decimals]
+chain_id = 12
+rpc_url = "http://127.0.0.1:8548"
+native_token = { symbol = "BIG", decimals = 256 }
+
+[chains.negative_block_time]
+chain_id = 13
+rpc_url = "http://127.0.0.1:8548"
+block_time_ms = -1
+
+[chains.huge_rate_limit]
+chain_id = 14
+rpc_urls = [{ url = "http://127.0.0.1:8548", rate_limit = 4294967296 }]
+
+[chains.negative_fee_cap]
+chain_id = 15
+rpc_url = "http://127.0.0.1:8548"
+max_fee_per_gas_gwei = -1.5
+
+[chains.infinite_fee_cap]
+chain_id = 16
+rpc_url = "http://127.0.0.1:8548"
+max_priority_fee_per_gas_gwei = inf
+
+[chains.nan_fee_cap]
+chain_id = 17
+rpc_url = "http://127.0.0.1:8548"
+max_fee_per_gas_gwei = nan
+
+[lifi]
+timeout_ms = 5000
+max_retries = 0
+max_key_wait_ms = 500
+"#;
+
+fn load(name: &str, contents: &str) -> Config {
+    let path = std::env::temp_dir().join(format!("test_config_{}_{}.toml", name, std::process::id()));
+    std::fs::write(&path, contents).expect("write config");
+    let config = Config::from_file(path.to_str().expect("utf-8 path"));
+    let _ = std::fs::remove_file(&path);
+    config
+}
+
+fn keys(config: &Config) -> Vec<&str> {
+    config.chains.iter().map(|chain| chain.key.as_str()).collect()
+}
+
+fn summary(chain: Option<&ChainConfig>) -> String {
+    match chain {
+        Some(chain) => format!(
+            "{} id {}, {} endpoints, {} confirmations, {:?}",
+            chain.key,
+            chain.chain_id,
+            chain.rpc_endpoints.len(),
+            chain.confirmations,
+            chain.fee.strategy
+        ),
+        None => "missing".to_string(),
+    }
+}
+
+fn main() {
+    let mut passed = true;
+
+    // 1. Without [chains] sections: both presets as built in
+    let defaults = load("presets", TOP_LEVEL);
+    let ethereum = defaults.chain("ethereum");
+    let hyperevm = defaults.chain("hyperevm");
+    check(
+        &mut passed,
+        "Presets",
+        defaults.chain_type == "ethereum"
+            && keys(&defaults) == ["ethereum", "hyperevm"]
+            && defaults.enabled_chains.is_empty()
+            && ethereum.is_some_and(|chain| {
+                chain.chain_id == ethereum::CHAIN_ID && chain.confirmations == 12 && chain.dual_blocks.is_none()
+            })
+            && hyperevm.is_some_and(|chain| {
+                chain.chain_id == hyperevm::CHAIN_ID
+                    && chain.native_token.symbol == "HYPE"
+                    && chain.hypercore_api_url.is_some()
+                    && chain.dual_blocks.is_some()
+            }),
+        format!("{:?}: {}; {}", keys(&defaults), summary(ethereum), summary(hyperevm)),
+    );
+
+    let config = load("overrides", CONFIG);
+
+    // 2. Overridden keys replace the preset's, the rest is kept
+    let ethereum = config.chain("ethereum");
+    check(
+        &mut passed,
+        "Preset overrides",
+        ethereum.is_some_and(|chain| {
+            chain.chain_id == ethereum::CHAIN_ID
+                && chain.rpc_endpoints
+                    == [
+                        EndpointConfig::new("http://127.0.0.1:8545"),
+                        EndpointConfig {
+                            url: "http://127.0.0.1:8546".to_string(),
+                            rate_limit: Some(5),
+                        },
+                    ]
+                && chain.ws_urls == ethereum::default_config().ws_urls
+                && chain.confirmations == 3
+                && chain.fee.strategy == FeeStrategy::Fast
+                && chain.fee.max_fee_cap == Some(U256::from(1_500_000_000u64))
+                && chain.lifi_key.as_deref() == Some("eth")
+        }),
+        summary(ethereum),
+    );
+    let hyperevm = config.chain("hyperevm");
+    check(
+        &mut passed,
+        "HyperEVM overrides",
+        hyperevm.is_some_and(|chain| {
+            chain.hypercore_api_url.is_none()
+                && chain.dual_blocks.as_ref().is_some_and(|blocks| {
+                    blocks.big_block_time == Duration::from_secs(30) && blocks.small_block_gas_limit == 2_000_000
+                })
+        }),
+        format!("{:?}", hyperevm.map(|chain| (&chain.hypercore_api_url, &chain.dual_blocks))),
+    );
+
+    // 3. A chain without a preset gets generic defaults
+    let base = config.chain("base");
+    check(
+        &mut passed,
+        "New chain",
+        config.chain_type == "hyperevm"
+            && config.enabled_chains == ["ethereum", "base"]
+            && base.is_some_and(|chain| {
+                chain.chain_id == 8453
+                    && chain.name == "base"
+                    && chain.rpc_endpoints == [EndpointConfig::new("http://127.0.0.1:8547")]
+                    && chain.native_token.symbol == "ETH"
+                    && chain.confirmations == 1
+                    && chain.fee.strategy == FeeStrategy::Percentile(75.0)
+                    && chain.multicall.address.is_none()
+                    && !chain.allow_unchecked_output
+                    && chain.dual_blocks.is_none()
+            }),
+        summary(base),
+    );
+
+    // 4. Invalid sections, including out-of-range numbers and fee caps, are skipped
+    //    instead of loading half a chain
+    check(
+        &mut passed,
+        "Invalid sections",
+        keys(&config) == ["ethereum", "hyperevm", "base"],
+        format!("{:?}", keys(&config)),
+    );
+
+    // 5. [lifi] overrides; unset keys keep the client defaults
+    check(
+        &mut passed,
+        "Li.Fi section",
+        config.lifi.timeout == Duration::from_secs(5)
+            && config.lifi.max_retries == 0
+            && config.lifi.max_key_wait == Duration::from_millis(500)
+            && config.lifi.base_url == LifiConfig::default().base_url
+            && config.lifi.api_keys.is_empty(),
+        format!("{:?}, {} retries", config.lifi.timeout, config.lifi.max_retries),
+    );
+
+    if !passed {
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,169 @@
+// Test program for HyperEVM small/big block routing
+//
+// Starts a local mock that answers eth_usingBigBlocks / eth_bigBlockGasPrice
+// on /evm and applies evmUserModify actions posted to /exchange, then checks
+// that accounts are switched only when a gas limit needs the other block type.
+// No network access needed.
+//
+// Usage: cargo run --bin test_dual_blocks
+
+mod support;
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use alloy_primitives::U256;
+use serde_json::{json, Value};
+use worker::chain::hyperevm::{self, BlockType};
+use worker::chain::rpc::EndpointConfig;
+use worker::chain::tx::{SendOptions, TxError, TxFees};
+use worker::chain::{Chain, EvmChain, LocalSigner, Signer, WalletAccount};
+
+use support::{check, Response};
+
+/// Throwaway key; holds no funds.
+const TEST_KEY: &str = "0x0123456789012345678901234567890123456789012345678901234567890123";
+const BIG_BLOCK_BASE_FEE: u64 = 50_000_000_000;
+
+#[derive(Default)]
+struct MockState {
+    using_big_blocks: AtomicBool,
+    toggles: AtomicU64,
+    lookups: AtomicU64,
+}
+
+async fn spawn_mock(state: Arc<MockState>) -> String {
+    support::serve(move |request| respond(&request.path, &request.json(), &state)).await
+}
+
+fn respond(path: &str, request: &Value, state: &MockState) -> Response {
+    if path == "/exchange" {
+        return Response::json(match request["action"]["type"].as_str() {
+            Some("evmUserModify") => {
+                state.toggles.fetch_add(1, Ordering::SeqCst);
+                let enabled = request["action"]["usingBigBlocks"].as_bool().unwrap_or_default();
+                state.using_big_blocks.store(enabled, Ordering::SeqCst);
+                json!({"status": "ok", "response": {"type": "default"}})
+            }
+            _ => json!({"status": "err", "response": "Unknown action"}),
+        });
+    }
+
+    let reply = match request["method"].as_str() {
+        Some("eth_usingBigBlocks") => {
+            state.lookups.fetch_add(1, Ordering::SeqCst);
+            json!({"result": state.using_big_blocks.load(Ordering::SeqCst)})
+        }
+        Some("eth_bigBlockGasPrice") => json!({"result": format!("{:#x}", BIG_BLOCK_BASE_FEE)}),
+        _ => support::rpc_error(-32601, "method not found"),
+    };
+    support::rpc_reply(request, reply)
+}
+
+fn mock_chain(url: &str, hypercore: bool, signer: Arc<dyn Signer>) -> EvmChain {
+    let mut config = hyperevm::default_config();
+    config.rpc_endpoints = vec![EndpointConfig::new(&format!("{}/evm", url))];
+    config.hypercore_api_url = hypercore.then(|| url.to_string());
+    EvmChain::new(config, vec![WalletAccount::Signer(signer)]).expect("valid config")
+}
+
+#[tokio::main]
+async fn main() {
+    let mut passed = true;
+    let signer: Arc<dyn Signer> = Arc::new(LocalSigner::from_hex(TEST_KEY).expect("test key"));
+    let state = Arc::new(MockState::default());
+    let url = spawn_mock(state.clone()).await;
+    let chain = mock_chain(&url, true, signer.clone());
+    let dual_blocks = chain.dual_blocks().expect("HyperEVM routes blocks");
+
+    // 1. Small transaction on a small block account: no switch
+    let small = dual_blocks.route(&chain, signer.as_ref(), 200_000).await;
+    check(
+        &mut passed,
+        "Small stays small",
+        matches!(small, Ok(BlockType::Small)) && state.toggles.load(Ordering::SeqCst) == 0,
+        format!("{:?}, {} toggles", small, state.toggles.load(Ordering::SeqCst)),
+    );
+
+    // 2. Gas limit above the small block limit switches to big blocks once
+    let big = dual_blocks.route(&chain, signer.as_ref(), 5_000_000).await;
+    let again = dual_blocks.route(&chain, signer.as_ref(), 8_000_000).await;
+    check(
+        &mut passed,
+        "Switch to big blocks",
+        matches!(big, Ok(BlockType::Big))
+            && matches!(again, Ok(BlockType::Big))
+            && state.using_big_blocks.load(Ordering::SeqCst)
+            && state.toggles.load(Ordering::SeqCst) == 1
+            && state.lookups.load(Ordering::SeqCst) == 1,
+        format!(
+            "{} toggles, {} eth_usingBigBlocks lookups",
+            state.toggles.load(Ordering::SeqCst),
+            state.lookups.load(Ordering::SeqCst)
+        ),
+    );
+
+    // 3. Big block sends wait for the next big block and pay its base fee
+    let options = dual_blocks.send_options(
+        BlockType::Big,
+        &SendOptions {
+            replace_after: Some(Duration::from_secs(30)),
+            ..SendOptions::default()
+        },
+    );
+    let fees = dual_blocks
+        .fit_fees(
+            chain.rpc(),
+            BlockType::Big,
+            &TxFees::Eip1559 {
+                max_fee_per_gas: U256::from(1_000_000_000u64),
+                max_priority_fee_per_gas: U256::from(1_000_000u64),
+            },
+        )
+        .await;
+    check(
+        &mut passed,
+        "Big block send options",
+        options.replace_after == Some(Duration::from_secs(90))
+            && options.timeout == Duration::from_secs(180)
+            && matches!(fees, Ok(TxFees::Eip1559 { max_fee_per_gas, .. }) if max_fee_per_gas == U256::from(BIG_BLOCK_BASE_FEE + 1_000_000)),
+        format!("replace after {:?}, timeout {:?}, fees {:?}", options.replace_after, options.timeout, fees),
+    );
+
+    // 4. Back to small blocks for a small transaction
+    let back = dual_blocks.route(&chain, signer.as_ref(), 100_000).await;
+    check(
+        &mut passed,
+        "Switch back to small blocks",
+        matches!(back, Ok(BlockType::Small))
+            && !state.using_big_blocks.load(Ordering::SeqCst)
+            && state.toggles.load(Ordering::SeqCst) == 2,
+        format!("{:?}, {} toggles", back, state.toggles.load(Ordering::SeqCst)),
+    );
+
+    // 5. Too large for any block, or no HyperCore API to switch with
+    let too_large = dual_blocks.route(&chain, signer.as_ref(), 40_000_000).await;
+    let no_hypercore = mock_chain(&url, false, signer.clone());
+    let unswitchable = no_hypercore
+        .dual_blocks()
+        .expect("HyperEVM routes blocks")
+        .route(&no_hypercore, signer.as_ref(), 5_000_000)
+        .await;
+    check(
+        &mut passed,
+        "Rejected routes",
+        matches!(too_large, Err(TxError::InvalidRequest(_)))
+            && matches!(unswitchable, Err(TxError::InvalidRequest(_)))
+            && state.toggles.load(Ordering::SeqCst) == 2,
+        format!(
+            "{}; {}",
+            too_large.err().map(|e| e.to_string()).unwrap_or_default(),
+            unswitchable.err().map(|e| e.to_string()).unwrap_or_default()
+        ),
+    );
+
+    if !passed {
+        std::process::exit(1);
+    }
+}
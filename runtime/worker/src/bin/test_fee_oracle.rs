@@ -0,0 +1,215 @@
+// Test program for the fee oracle and the replace-by-fee loop
+//
+// Checks fee estimates from eth_feeHistory (and the eth_gasPrice fallback),
+// caps, adjusting Li.Fi's suggested fees and replacement bumps, then lets a
+// mock node hold a transaction until it is replaced with higher fees, or until
+// the cap stops replacements. No network access needed.
+//
+// Usage: cargo run --bin test_fee_oracle
+
+mod support;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use alloy_primitives::{Address, Bytes, B256, U256};
+use worker::chain::gas::{FeeOracle, FeeOracleConfig};
+use worker::chain::rpc::{EndpointConfig, RpcClient};
+use worker::chain::tx::{send_transaction, SendOptions, TxFees, TypedTransaction};
+use worker::chain::{ethereum, Chain, EvmChain, LocalSigner, Signer, WalletAccount};
+
+use support::check;
+use support::node::{decode_transaction, Mining, MockNode};
+
+/// Throwaway key; holds no funds.
+const TEST_KEY: &str = "0x0123456789012345678901234567890123456789012345678901234567890123";
+const GWEI: u64 = 1_000_000_000;
+
+fn gwei(tenths: u64) -> U256 {
+    U256::from(tenths * GWEI / 10)
+}
+
+fn eip1559(max_fee: U256, priority_fee: U256) -> TxFees {
+    TxFees::Eip1559 {
+        max_fee_per_gas: max_fee,
+        max_priority_fee_per_gas: priority_fee,
+    }
+}
+
+/// Sends a transfer with `replace_after` while the node holds it, and mines once
+/// the node has seen `broadcasts` raw sends (or after `mine_after`).
+async fn send_held(
+    node: &MockNode,
+    fee: FeeOracleConfig,
+    signer: Arc<dyn Signer>,
+    broadcasts: usize,
+    mine_after: Duration,
+) -> (Result<B256, String>, Vec<TxFees>) {
+    let mut config = ethereum::default_config();
+    config.rpc_endpoints = vec![EndpointConfig::new(&node.url)];
+    config.fee = fee;
+    let chain = EvmChain::new(config, vec![WalletAccount::Signer(signer.clone())]).expect("valid config");
+    let first_request = node.requests("eth_sendRawTransaction").len();
+
+    let miner = node.clone();
+    let held = tokio::spawn(async move {
+        let started = tokio::time::Instant::now();
+        while miner.requests("eth_sendRawTransaction").len() < first_request + broadcasts
+            && started.elapsed() < mine_after
+        {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        miner.mine();
+    });
+
+    let nonce = chain.nonces().reserve(&chain, signer.address()).await.unwrap_or_default();
+    let tx = TypedTransaction {
+        chain_id: ethereum::CHAIN_ID,
+        nonce,
+        gas_limit: 21_000,
+        fees: eip1559(gwei(30), gwei(10)),
+        to: Some(Address::repeat_byte(0x22)),
+        value: U256::from(1),
+        data: Bytes::new(),
+    };
+    let options = SendOptions {
+        confirmations: Some(1),
+        poll_interval: Duration::from_millis(20),
+        timeout: Duration::from_secs(5),
+        replace_after: Some(Duration::from_millis(100)),
+        fee_bump_percent: 12,
+    };
+    let receipt = send_transaction(&chain, signer.as_ref(), &tx, &options).await;
+    let _ = held.await;
+
+    let sent: Vec<TxFees> = node.requests("eth_sendRawTransaction")[first_request..]
+        .iter()
+        .filter_map(|params| params[0].as_str().and_then(|raw| raw.parse::<Bytes>().ok()))
+        .filter_map(|raw| decode_transaction(&raw).map(|tx| tx.tx.fees))
+        .collect();
+    (receipt.map(|receipt| receipt.transaction_hash).map_err(|e| e.to_string()), sent)
+}
+
+#[tokio::main]
+async fn main() {
+    let mut passed = true;
+    // Base fee 1 gwei, priority fee 0.1 gwei
+    let node = MockNode::start(ethereum::CHAIN_ID).await;
+    let rpc = RpcClient::new(&node.url);
+
+    // 1. Estimates: base fee * multiplier + priority fee, or the legacy gas price
+    let estimate = FeeOracle::new(FeeOracleConfig::default()).estimate(&rpc).await;
+    let legacy = FeeOracle::new(FeeOracleConfig {
+        eip1559: false,
+        ..FeeOracleConfig::default()
+    })
+    .estimate(&rpc)
+    .await;
+    check(
+        &mut passed,
+        "Estimate",
+        matches!(&estimate, Ok(fees) if *fees == eip1559(gwei(21), gwei(1)))
+            && matches!(&legacy, Ok(fees) if *fees == TxFees::Legacy { gas_price: gwei(11) }),
+        format!("{:?}; {:?}", estimate, legacy),
+    );
+
+    // 2. Nodes without eth_feeHistory get a legacy price
+    node.state()
+        .replies
+        .insert("eth_feeHistory".to_string(), support::rpc_error(-32601, "method not found"));
+    let fallback = FeeOracle::new(FeeOracleConfig::default()).estimate(&rpc).await;
+    node.state().replies.clear();
+    check(
+        &mut passed,
+        "No eth_feeHistory",
+        matches!(&fallback, Ok(fees) if *fees == TxFees::Legacy { gas_price: gwei(11) }),
+        format!("{:?}", fallback),
+    );
+
+    // 3. Caps bound both fields and keep the priority fee within the max fee
+    let capped = FeeOracle::new(FeeOracleConfig {
+        max_fee_cap: Some(gwei(15)),
+        max_priority_fee_cap: Some(gwei(20)),
+        ..FeeOracleConfig::default()
+    });
+    let priority_capped = FeeOracle::new(FeeOracleConfig {
+        max_priority_fee_cap: Some(U256::from(50_000_000u64)),
+        ..FeeOracleConfig::default()
+    });
+    let capped_estimate = capped.estimate(&rpc).await;
+    let priority_estimate = priority_capped.estimate(&rpc).await;
+    let capped_high = capped.adjust(&rpc, &eip1559(gwei(50), gwei(40))).await;
+    check(
+        &mut passed,
+        "Caps",
+        matches!(&capped_estimate, Ok(fees) if *fees == eip1559(gwei(15), gwei(1)))
+            && matches!(&priority_estimate, Ok(fees) if *fees == eip1559(gwei(21), U256::from(50_000_000u64)))
+            && matches!(&capped_high, Ok(fees) if *fees == eip1559(gwei(15), gwei(15))),
+        format!("{:?}; {:?}; {:?}", capped_estimate, priority_estimate, capped_high),
+    );
+
+    // 4. Suggestions are raised to the estimate, never lowered, and the wrong type is replaced
+    let default = FeeOracle::new(FeeOracleConfig::default());
+    let low = default.adjust(&rpc, &eip1559(gwei(5), gwei(0))).await;
+    let high = default.adjust(&rpc, &eip1559(gwei(40), gwei(5))).await;
+    let wrong_type = default.adjust(&rpc, &TxFees::Legacy { gas_price: gwei(50) }).await;
+    check(
+        &mut passed,
+        "Adjust",
+        matches!(&low, Ok(fees) if *fees == eip1559(gwei(21), gwei(1)))
+            && matches!(&high, Ok(fees) if *fees == eip1559(gwei(40), gwei(5)))
+            && matches!(&wrong_type, Ok(fees) if *fees == eip1559(gwei(21), gwei(1))),
+        format!("{:?}; {:?}; {:?}", low, high, wrong_type),
+    );
+
+    // 5. Bumps raise every field by at least 10%, rounding up, unless a cap is in the way
+    let small = default.bump(&eip1559(U256::from(10), U256::from(1)), 5);
+    let bumped = default.bump(&TxFees::Legacy { gas_price: gwei(20) }, 25);
+    let at_cap = capped.bump(&eip1559(gwei(14), gwei(1)), 12);
+    check(
+        &mut passed,
+        "Bump",
+        small == Some(eip1559(U256::from(11), U256::from(2)))
+            && bumped == Some(TxFees::Legacy { gas_price: gwei(25) })
+            && at_cap.is_none(),
+        format!("{:?}; {:?}; {:?}", small, bumped, at_cap),
+    );
+
+    // 6. A held transaction is replaced with bumped fees and the replacement is mined
+    node.state().mining = Mining::Manual;
+    let signer: Arc<dyn Signer> = Arc::new(LocalSigner::from_hex(TEST_KEY).expect("test key"));
+    node.state().balances.insert(signer.address(), U256::from(10u64).pow(U256::from(18)));
+    let (receipt, sent) = send_held(&node, FeeOracleConfig::default(), signer.clone(), 2, Duration::from_secs(3)).await;
+    let mined = receipt.as_ref().ok().and_then(|hash| node.state().mined.get(hash).map(|tx| tx.tx.tx.fees.clone()));
+    check(
+        &mut passed,
+        "Replace by fee",
+        sent.len() >= 2
+            && sent[1] == eip1559(U256::from(3_360_000_000u64), U256::from(1_120_000_000u64))
+            && mined.as_ref() == sent.last(),
+        format!("sent {:?}; mined {:?}", sent, mined),
+    );
+
+    // 7. At the cap the original keeps waiting instead of being replaced
+    let (receipt, sent) = send_held(
+        &node,
+        FeeOracleConfig {
+            max_fee_cap: Some(gwei(30)),
+            ..FeeOracleConfig::default()
+        },
+        signer,
+        2,
+        Duration::from_millis(400),
+    )
+    .await;
+    check(
+        &mut passed,
+        "Capped replacement",
+        receipt.is_ok() && sent == [eip1559(gwei(30), gwei(10))],
+        format!("{:?}; sent {:?}", receipt, sent),
+    );
+
+    if !passed {
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,103 @@
+// Test program for BIP-39 / BIP-44 wallet derivation
+//
+// Derives addresses from the well-known "abandon ... about" test mnemonic and
+// the BIP-32 test vector seed, expands path templates and checks that
+// malformed paths and mnemonics are rejected. No network access needed.
+//
+// Usage: cargo run --bin test_hd
+
+mod support;
+
+use alloy_primitives::{address, Address};
+use worker::chain::signer::hd::{self, DerivationPath, HdError, DEFAULT_DERIVATION_PATH};
+use worker::chain::{LocalSigner, Signer};
+
+use support::check;
+
+/// Public test mnemonic; never holds funds.
+const TEST_MNEMONIC: &str =
+    "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+const ACCOUNT_0: Address = address!("9858EfFD232B4033E47d90003D41EC34EcaEda94");
+const ACCOUNT_1: Address = address!("6Fac4D18c912343BF86fa7049364Dd4E424Ab9C0");
+
+/// BIP-32 test vector 1 seed and private keys.
+const BIP32_SEED: &str = "000102030405060708090a0b0c0d0e0f";
+const BIP32_VECTORS: [(&str, &str); 2] = [
+    ("m/0'", "edb2e14f9ee77d26dd93b4ecede8d16ed408ce149b6cd80b0715a2d911a0afea"),
+    ("m/0'/1", "3c6cb8d0f6a264c91ea8b5030fadaa8e538b020f0a387421a12de9319dc93368"),
+];
+
+fn addresses(result: &Result<Vec<LocalSigner>, HdError>) -> Result<Vec<Address>, String> {
+    match result {
+        Ok(signers) => Ok(signers.iter().map(|signer| signer.address()).collect()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+fn main() {
+    let mut passed = true;
+
+    // 1. Default path and path templates
+    for (name, template, expected) in [
+        ("Default path", DEFAULT_DERIVATION_PATH, vec![ACCOUNT_0]),
+        ("Second account", "m/44'/60'/0'/0/1", vec![ACCOUNT_1]),
+        ("Exclusive range", "m/44'/60'/0'/0/{0..2}", vec![ACCOUNT_0, ACCOUNT_1]),
+        ("Inclusive range", "m/44'/60'/0'/0/{0..=1}", vec![ACCOUNT_0, ACCOUNT_1]),
+        ("h marks hardened", "m/44h/60h/0h/0/{1..=1}", vec![ACCOUNT_1]),
+    ] {
+        let result = addresses(&hd::signers_from_mnemonic(TEST_MNEMONIC, "", template));
+        check(&mut passed, name, result.as_ref() == Ok(&expected), format!("{} -> {:?}", template, result));
+    }
+
+    // 2. BIP-32 hardened and normal child derivation
+    let seed = hex::decode(BIP32_SEED).expect("seed hex");
+    for (path, private_key) in BIP32_VECTORS {
+        let derived = path
+            .parse::<DerivationPath>()
+            .and_then(|path| hd::signer_from_seed(&seed, &path))
+            .map(|signer| signer.address());
+        let expected = LocalSigner::from_hex(private_key).expect("vector key").address();
+        check(
+            &mut passed,
+            "BIP-32 test vector",
+            matches!(derived, Ok(address) if address == expected),
+            format!("{} -> {:?}", path, derived.map_err(|e| e.to_string())),
+        );
+    }
+
+    // 3. Malformed paths and templates
+    for template in [
+        "44'/60'/0'/0/0",
+        "m/44'/60'/x/0/0",
+        "m/44'/60'/0'/0/2147483648",
+        "m/44'/60'/0'/0/{3..3}",
+        "m/44'/60'/0'/0/{5..1}",
+        "m/44'/60'/0'/0/{1..}",
+        "m/44'/60'/0'/0/{0-2}",
+        "m/44'/60'/0'/0/}0..1{",
+        "m/44'/60'/0'/0/{0..1",
+        "m/44'/60'/{0..1}/0/{0..1}",
+        "m/44'/60'/0'/0/{0..=4294967295}",
+    ] {
+        let result = hd::expand_path_template(template);
+        check(
+            &mut passed,
+            "Malformed path",
+            matches!(result, Err(HdError::InvalidPath(_))),
+            format!("{} -> {:?}", template, result.map(|paths| paths.len())),
+        );
+    }
+
+    // 4. A mnemonic with a bad checksum
+    let result = hd::signers_from_mnemonic(&TEST_MNEMONIC.replace("about", "abandon"), "", DEFAULT_DERIVATION_PATH);
+    check(
+        &mut passed,
+        "Bad checksum",
+        matches!(result, Err(HdError::Mnemonic(_))),
+        format!("{:?}", addresses(&result)),
+    );
+
+    if !passed {
+        std::process::exit(1);
+    }
+}
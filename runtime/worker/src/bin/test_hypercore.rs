@@ -0,0 +1,255 @@
+// Test program for the HyperCore integration
+//
+// Starts a local mock serving the Hyperliquid info API on /info and the
+// HyperEVM read precompiles over JSON-RPC on /evm, then checks request shapes
+// and decoding. No network access needed.
+//
+// Usage: cargo run --bin test_hypercore
+
+mod support;
+
+use alloy_primitives::{Address, U256};
+use serde_json::{json, Value};
+use worker::chain::hyperevm::hypercore::HyperCoreError;
+use worker::chain::hyperevm::precompiles::{self, PerpPosition};
+use worker::chain::hyperevm::HyperCoreClient;
+use worker::chain::rpc::RpcClient;
+
+use support::{check, Response};
+
+const USER: Address = Address::repeat_byte(0x11);
+
+async fn spawn_mock() -> String {
+    support::serve(|request| {
+        let body = request.json();
+        match request.path.as_str() {
+            "/info" => match info_response(&body) {
+                Some(response) => Response::json(response),
+                None => Response::text(422, "Failed to deserialize the JSON body"),
+            },
+            "/evm" => rpc_response(&body),
+            _ => Response::text(404, ""),
+        }
+    })
+    .await
+}
+
+fn info_response(request: &Value) -> Option<Value> {
+    let user = format!("{:#x}", USER);
+    Some(match request["type"].as_str()? {
+        "meta" => json!({"universe": [
+            {"name": "BTC", "szDecimals": 5, "maxLeverage": 40},
+            {"name": "ETH", "szDecimals": 4, "maxLeverage": 25, "onlyIsolated": false},
+        ]}),
+        "spotMeta" => json!({
+            "universe": [{"name": "PURR/USDC", "tokens": [1, 0], "index": 0, "isCanonical": true}],
+            "tokens": [
+                {"name": "USDC", "szDecimals": 8, "weiDecimals": 8, "index": 0, "tokenId": "0x6d1e7cde53ba9467b783cb7c530ce054", "isCanonical": true, "evmContract": null},
+                {"name": "PURR", "szDecimals": 0, "weiDecimals": 5, "index": 1, "tokenId": "0xc1fb593aeffbeb02f85e0308e9956a90", "isCanonical": true,
+                 "evmContract": {"address": "0x9b498c3c8a0b8cd8ba1d9851d40d186f1872b44e", "evm_extra_wei_decimals": 13}},
+            ],
+        }),
+        "metaAndAssetCtxs" => json!([
+            {"universe": [{"name": "BTC", "szDecimals": 5, "maxLeverage": 40}, {"name": "ETH", "szDecimals": 4, "maxLeverage": 25}]},
+            [
+                {"funding": "0.0000125", "openInterest": "100.0", "oraclePx": "65000.0", "markPx": "65010.0", "midPx": "65005.0", "premium": "0.0001", "prevDayPx": "64000.0", "dayNtlVlm": "1000000.0", "impactPxs": ["65000.0", "65010.0"]},
+                {"funding": "-0.00002", "openInterest": "200.0", "oraclePx": "3000.0", "markPx": "3001.0", "midPx": null, "premium": null, "prevDayPx": "2900.0", "dayNtlVlm": "500000.0"},
+            ],
+        ]),
+        "l2Book" if request["coin"] == "BTC" => json!({
+            "coin": "BTC",
+            "time": 1_700_000_000_000u64,
+            "levels": [
+                [{"px": "65000.0", "sz": "0.5", "n": 3}, {"px": "64999.0", "sz": "1.0", "n": 1}],
+                [{"px": "65001.0", "sz": "0.2", "n": 2}],
+            ],
+        }),
+        "allMids" => json!({"BTC": "65005.0", "ETH": "3000.5", "@107": "12.5"}),
+        "fundingHistory" if request["coin"] == "ETH" && request["startTime"] == 1_000 && request["endTime"].is_null() => json!([
+            {"coin": "ETH", "fundingRate": "-0.00022196", "premium": "-0.00052196", "time": 1_683_849_600_076u64},
+        ]),
+        "clearinghouseState" if request["user"] == user => json!({
+            "assetPositions": [{"type": "oneWay", "position": {
+                "coin": "ETH", "szi": "-0.0335", "entryPx": "2986.3", "positionValue": "100.02765",
+                "unrealizedPnl": "-0.0134", "returnOnEquity": "-0.0026789", "liquidationPx": null,
+                "marginUsed": "4.967826", "maxLeverage": 25, "cumFunding": {"allTime": "0.0"},
+                "leverage": {"type": "isolated", "value": 20, "rawUsd": "-95.059824"},
+            }}],
+            "marginSummary": {"accountValue": "13104.51", "totalNtlPos": "100.0", "totalRawUsd": "13004.51", "totalMarginUsed": "4.97"},
+            "crossMarginSummary": {"accountValue": "13000.0", "totalNtlPos": "0.0", "totalRawUsd": "13000.0", "totalMarginUsed": "0.0"},
+            "crossMaintenanceMarginUsed": "0.0",
+            "withdrawable": "13000.0",
+            "time": 1_700_000_000_000u64,
+        }),
+        "spotClearinghouseState" if request["user"] == user => json!({"balances": [
+            {"coin": "USDC", "token": 0, "hold": "1.0", "total": "14.625485", "entryNtl": "0.0"},
+        ]}),
+        _ => return None,
+    })
+}
+
+fn word(value: U256) -> [u8; 32] {
+    value.to_be_bytes()
+}
+
+fn signed(value: i64) -> [u8; 32] {
+    word(U256::from(value as u64) | if value < 0 { U256::MAX << 64 } else { U256::ZERO })
+}
+
+/// Precompile results; arguments are checked so a wrong encoding fails the test.
+fn precompile_output(target: Address, data: &[u8]) -> Option<Vec<u8>> {
+    let arg = |index: usize| data.get(index * 32..index * 32 + 32).map(U256::from_be_slice);
+    let user = U256::from_be_slice(USER.into_word().as_slice());
+    let out: Vec<[u8; 32]> = if target == precompiles::MARK_PX && arg(0)? == U256::from(1) {
+        vec![word(U256::from(300_100u64))]
+    } else if target == precompiles::ORACLE_PX && arg(0)? == U256::from(1) {
+        vec![word(U256::from(300_000u64))]
+    } else if target == precompiles::SPOT_PX && arg(0)? == U256::from(0) {
+        vec![word(U256::from(21_500_000u64))]
+    } else if target == precompiles::L1_BLOCK_NUMBER && data.is_empty() {
+        vec![word(U256::from(123_456u64))]
+    } else if target == precompiles::POSITION && arg(0)? == user && arg(1)? == U256::from(1) {
+        vec![
+            signed(-335),
+            word(U256::from(100_027_650u64)),
+            signed(-95_059_824),
+            word(U256::from(20)),
+            word(U256::from(1)),
+        ]
+    } else if target == precompiles::SPOT_BALANCE && arg(0)? == user && arg(1)? == U256::from(0) {
+        vec![
+            word(U256::from(1_462_548_500u64)),
+            word(U256::from(100_000_000u64)),
+            word(U256::ZERO),
+        ]
+    } else {
+        return None;
+    };
+    Some(out.concat())
+}
+
+fn rpc_response(request: &Value) -> Response {
+    let call = &request["params"][0];
+    let target: Address = call["to"].as_str().unwrap_or_default().parse().unwrap_or_default();
+    let data = hex::decode(call["input"].as_str().or(call["data"].as_str()).unwrap_or("0x").trim_start_matches("0x"))
+        .unwrap_or_default();
+    let reply = match (request["method"].as_str(), precompile_output(target, &data)) {
+        (Some("eth_call"), Some(out)) => json!({"result": format!("0x{}", hex::encode(out))}),
+        (Some("eth_call"), None) => json!({"result": "0x"}),
+        _ => support::rpc_error(-32601, "method not found"),
+    };
+    support::rpc_reply(request, reply)
+}
+
+#[tokio::main]
+async fn main() {
+    let mut passed = true;
+    let url = spawn_mock().await;
+    // A trailing slash must not produce `//info`
+    let client = HyperCoreClient::new(&format!("{}/", url));
+
+    // 1. Perp and spot metadata
+    let meta = client.meta().await;
+    let spot = client.spot_meta().await;
+    let purr: Address = "0x9b498c3c8a0b8cd8ba1d9851d40d186f1872b44e".parse().unwrap();
+    check(
+        &mut passed,
+        "Metadata",
+        meta.as_ref().is_ok_and(|meta| meta.asset("ETH").is_some_and(|(index, asset)| index == 1 && asset.sz_decimals == 4))
+            && spot.as_ref().is_ok_and(|spot| {
+                spot.token_by_evm_address(purr).is_some_and(|token| token.name == "PURR")
+                    && spot.token("USDC").is_some_and(|token| token.evm_contract.is_none())
+            }),
+        format!("meta ok: {}, spotMeta ok: {}", meta.is_ok(), spot.is_ok()),
+    );
+
+    // 2. Order book and mids
+    let book = client.l2_book("BTC").await;
+    let mid = client.mid_price("ETH").await;
+    let unknown = client.mid_price("DOGE").await;
+    check(
+        &mut passed,
+        "Book and mids",
+        book.as_ref().is_ok_and(|book| {
+            book.best_bid() == Some(65000.0) && book.best_ask() == Some(65001.0) && book.bids().len() == 2
+        }) && mid.as_ref().is_ok_and(|mid| *mid == 3000.5)
+            && matches!(unknown, Err(HyperCoreError::Missing(_))),
+        format!("book ok: {}, ETH mid: {:?}", book.is_ok(), mid.ok()),
+    );
+
+    // 3. Funding: current rates by coin and history
+    let rates = client.funding_rates().await;
+    let history = client.funding_history("ETH", 1_000, None).await;
+    check(
+        &mut passed,
+        "Funding",
+        rates
+            .as_ref()
+            .is_ok_and(|rates| rates.get("BTC") == Some(&0.0000125) && rates.get("ETH") == Some(&-0.00002))
+            && history.as_ref().is_ok_and(|history| history.len() == 1 && history[0].funding_rate == "-0.00022196"),
+        format!("rates: {:?}, history entries: {:?}", rates.ok(), history.map(|h| h.len()).ok()),
+    );
+
+    // 4. User state
+    let perp = client.clearinghouse_state(USER).await;
+    let spot_state = client.spot_clearinghouse_state(USER).await;
+    check(
+        &mut passed,
+        "User state",
+        perp.as_ref().is_ok_and(|state| {
+            state.position("ETH").is_some_and(|position| {
+                position.szi == "-0.0335" && position.leverage.value == 20 && position.liquidation_px.is_none()
+            })
+        }) && spot_state.as_ref().is_ok_and(|state| state.balances[0].total == "14.625485"),
+        format!("perp ok: {}, spot ok: {}", perp.is_ok(), spot_state.is_ok()),
+    );
+
+    // 5. API errors are surfaced with their status
+    let unknown_user = client.clearinghouse_state(Address::ZERO).await;
+    check(
+        &mut passed,
+        "HTTP errors",
+        matches!(unknown_user, Err(HyperCoreError::Http { status: 422, .. })),
+        format!("{:?}", unknown_user.err().map(|e| e.to_string())),
+    );
+
+    // 6. Read precompiles over eth_call
+    let rpc = RpcClient::new(&format!("{}/evm", url));
+    let mark = precompiles::mark_px(&rpc, 1).await;
+    let oracle = precompiles::oracle_px(&rpc, 1).await;
+    let spot_px = precompiles::spot_px(&rpc, 0).await;
+    let l1_block = precompiles::l1_block_number(&rpc).await;
+    check(
+        &mut passed,
+        "Price precompiles",
+        mark.as_ref().is_ok_and(|raw| precompiles::perp_price(*raw, 4) == 3001.0)
+            && oracle.as_ref().is_ok_and(|raw| precompiles::perp_price(*raw, 4) == 3000.0)
+            && spot_px.as_ref().is_ok_and(|raw| precompiles::spot_price(*raw, 0) == 0.215)
+            && l1_block.as_ref().is_ok_and(|block| *block == 123_456),
+        format!("mark {:?}, oracle {:?}, spot {:?}, l1 block {:?}", mark.ok(), oracle.ok(), spot_px.ok(), l1_block.ok()),
+    );
+
+    let position = precompiles::position(&rpc, USER, 1).await;
+    let balance = precompiles::spot_balance(&rpc, USER, 0).await;
+    let empty = precompiles::spot_balance(&rpc, Address::ZERO, 0).await;
+    check(
+        &mut passed,
+        "Account precompiles",
+        position.as_ref().is_ok_and(|position| {
+            *position
+                == PerpPosition {
+                    szi: -335,
+                    entry_ntl: 100_027_650,
+                    isolated_raw_usd: -95_059_824,
+                    leverage: 20,
+                    is_isolated: true,
+                }
+        }) && balance.as_ref().is_ok_and(|balance| balance.total == 1_462_548_500 && balance.hold == 100_000_000)
+            && empty.is_err(),
+        format!("position {:?}, balance {:?}", position.ok(), balance.ok()),
+    );
+
+    if !passed {
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,290 @@
+// Test program for HyperCore exchange actions
+//
+// Checks action hashing and EIP-712 signing against the test vectors of the
+// official Hyperliquid Python SDK and known answers for the user-signed
+// transfer actions, then posts orders, cancels and transfers to a local mock
+// of the /exchange endpoint. No network access needed.
+//
+// Usage: cargo run --bin test_hypercore_exchange
+
+mod support;
+
+use std::sync::{Arc, Mutex};
+
+use alloy_primitives::{Address, U256};
+use serde::Serialize;
+use serde_json::{json, Value};
+use worker::chain::hyperevm::hypercore::exchange::{self, float_to_wire, sign_l1_action};
+use worker::chain::hyperevm::hypercore::{
+    system_address, CancelRequest, CancelStatus, HyperCoreClient, HyperCoreError, OrderRequest, OrderStatus, SpotToken,
+    TimeInForce,
+};
+use worker::chain::signer::Signature;
+use worker::chain::{LocalSigner, Signer};
+
+use support::{check, Response};
+
+/// Key used by the SDK's signing tests; holds no funds.
+const TEST_KEY: &str = "0x0123456789012345678901234567890123456789012345678901234567890123";
+
+#[derive(Serialize)]
+struct DummyAction {
+    #[serde(rename = "type")]
+    action_type: &'static str,
+    num: u64,
+}
+
+fn signature_matches(signature: &Signature, r: &str, s: &str, v: u8) -> bool {
+    signature.r == r.parse::<U256>().unwrap()
+        && signature.s == s.parse::<U256>().unwrap()
+        && 27 + signature.y_parity as u8 == v
+}
+
+fn purr() -> SpotToken {
+    SpotToken {
+        name: "PURR".to_string(),
+        sz_decimals: 0,
+        wei_decimals: 5,
+        index: 1,
+        token_id: "0xc1fb593aeffbeb02f85e0308e9956a90".to_string(),
+        is_canonical: true,
+        evm_contract: None,
+    }
+}
+
+type Requests = Arc<Mutex<Vec<Value>>>;
+
+async fn spawn_mock_exchange() -> (String, Requests) {
+    let requests: Requests = Arc::default();
+    let recorded = requests.clone();
+    let url = support::serve(move |request| {
+        let request = request.json();
+        let response = exchange_response(&request);
+        recorded.lock().unwrap().push(request);
+        Response::json(response)
+    })
+    .await;
+    (url, requests)
+}
+
+fn exchange_response(request: &Value) -> Value {
+    let action = &request["action"];
+    match action["type"].as_str() {
+        Some("order") => {
+            let statuses: Vec<Value> = action["orders"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .enumerate()
+                .map(|(i, order)| match order["t"]["limit"]["tif"].as_str() {
+                    Some("Ioc") => json!({"filled": {"totalSz": order["s"], "avgPx": order["p"], "oid": 100 + i}}),
+                    Some("Alo") => json!({"error": "Post only order would have immediately matched"}),
+                    _ => json!({"resting": {"oid": 100 + i}}),
+                })
+                .collect();
+            json!({"status": "ok", "response": {"type": "order", "data": {"statuses": statuses}}})
+        }
+        Some("cancel") => json!({"status": "ok", "response": {"type": "cancel", "data": {"statuses": [
+            "success",
+            {"error": "Order was never placed, already canceled, or filled."},
+        ]}}}),
+        Some("usdClassTransfer") | Some("spotSend") => json!({"status": "ok", "response": {"type": "default"}}),
+        _ => json!({"status": "err", "response": "Unknown action"}),
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let mut passed = true;
+    let signer = LocalSigner::from_hex(TEST_KEY).expect("test key");
+
+    // 1. SDK vector: L1 action signing (msgpack hash + phantom agent)
+    let dummy = DummyAction {
+        action_type: "dummy",
+        num: 100_000_000_000,
+    };
+    let mainnet = sign_l1_action(&signer, &dummy, None, 0, None, true).unwrap();
+    let testnet = sign_l1_action(&signer, &dummy, None, 0, None, false).unwrap();
+    check(
+        &mut passed,
+        "L1 action vector",
+        signature_matches(
+            &mainnet,
+            "0x53749d5b30552aeb2fca34b530185976545bb22d0b3ce6f62e31be961a59298",
+            "0x755c40ba9bf05223521753995abb2f73ab3229be8ec921f350cb447e384d8ed8",
+            27,
+        ) && signature_matches(
+            &testnet,
+            "0x542af61ef1f429707e3c76c5293c80d01f74ef853e34b76efffcb57e574f9510",
+            "0x17b8b32f086e8cdede991f1e2c529f5dd5297cbe8128500e00cbaf766204a613",
+            28,
+        ),
+        format!("mainnet r {:#x}, testnet r {:#x}", mainnet.r, testnet.r),
+    );
+
+    // 2. SDK vector: order action (asset 1, buy 100 @ 100, GTC)
+    let order = OrderRequest {
+        asset: 1,
+        is_buy: true,
+        limit_px: 100.0,
+        sz: 100.0,
+        reduce_only: false,
+        tif: TimeInForce::Gtc,
+        cloid: None,
+    };
+    let mainnet = exchange::order_action(&signer, std::slice::from_ref(&order), None, 0, true).unwrap();
+    let testnet = exchange::order_action(&signer, std::slice::from_ref(&order), None, 0, false).unwrap();
+    check(
+        &mut passed,
+        "Order action vector",
+        signature_matches(
+            mainnet.signature(),
+            "0xd65369825a9df5d80099e513cce430311d7d26ddf477f5b3a33d2806b100d78e",
+            "0x2b54116ff64054968aa237c20ca9ff68000f977c93289157748a3162b6ea940e",
+            28,
+        ) && signature_matches(
+            testnet.signature(),
+            "0x82b2ba28e76b3d761093aaded1b1cdad4960b3af30212b343fb2e6cdfa4e3d54",
+            "0x6b53878fc99d26047f4d7e8c90eb98955a109f44209163f52d8dc4278cbbd9f5",
+            27,
+        ),
+        format!("mainnet r {:#x}, testnet r {:#x}", mainnet.signature().r, testnet.signature().r),
+    );
+
+    // 3. User-signed actions (signatureChainId 0x66eee): the typed data of the SDK's
+    //    sign_usd_class_transfer_action and sign_spot_transfer_action for the same key,
+    //    signed by a standalone EIP-712 / RFC 6979 implementation that reproduces 1.
+    let destination: Address = "0x5e9ee1089755c3435139848e47e6635505d5a13a".parse().unwrap();
+    let vectors = [
+        (
+            "UsdClassTransfer mainnet",
+            exchange::usd_class_transfer_action(&signer, 1.0, true, 1687816341423, true),
+            "0x7000485fd96b213d769e6f07fc859c2683f52f4bcf24209f4a40379be9336e40",
+            "0x0eca63d4e42e247ca9d7b3e4ffd8b72e73237a74cec0c0d9a490bd05dc3a7153",
+            27,
+        ),
+        (
+            "UsdClassTransfer testnet",
+            exchange::usd_class_transfer_action(&signer, 1.0, true, 1687816341423, false),
+            "0xe265b4f0207b3e5ba5d7f863c150b41a25449bf6f6bda1cf278f4b2b6db25279",
+            "0x2469568d152f30acad5672b7657f0aa8e2f9d897fbd03b9b6b7dc3f443a1dff9",
+            27,
+        ),
+        (
+            "SpotSend mainnet",
+            exchange::spot_send_action(&signer, destination, &purr(), 1.0, 1687816341423, true),
+            "0x562b6661761d9eaa985454d3e1a0f316f02cf9e8288a951732dc9bf801fcc6b3",
+            "0x05a31e9a6138bf56ac25dc25e9f24ea7748ff418d2d311aedd00035bbf56c530",
+            28,
+        ),
+        (
+            "SpotSend testnet",
+            exchange::spot_send_action(&signer, destination, &purr(), 1.0, 1687816341423, false),
+            "0xe22aa90dd9e8e66e6c0a915a0503947b5324cd9910588ce7fc74c17540cf7c19",
+            "0x1091cd94f37e3f476fcbf67ef1e97b4a7a680e28eac21b8b431a4a6ca5c12607",
+            28,
+        ),
+    ];
+    let mismatched: Vec<String> = vectors
+        .iter()
+        .filter(|(_, action, r, s, v)| !action.as_ref().is_ok_and(|action| signature_matches(action.signature(), r, s, *v)))
+        .map(|(name, action, ..)| format!("{}: {:?}", name, action.as_ref().map(|action| action.signature().r)))
+        .collect();
+    check(&mut passed, "User-signed action vectors", mismatched.is_empty(), format!("{:?}", mismatched));
+
+    // 4. Wire decimals and bridge addresses
+    let mut hype = purr();
+    hype.name = "HYPE".to_string();
+    hype.index = 150;
+    let wire = [0.1, 100.0, 1234.5, -0.0, 0.00000001].map(|value| float_to_wire(value).unwrap_or_default());
+    check(
+        &mut passed,
+        "Wire format",
+        wire == ["0.1", "100", "1234.5", "0", "0.00000001"]
+            && float_to_wire(0.123456789).is_err()
+            && system_address(&purr()) == "0x2000000000000000000000000000000000000001".parse::<Address>().unwrap()
+            && system_address(&hype) == Address::repeat_byte(0x22),
+        format!("{:?}", wire),
+    );
+
+    // 5. Orders and cancels against the mock exchange
+    let (url, requests) = spawn_mock_exchange().await;
+    let client = HyperCoreClient::new(&url);
+    let orders = [
+        order.clone(),
+        OrderRequest {
+            tif: TimeInForce::Ioc,
+            is_buy: false,
+            limit_px: 99.5,
+            cloid: Some(1),
+            ..order.clone()
+        },
+        OrderRequest {
+            tif: TimeInForce::Alo,
+            ..order.clone()
+        },
+    ];
+    let statuses = client.place_orders(&signer, &orders, None).await;
+    let order_request = requests.lock().unwrap().last().cloned().unwrap_or_default();
+    check(
+        &mut passed,
+        "Place orders",
+        statuses.as_ref().is_ok_and(|statuses| {
+            statuses[0] == OrderStatus::Resting { oid: 100 }
+                && matches!(&statuses[1], OrderStatus::Filled { oid: 101, avg_px, .. } if avg_px == "99.5")
+                && matches!(statuses[2], OrderStatus::Error(_))
+        }) && order_request["action"]["orders"][1]["c"] == "0x00000000000000000000000000000001"
+            && order_request["action"]["grouping"] == "na"
+            && order_request["vaultAddress"].is_null()
+            && order_request["signature"]["v"].is_u64(),
+        format!("{:?}", statuses),
+    );
+
+    let cancels = client
+        .cancel_orders(&signer, &[CancelRequest { asset: 1, oid: 100 }, CancelRequest { asset: 1, oid: 7 }], None)
+        .await;
+    check(
+        &mut passed,
+        "Cancel orders",
+        cancels.as_ref().is_ok_and(|statuses| {
+            statuses[0] == CancelStatus::Success && matches!(statuses[1], CancelStatus::Error(_))
+        }),
+        format!("{:?}", cancels),
+    );
+
+    // 6. Transfers: user-signed actions carry their chain fields
+    let class_transfer = client.usd_class_transfer(&signer, 12.5, true).await;
+    let class_request = requests.lock().unwrap().last().cloned().unwrap_or_default();
+    let to_evm = client.transfer_to_evm(&signer, &hype, 1.0).await;
+    let send_request = requests.lock().unwrap().last().cloned().unwrap_or_default();
+    let unlinked = client.transfer_to_evm(&signer, &purr(), 1.0).await;
+    check(
+        &mut passed,
+        "Transfers",
+        class_transfer.is_ok()
+            && class_request["action"]["amount"] == "12.5"
+            && class_request["action"]["toPerp"] == true
+            && class_request["action"]["hyperliquidChain"] == "Mainnet"
+            && class_request["action"]["signatureChainId"] == "0x66eee"
+            && class_request["action"]["nonce"] == class_request["nonce"]
+            && to_evm.is_ok()
+            && send_request["action"]["destination"] == "0x2222222222222222222222222222222222222222"
+            && send_request["action"]["token"] == "HYPE:0xc1fb593aeffbeb02f85e0308e9956a90"
+            && matches!(unlinked, Err(HyperCoreError::InvalidAction(_))),
+        format!("class transfer {:?}, to EVM {:?}", class_transfer.err(), to_evm.err()),
+    );
+
+    // 7. Nonces stay unique under bursts
+    let nonces: Vec<u64> = (0..100).map(|_| client.next_nonce()).collect();
+    check(
+        &mut passed,
+        "Unique nonces",
+        nonces.windows(2).all(|pair| pair[1] > pair[0]),
+        format!("{}..{}", nonces[0], nonces[99]),
+    );
+
+    println!("Signer: {:#x}", signer.address());
+    if !passed {
+        std::process::exit(1);
+    }
+}
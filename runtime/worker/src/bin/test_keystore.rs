@@ -0,0 +1,128 @@
+// Test program for Web3 Secret Storage keystores
+//
+// Decrypts the pbkdf2 and scrypt test vectors from the Web3 Secret Storage
+// definition, round-trips a newly written keystore and checks that scrypt
+// costs above the accepted bounds are refused before any work is done.
+// No network access needed.
+//
+// Usage: cargo run --release --bin test_keystore
+// (the scrypt and pbkdf2 costs make debug builds take minutes)
+
+mod support;
+
+use alloy_primitives::{address, Address};
+use serde_json::json;
+use worker::chain::signer::keystore::{self, KeystoreError, KeystoreFile};
+use worker::chain::Signer;
+
+use support::check;
+
+const PASSWORD: &str = "testpassword";
+/// Key and address of both spec vectors.
+const SPEC_ADDRESS: Address = address!("008aeeda4d805471df9b2a5b0f38a0c3bcba786b");
+
+fn pbkdf2_vector() -> serde_json::Value {
+    json!({
+        "crypto": {
+            "cipher": "aes-128-ctr",
+            "cipherparams": {"iv": "6087dab2f9fdbbfaddc31a909735c1e6"},
+            "ciphertext": "5318b4d5bcd28de64ee5559e671353e16f075ecae9f99c7a79a38af5f869aa46",
+            "kdf": "pbkdf2",
+            "kdfparams": {
+                "c": 262144,
+                "dklen": 32,
+                "prf": "hmac-sha256",
+                "salt": "ae3cd4e7013836a3df6bd7241b12db061dbe2c6785853cce422d148a624ce0bd"
+            },
+            "mac": "517ead924a9d0dc3124507e3393d175ce3ff7c1e96529c6c555ce9e51205e9b2"
+        },
+        "id": "3198bc9c-6672-5ab3-d995-4942343ae5b6",
+        "version": 3
+    })
+}
+
+fn scrypt_vector() -> serde_json::Value {
+    json!({
+        "crypto": {
+            "cipher": "aes-128-ctr",
+            "cipherparams": {"iv": "83dbcc02d8ccb40e466191a123791e0e"},
+            "ciphertext": "d172bf743a674da9cdad04534d56926ef8358534d458fffccd4e6ad2fbde479c",
+            "kdf": "scrypt",
+            "kdfparams": {
+                "dklen": 32,
+                "n": 262144,
+                "p": 8,
+                "r": 1,
+                "salt": "ab0c7876052600dd703518d6fc3fe8984592145b591fc8fb5c6d43190334ba19"
+            },
+            "mac": "2103ac29920d71da29f15d75b4a16dbe95cfd7ff8faea1056c33131d846e3097"
+        },
+        "id": "3198bc9c-6672-5ab3-d995-4942343ae5b6",
+        "version": 3
+    })
+}
+
+fn decrypt(keystore: serde_json::Value, password: &str) -> Result<Address, KeystoreError> {
+    let keystore: KeystoreFile = serde_json::from_value(keystore)?;
+    Ok(keystore.decrypt(password)?.address())
+}
+
+fn describe(result: &Result<Address, KeystoreError>) -> String {
+    match result {
+        Ok(address) => format!("{:#x}", address),
+        Err(e) => e.to_string(),
+    }
+}
+
+fn main() {
+    let mut passed = true;
+
+    // 1. Spec vectors, including scrypt with r = 1 that RFC 7914 bounds would reject
+    for (name, vector) in [("pbkdf2 spec vector", pbkdf2_vector()), ("scrypt spec vector", scrypt_vector())] {
+        let result = decrypt(vector, PASSWORD);
+        check(&mut passed, name, matches!(result, Ok(a) if a == SPEC_ADDRESS), describe(&result));
+    }
+
+    // 2. Wrong passphrase fails the MAC check
+    let result = decrypt(pbkdf2_vector(), "wrongpassword");
+    check(
+        &mut passed,
+        "Wrong passphrase",
+        matches!(result, Err(KeystoreError::InvalidPassword)),
+        describe(&result),
+    );
+
+    // 3. Written keystores decrypt to the same key
+    let dir = std::env::temp_dir().join(format!("test_keystore_{}", std::process::id()));
+    let round_trip = keystore::create_keystore(&dir, PASSWORD)
+        .and_then(|(address, path)| Ok((address, keystore::decrypt_keystore_file(&path, PASSWORD)?.address())));
+    let _ = std::fs::remove_dir_all(&dir);
+    check(
+        &mut passed,
+        "Round trip",
+        matches!(round_trip, Ok((written, read)) if written == read),
+        format!("{:?}", round_trip.map_err(|e| e.to_string())),
+    );
+
+    // 4. Costs above the bounds are refused
+    for (name, field, value) in [
+        ("n above bound", "n", json!(1u64 << 21)),
+        ("p above bound", "p", json!(17)),
+        ("Memory above bound", "r", json!(4096)),
+        ("n not a power of two", "n", json!(262143)),
+    ] {
+        let mut vector = scrypt_vector();
+        vector["crypto"]["kdfparams"][field] = value;
+        let result = decrypt(vector, PASSWORD);
+        check(
+            &mut passed,
+            name,
+            matches!(result, Err(KeystoreError::Unsupported(_))),
+            describe(&result),
+        );
+    }
+
+    if !passed {
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,433 @@
+// Test program for the shared Li.Fi client
+//
+// Points LifiClient at a local mock of the Li.Fi API and checks route search,
+// step transaction building, API key headers, error handling without panics,
+// key rotation under 429s and quota headers, the bound on waiting for a free
+// key, and the classification of Li.Fi error bodies. No network access needed.
+//
+// Usage: cargo run --bin test_lifi_client
+
+mod support;
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+use worker::chain::{ethereum, Chain, EvmChain, LocalSigner, Signer, WalletAccount};
+use worker::module::lifi::{LifiClient, LifiConfig, LifiError, RouteSearchParams};
+
+use support::{check, Request, Response};
+
+/// Throwaway key; holds no funds.
+const TEST_KEY: &str = "0x0123456789012345678901234567890123456789012345678901234567890123";
+const TEST_API_KEY: &str = "test-api-key";
+/// Always answered with 429 and `Retry-After: 1`.
+const LIMITED_KEY: &str = "limited-key";
+/// Answered normally, but reports its quota as used up for 30 seconds.
+const QUOTA_KEY: &str = "quota-key";
+/// Reports its quota as used up until a Unix time in the past.
+const EPOCH_KEY: &str = "epoch-key";
+/// Reports its quota as used up until a Unix time decades away.
+const FAR_EPOCH_KEY: &str = "far-epoch-key";
+
+type Requests = Arc<Mutex<Vec<Request>>>;
+
+async fn spawn_mock() -> (String, Requests) {
+    let requests: Requests = Arc::default();
+    let recorded = requests.clone();
+    let url = support::serve(move |request| {
+        let (limited, headers) = rate_limit(request.header("x-lifi-api-key"));
+        let response = match limited {
+            Some(status) => Response::json(json!({"message": "Too many requests"})).with_status(status),
+            None => {
+                let (status, reply) = respond(&request.path, &request.json());
+                Response::json(reply).with_status(status)
+            }
+        };
+        recorded.lock().unwrap().push(request);
+        headers
+            .iter()
+            .fold(response, |response, (name, value)| response.with_header(name, value))
+    })
+    .await;
+    (url, requests)
+}
+
+fn token(symbol: &str) -> Value {
+    json!({
+        "address": "0x5555555555555555555555555555555555555555",
+        "chainId": 1,
+        "symbol": symbol,
+        "decimals": 18,
+        "name": symbol,
+        "coinKey": symbol,
+        "logoURI": "",
+        "priceUSD": "1"
+    })
+}
+
+fn step() -> Value {
+    json!({
+        "id": "step-1",
+        "type": "swap",
+        "tool": "mock",
+        "toolDetails": {"key": "mock", "name": "Mock", "logoURI": ""},
+        "action": {
+            "fromToken": token("USDC"),
+            "fromAmount": "1000000",
+            "toToken": token("WETH"),
+            "fromChainId": 1,
+            "toChainId": 1,
+            "slippage": 0.003
+        },
+        "estimate": {
+            "fromAmount": "1000000",
+            "toAmount": "400000000000000",
+            "toAmountMin": "398800000000000",
+            "tool": "mock",
+            "approvalAddress": "0x1231deb6f5749ef6ce6943a275a1d3e7486f4eae",
+            "executionDuration": 30,
+            "feeCosts": [],
+            "gasCosts": []
+        },
+        "includedSteps": [],
+        "integrator": "fee-demo",
+        "referrer": ""
+    })
+}
+
+/// Status override (`None` answers normally) and rate limit headers for `api_key`.
+fn rate_limit(api_key: Option<&str>) -> (Option<u16>, &'static [(&'static str, &'static str)]) {
+    match api_key {
+        Some(LIMITED_KEY) => (Some(429), &[("Retry-After", "1")]),
+        Some(QUOTA_KEY) => (None, &[("RateLimit-Remaining", "0"), ("RateLimit-Reset", "30")]),
+        Some(EPOCH_KEY) => (None, &[("RateLimit-Remaining", "0"), ("RateLimit-Reset", "1000000000")]),
+        Some(FAR_EPOCH_KEY) => (None, &[("RateLimit-Remaining", "0"), ("RateLimit-Reset", "4000000000")]),
+        _ => (None, &[("RateLimit-Remaining", "100")]),
+    }
+}
+
+fn respond(path: &str, request: &Value) -> (u16, Value) {
+    match path {
+        "/v1/advanced/routes" if request["fromChainId"] == 0 => (400, json!({"message": "Invalid fromChainId", "code": 1011})),
+        // No route: one tool without liquidity, one that timed out
+        "/v1/advanced/routes" if request["fromAmount"] == "1" => (
+            200,
+            json!({
+                "routes": [],
+                "unavailableRoutes": {
+                    "filteredOut": [],
+                    "failed": [{
+                        "overallPath": "1:USDC-1inch-1:WETH",
+                        "subpaths": {
+                            "1:USDC-openocean-1:WETH": [{"errorType": "NO_QUOTE", "code": "INSUFFICIENT_LIQUIDITY", "tool": "openocean", "message": "Not enough liquidity"}],
+                            "1:USDC-1inch-1:WETH": [{"errorType": "NO_QUOTE", "code": "TOOL_TIMEOUT", "tool": "1inch", "message": "Request timed out"}]
+                        }
+                    }]
+                }
+            }),
+        ),
+        "/v1/advanced/routes" => (
+            200,
+            json!({
+                "routes": [{
+                    "id": "route-1",
+                    "fromChainId": 1,
+                    "fromAmountUSD": "1.00",
+                    "fromAmount": "1000000",
+                    "fromToken": token("USDC"),
+                    "toChainId": 1,
+                    "toAmountUSD": "1.00",
+                    "toAmount": "400000000000000",
+                    "toAmountMin": "398800000000000",
+                    "toToken": token("WETH"),
+                    "gasCostUSD": "0.10",
+                    "containsSwitchChain": false,
+                    "steps": [step()],
+                    "tags": ["CHEAPEST"]
+                }],
+                "unavailableRoutes": {"filteredOut": [], "failed": []}
+            }),
+        ),
+        "/v1/advanced/stepTransaction" => {
+            let mut response = request.clone();
+            response["transactionRequest"] = json!({
+                "data": "0x",
+                "to": "0x1231deb6f5749ef6ce6943a275a1d3e7486f4eae",
+                "value": "0x0",
+                "from": request["action"]["fromAddress"],
+                "chainId": 1,
+                "gasLimit": "0x30d40",
+                "gasPrice": "0x3b9aca00"
+            });
+            (200, response)
+        }
+        _ => (404, json!({"message": "Not found"})),
+    }
+}
+
+fn client(base_url: &str, api_keys: &[&str]) -> LifiClient {
+    LifiClient::new(LifiConfig {
+        base_url: base_url.to_string(),
+        api_keys: api_keys.iter().map(|key| key.to_string()).collect(),
+        timeout: Duration::from_secs(5),
+        ..LifiConfig::default()
+    })
+    .expect("valid Li.Fi config")
+}
+
+fn last(requests: &Requests) -> Request {
+    requests.lock().unwrap().last().cloned().unwrap_or_default()
+}
+
+/// API keys of the last `n` requests, oldest first.
+fn last_keys(requests: &Requests, n: usize) -> Vec<String> {
+    let requests = requests.lock().unwrap();
+    requests[requests.len().saturating_sub(n)..]
+        .iter()
+        .map(|request| {
+            request.header("x-lifi-api-key").unwrap_or_default().to_string()
+        })
+        .collect()
+}
+
+async fn search(lifi: &LifiClient) -> Result<usize, String> {
+    lifi.find_routes(RouteSearchParams {
+        from_chain_id: 1,
+        to_chain_id: 1,
+        ..Default::default()
+    })
+    .await
+    .map(|response| response.routes.len())
+    .map_err(|e| e.to_string())
+}
+
+#[tokio::main]
+async fn main() {
+    let mut passed = true;
+    let (url, requests) = spawn_mock().await;
+    let signer: Arc<dyn Signer> = Arc::new(LocalSigner::from_hex(TEST_KEY).expect("test key"));
+    let chain: Arc<dyn Chain> = Arc::new(
+        EvmChain::new(ethereum::default_config(), vec![WalletAccount::Signer(signer.clone())]).expect("valid config"),
+    );
+
+    // 1. Route search against the configured base URL, with the API key header
+    let lifi = client(&format!("{}/v1/", url), &[TEST_API_KEY]);
+    let routes = lifi
+        .find_routes(RouteSearchParams {
+            from_chain_id: 1,
+            to_chain_id: 1,
+            ..Default::default()
+        })
+        .await;
+    let route_request = last(&requests);
+    check(
+        &mut passed,
+        "Find routes",
+        routes.as_ref().is_ok_and(|response| response.routes.len() == 1)
+            && route_request.path == "/v1/advanced/routes"
+            && route_request.headers.contains(&format!("x-lifi-api-key: {}", TEST_API_KEY))
+            && route_request.json()["options"]["order"] == "CHEAPEST",
+        format!("{} {:?}", route_request.path, routes.as_ref().err().map(|e| e.to_string())),
+    );
+
+    // 2. Step transaction for the best route, sent from the chain's signer
+    let transaction = match &routes {
+        Ok(response) => Some(lifi.build_best_route_transaction(response, chain.clone()).await),
+        Err(_) => None,
+    };
+    let step_request = last(&requests);
+    let from = format!("{:#x}", signer.address());
+    check(
+        &mut passed,
+        "Build transaction",
+        matches!(&transaction, Some(Ok(tx)) if tx.transaction_request.from == from && tx.transaction_request.chain_id == 1)
+            && step_request.path == "/v1/advanced/stepTransaction"
+            && step_request.json()["action"]["toAddress"] == from.as_str(),
+        format!("{:?}", transaction.map(|tx| tx.map(|tx| tx.transaction_request.to).map_err(|e| e.to_string()))),
+    );
+
+    // 3. API errors and unreachable servers are errors, not panics
+    let rejected = lifi
+        .find_routes(RouteSearchParams {
+            from_chain_id: 0,
+            ..Default::default()
+        })
+        .await;
+    let unreachable = client("http://127.0.0.1:1/v1", &[]).find_routes(RouteSearchParams::default()).await;
+    check(
+        &mut passed,
+        "Errors",
+        rejected.as_ref().is_err_and(|e| e.to_string().contains("400")) && unreachable.is_err(),
+        format!(
+            "{}; {}",
+            rejected.err().map(|e| e.to_string()).unwrap_or_default(),
+            unreachable.err().map(|e| e.to_string()).unwrap_or_default()
+        ),
+    );
+
+    // 4. Without a key no x-lifi-api-key header is sent; invalid keys are rejected up front
+    let keyless = client(&format!("{}/v1", url), &[]);
+    let _ = keyless.find_routes(RouteSearchParams::default()).await;
+    let keyless_request = last(&requests);
+    let invalid_key = LifiClient::new(LifiConfig {
+        api_keys: vec![TEST_API_KEY.to_string(), "bad\nkey".to_string()],
+        ..LifiConfig::default()
+    });
+    check(
+        &mut passed,
+        "API key headers",
+        !keyless_request.headers.contains("x-lifi-api-key") && invalid_key.is_err(),
+        format!("{:?}", invalid_key.err().map(|e| e.to_string())),
+    );
+
+    // 5. LIFI_API_KEYS is a comma-separated set used round-robin
+    let keys = LifiConfig::parse_api_keys(" key-a, key-b ,,");
+    let rotating = client(&format!("{}/v1", url), &["key-a", "key-b"]);
+    for _ in 0..4 {
+        let _ = search(&rotating).await;
+    }
+    let used = last_keys(&requests, 4);
+    check(
+        &mut passed,
+        "Round-robin keys",
+        keys == ["key-a", "key-b"] && used == ["key-a", "key-b", "key-a", "key-b"],
+        format!("{:?}", used),
+    );
+
+    // 6. A 429 puts the key into cooldown and the request is retried with the next key
+    let failover = client(&format!("{}/v1", url), &[LIMITED_KEY, TEST_API_KEY]);
+    let first = search(&failover).await;
+    let second = search(&failover).await;
+    let used = last_keys(&requests, 3);
+    let usage = failover.key_usage();
+    check(
+        &mut passed,
+        "429 failover",
+        first == Ok(1)
+            && second == Ok(1)
+            && used == [LIMITED_KEY, TEST_API_KEY, TEST_API_KEY]
+            && usage[0].rate_limited == 1
+            && usage[0].cooling_down
+            && !usage[1].cooling_down,
+        format!("{:?}, {:?}", used, usage),
+    );
+
+    // 7. A key reporting RateLimit-Remaining: 0 is skipped until its reset
+    let quota = client(&format!("{}/v1", url), &[QUOTA_KEY, TEST_API_KEY]);
+    for _ in 0..3 {
+        let _ = search(&quota).await;
+    }
+    let used = last_keys(&requests, 3);
+    let usage = quota.key_usage();
+    check(
+        &mut passed,
+        "Quota headers",
+        used == [QUOTA_KEY, TEST_API_KEY, TEST_API_KEY] && usage[0].remaining == Some(0) && usage[1].remaining == Some(100),
+        format!("{:?}, {:?}", used, usage),
+    );
+
+    // 8. When every key keeps returning 429, Retry-After is honoured and the retries run out
+    let exhausted = LifiClient::new(LifiConfig {
+        base_url: format!("{}/v1", url),
+        api_keys: vec![LIMITED_KEY.to_string()],
+        max_retries: 1,
+        ..LifiConfig::default()
+    })
+    .expect("valid Li.Fi config");
+    let started = Instant::now();
+    let result = search(&exhausted).await;
+    let elapsed = started.elapsed();
+    check(
+        &mut passed,
+        "Retries exhausted",
+        result.as_ref().is_err_and(|e| e.contains("rate limited"))
+            && elapsed >= Duration::from_secs(1)
+            && exhausted.key_usage()[0].rate_limited == 2,
+        format!("{:?} after {:?}", result, elapsed),
+    );
+
+    // 9. Waiting for a free key is bounded; Unix-time resets are read as such
+    let impatient = |api_key: &str| {
+        LifiClient::new(LifiConfig {
+            base_url: format!("{}/v1", url),
+            api_keys: vec![api_key.to_string()],
+            max_retries: 5,
+            max_key_wait: Duration::from_millis(200),
+            ..LifiConfig::default()
+        })
+        .expect("valid Li.Fi config")
+    };
+    let limited = impatient(LIMITED_KEY);
+    let started = Instant::now();
+    let bounded = search(&limited).await;
+    let bounded_elapsed = started.elapsed();
+    let past = impatient(EPOCH_KEY);
+    let past_results = (search(&past).await, search(&past).await);
+    let far = impatient(FAR_EPOCH_KEY);
+    let started = Instant::now();
+    let far_results = (search(&far).await, search(&far).await);
+    let far_elapsed = started.elapsed();
+    check(
+        &mut passed,
+        "Bounded key wait",
+        bounded.as_ref().is_err_and(|e| e.contains("rate limited"))
+            && bounded_elapsed < Duration::from_secs(1)
+            && limited.key_usage()[0].rate_limited == 1
+            && past_results == (Ok(1), Ok(1))
+            && far_results.0 == Ok(1)
+            && far_results.1.as_ref().is_err_and(|e| e.contains("rate limited"))
+            && far_elapsed < Duration::from_secs(1),
+        format!(
+            "{:?} after {:?}; past reset {:?}; far reset {:?} after {:?}",
+            bounded, bounded_elapsed, past_results, far_results, far_elapsed
+        ),
+    );
+
+    // 10. Empty route lists and error bodies become typed errors
+    let no_route = lifi
+        .find_routes(RouteSearchParams {
+            from_amount: "1".to_string(),
+            deny_tools: vec!["lifuel".to_string()],
+            ..Default::default()
+        })
+        .await;
+    let denied = last(&requests).json()["options"]["exchanges"]["deny"].clone();
+    let liquidity = matches!(&no_route, Err(e @ LifiError::InsufficientLiquidity { failures, .. })
+        if failures.len() == 2 && e.failed_tools() == ["1inch"] && e.skips_pair() && !e.is_retryable());
+    let invalid_token = LifiError::from_response(400, r#"{"message": "Unknown token 0xdead", "code": 1011}"#);
+    let slippage = LifiError::from_response(409, r#"{"message": "The price changed", "code": 1007}"#);
+    let no_quote = LifiError::from_response(
+        404,
+        r#"{"message": "No available quotes", "code": 1002, "errors": {"filteredOut": [], "failed": [{"overallPath": "x", "subpaths": {"x": [{"code": "NO_POSSIBLE_ROUTE", "tool": "relay", "message": "No route"}]}}]}}"#,
+    );
+    let tool = LifiError::from_response(424, r#"{"message": "Bridge is paused", "code": 1008, "tool": "relay"}"#);
+    let server = LifiError::from_response(502, "Bad Gateway");
+    check(
+        &mut passed,
+        "Error classification",
+        liquidity
+            && denied == json!(["lifuel"])
+            && invalid_token.kind() == "invalid_token"
+            && invalid_token.skips_pair()
+            && slippage.kind() == "slippage"
+            && slippage.is_retryable()
+            && no_quote.kind() == "no_route"
+            && no_quote.failed_tools().is_empty()
+            && tool.failed_tools() == ["relay"]
+            && !tool.is_retryable()
+            && matches!(&server, LifiError::Api { status: 502, code: None, .. })
+            && server.is_retryable(),
+        format!(
+            "{}; {}; {}; {}",
+            no_route.err().map(|e| e.to_string()).unwrap_or_default(),
+            no_quote,
+            tool,
+            server
+        ),
+    );
+
+    if !passed {
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,246 @@
+// Test program for Multicall3 batching
+//
+// Starts local mock JSON-RPC nodes that implement aggregate3 (or lack
+// Multicall3 entirely) and checks batching, splitting, per-call failures and
+// the plain eth_call fallback. No network access needed.
+//
+// Usage: cargo run --bin test_multicall
+
+mod support;
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use alloy_primitives::{keccak256, Address, U256};
+use worker::chain::multicall::{CallError, Multicall, MulticallConfig, TypedCall, MULTICALL3_ADDRESS};
+use worker::chain::rpc::{BlockTag, RpcClient};
+
+use support::{check, Response};
+
+/// Larger aggregate3 batches are rejected, like a node's response size limit.
+const MAX_CALLS_PER_REQUEST: usize = 40;
+/// Token whose `balanceOf` always reverts.
+const BAD_TOKEN: Address = Address::repeat_byte(0xba);
+
+#[derive(Default)]
+struct Hits {
+    aggregate: AtomicU64,
+    plain: AtomicU64,
+    get_code: AtomicU64,
+}
+
+async fn spawn_mock_node(deployed: bool) -> (String, Arc<Hits>) {
+    let hits = Arc::new(Hits::default());
+    let counters = hits.clone();
+    let url = support::serve(move |request| respond(&request.json(), deployed, &counters)).await;
+    (url, hits)
+}
+
+fn word(value: usize) -> [u8; 32] {
+    U256::from(value).to_be_bytes()
+}
+
+fn read(data: &[u8], position: usize) -> usize {
+    U256::from_be_slice(&data[position..position + 32]).to::<usize>()
+}
+
+/// `Error("bad token")`
+fn revert_payload() -> Vec<u8> {
+    let mut data = keccak256("Error(string)")[..4].to_vec();
+    data.extend_from_slice(&word(32));
+    data.extend_from_slice(&word(9));
+    let mut text = b"bad token".to_vec();
+    text.resize(32, 0);
+    data.extend_from_slice(&text);
+    data
+}
+
+/// Mock token: `balanceOf(owner)` returns the owner address as a number.
+fn execute(target: Address, data: &[u8]) -> (bool, Vec<u8>) {
+    if target == BAD_TOKEN || data.len() != 36 {
+        return (false, revert_payload());
+    }
+    (true, data[4..36].to_vec())
+}
+
+/// Decodes aggregate3 calldata, executes each call and encodes the results.
+fn aggregate3(calldata: &[u8]) -> Result<Vec<u8>, usize> {
+    let args = &calldata[4..];
+    let array = read(args, 0);
+    let count = read(args, array);
+    if count > MAX_CALLS_PER_REQUEST {
+        return Err(count);
+    }
+    let elements = array + 32;
+    let results: Vec<(bool, Vec<u8>)> = (0..count)
+        .map(|index| {
+            let tuple = elements + read(args, elements + 32 * index);
+            let target = Address::from_slice(&args[tuple + 12..tuple + 32]);
+            let bytes = tuple + read(args, tuple + 64);
+            let length = read(args, bytes);
+            execute(target, &args[bytes + 32..bytes + 32 + length])
+        })
+        .collect();
+
+    let mut out = word(32).to_vec();
+    out.extend_from_slice(&word(count));
+    let mut offset = 32 * count;
+    for (_, data) in &results {
+        out.extend_from_slice(&word(offset));
+        offset += 96 + data.len().div_ceil(32) * 32;
+    }
+    for (success, data) in &results {
+        out.extend_from_slice(&word(*success as usize));
+        out.extend_from_slice(&word(64));
+        out.extend_from_slice(&word(data.len()));
+        out.extend_from_slice(data);
+        out.resize(out.len() + data.len().div_ceil(32) * 32 - data.len(), 0);
+    }
+    Ok(out)
+}
+
+fn respond(request: &serde_json::Value, deployed: bool, hits: &Hits) -> Response {
+    let hex_result = |data: &[u8]| serde_json::json!({"result": format!("0x{}", hex::encode(data))});
+
+    let reply = match request["method"].as_str() {
+        Some("eth_getCode") => {
+            hits.get_code.fetch_add(1, Ordering::SeqCst);
+            hex_result(if deployed { &[0x60, 0x80] } else { &[] })
+        }
+        Some("eth_call") => {
+            let call = &request["params"][0];
+            let target: Address = call["to"].as_str().unwrap_or_default().parse().unwrap_or_default();
+            let data = hex::decode(call["input"].as_str().or(call["data"].as_str()).unwrap_or("0x").trim_start_matches("0x"))
+                .unwrap_or_default();
+            if target == MULTICALL3_ADDRESS && deployed {
+                hits.aggregate.fetch_add(1, Ordering::SeqCst);
+                if data[..4] != keccak256("aggregate3((address,bool,bytes)[])")[..4] {
+                    serde_json::json!({"error": {"code": -32000, "message": "unknown selector"}})
+                } else {
+                    match aggregate3(&data) {
+                        Ok(out) => hex_result(&out),
+                        Err(count) => serde_json::json!({"error": {"code": -32000, "message": format!("response size exceeded ({} calls)", count)}}),
+                    }
+                }
+            } else {
+                hits.plain.fetch_add(1, Ordering::SeqCst);
+                match execute(target, &data) {
+                    (true, out) => hex_result(&out),
+                    (false, payload) => serde_json::json!({"error": {
+                        "code": 3,
+                        "message": "execution reverted: bad token",
+                        "data": format!("0x{}", hex::encode(payload)),
+                    }}),
+                }
+            }
+        }
+        _ => serde_json::json!({"result": null}),
+    };
+
+    support::rpc_reply(request, reply)
+}
+
+/// 100 balance reads; every 25th token reverts.
+fn balance_calls() -> Vec<TypedCall<U256>> {
+    (0..100u8)
+        .map(|i| {
+            let token = if i % 25 == 0 { BAD_TOKEN } else { Address::repeat_byte(0x10) };
+            TypedCall::balance_of(token, Address::with_last_byte(i))
+        })
+        .collect()
+}
+
+/// Every good call returns its owner index, every bad one a decoded revert.
+fn results_correct(results: &[Result<U256, CallError>]) -> bool {
+    results.len() == 100
+        && results.iter().enumerate().all(|(i, result)| match result {
+            Ok(balance) => i % 25 != 0 && *balance == U256::from(i),
+            Err(CallError::Reverted { reason, .. }) => i % 25 == 0 && reason == "bad token",
+            Err(_) => false,
+        })
+}
+
+#[tokio::main]
+async fn main() {
+    let mut passed = true;
+    let calls = balance_calls();
+
+    // 1. One aggregate3 per batch; oversized batches are halved until accepted
+    let (url, hits) = spawn_mock_node(true).await;
+    let rpc = RpcClient::new(&url);
+    let multicall = Multicall::new(MulticallConfig::default());
+    let results = multicall.call_typed(&rpc, &calls, BlockTag::Latest).await;
+    check(
+        &mut passed,
+        "Batch with split on node limit",
+        results.as_ref().is_ok_and(|results| results_correct(results))
+            && hits.aggregate.load(Ordering::SeqCst) == 7
+            && hits.plain.load(Ordering::SeqCst) == 0,
+        format!(
+            "{} aggregate3 requests, {} plain calls",
+            hits.aggregate.load(Ordering::SeqCst),
+            hits.plain.load(Ordering::SeqCst)
+        ),
+    );
+
+    // 2. Deployment check is cached
+    let _ = multicall.call_typed(&rpc, &calls[..5], BlockTag::Latest).await;
+    check(
+        &mut passed,
+        "Cached deployment check",
+        hits.get_code.load(Ordering::SeqCst) == 1,
+        format!("{} eth_getCode requests", hits.get_code.load(Ordering::SeqCst)),
+    );
+
+    // 3. Gas limit splits up front: 30k per balanceOf, 300k per batch
+    let (url, hits) = spawn_mock_node(true).await;
+    let rpc = RpcClient::new(&url);
+    let multicall = Multicall::new(MulticallConfig {
+        max_batch_gas: 300_000,
+        ..MulticallConfig::default()
+    });
+    let results = multicall.call_typed(&rpc, &calls, BlockTag::Latest).await;
+    check(
+        &mut passed,
+        "Gas-based batches",
+        results.as_ref().is_ok_and(|results| results_correct(results)) && hits.aggregate.load(Ordering::SeqCst) == 10,
+        format!("{} aggregate3 requests", hits.aggregate.load(Ordering::SeqCst)),
+    );
+
+    // 4. Chain without Multicall3: plain eth_call per call, same results
+    let (url, hits) = spawn_mock_node(false).await;
+    let rpc = RpcClient::new(&url);
+    let multicall = Multicall::new(MulticallConfig::default());
+    let results = multicall.call_typed(&rpc, &calls, BlockTag::Latest).await;
+    check(
+        &mut passed,
+        "Fallback without Multicall3",
+        results.as_ref().is_ok_and(|results| results_correct(results))
+            && hits.plain.load(Ordering::SeqCst) == 100
+            && hits.aggregate.load(Ordering::SeqCst) == 0,
+        format!("{} plain calls", hits.plain.load(Ordering::SeqCst)),
+    );
+
+    // 5. Batching disabled in config: no deployment check at all
+    let (url, hits) = spawn_mock_node(true).await;
+    let rpc = RpcClient::new(&url);
+    let multicall = Multicall::new(MulticallConfig {
+        address: None,
+        ..MulticallConfig::default()
+    });
+    let results = multicall.call_typed(&rpc, &calls[..10], BlockTag::Latest).await;
+    check(
+        &mut passed,
+        "Disabled batching",
+        results.is_ok() && hits.get_code.load(Ordering::SeqCst) == 0 && hits.plain.load(Ordering::SeqCst) == 10,
+        format!(
+            "{} eth_getCode, {} plain calls",
+            hits.get_code.load(Ordering::SeqCst),
+            hits.plain.load(Ordering::SeqCst)
+        ),
+    );
+
+    if !passed {
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,195 @@
+// Test program for nonce gap detection and filling
+//
+// Runs a NonceManager against a local mock node that queues transactions
+// behind a missing nonce like a real mempool, then checks that queued
+// transactions are not reported as dropped, that only the gap is filled, that
+// really dropped and stuck nonces are told apart, and that a timed out send and
+// the maintenance pass repair what they find. No network access needed.
+//
+// Usage: cargo run --bin test_nonce_gaps
+
+mod support;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use alloy_primitives::{Address, Bytes, B256, U256};
+use worker::chain::nonce::{NonceIssue, NonceIssueKind};
+use worker::chain::rpc::EndpointConfig;
+use worker::chain::tx::{send_transaction, SendOptions, TxError, TxFees, TypedTransaction};
+use worker::chain::{ethereum, BlockTag, Chain, EvmChain, LocalSigner, Signer, WalletAccount};
+
+use support::check;
+use support::node::{Mining, MockNode};
+
+/// Throwaway key; holds no funds.
+const TEST_KEY: &str = "0x0123456789012345678901234567890123456789012345678901234567890123";
+const STUCK_AFTER: Duration = Duration::from_secs(60);
+
+fn fees() -> TxFees {
+    TxFees::Legacy {
+        gas_price: U256::from(2_000_000_000u64),
+    }
+}
+
+fn transfer(nonce: u64) -> TypedTransaction {
+    TypedTransaction {
+        chain_id: ethereum::CHAIN_ID,
+        nonce,
+        gas_limit: 21_000,
+        fees: fees(),
+        to: Some(Address::repeat_byte(0x22)),
+        value: U256::from(1),
+        data: Bytes::new(),
+    }
+}
+
+/// Signs and broadcasts a transfer with `nonce` and records it like `send_transaction` does.
+async fn broadcast(chain: &EvmChain, signer: &dyn Signer, nonce: u64) -> B256 {
+    let signed = signer.sign_transaction(&transfer(nonce)).expect("sign");
+    let hash = chain.rpc().send_raw_transaction(&signed.raw).await.expect("broadcast");
+    chain.nonces().mark_broadcast(signer.address(), nonce, hash);
+    hash
+}
+
+fn kinds(issues: &Result<Vec<NonceIssue>, impl std::fmt::Display>) -> Vec<(u64, NonceIssueKind)> {
+    issues
+        .as_ref()
+        .map(|issues| issues.iter().map(|issue| (issue.nonce, issue.kind)).collect())
+        .unwrap_or_default()
+}
+
+#[tokio::main]
+async fn main() {
+    let mut passed = true;
+    let node = MockNode::start(ethereum::CHAIN_ID).await;
+    node.state().mining = Mining::Manual;
+    let signer: Arc<dyn Signer> = Arc::new(LocalSigner::from_hex(TEST_KEY).expect("test key"));
+    let address = signer.address();
+    node.state().balances.insert(address, U256::from(10u64).pow(U256::from(18)));
+
+    let mut config = ethereum::default_config();
+    config.rpc_endpoints = vec![EndpointConfig::new(&node.url)];
+    let chain = EvmChain::new(config, vec![WalletAccount::Signer(signer.clone())]).expect("valid config");
+    let nonces = chain.nonces();
+
+    // Nonce 0 failed to send, 1 and 2 are queued behind it, 3 was lost by the node
+    for _ in 0..4 {
+        let _ = nonces.reserve(&chain, address).await;
+    }
+    nonces.release(address, 0);
+    let queued = [broadcast(&chain, signer.as_ref(), 1).await, broadcast(&chain, signer.as_ref(), 2).await];
+    nonces.mark_broadcast(address, 3, B256::repeat_byte(0xdd));
+
+    // 1. Only the gap is an issue; the queued transactions and the one above them wait for it
+    let issues = nonces.find_issues(&chain, address, STUCK_AFTER).await;
+    check(
+        &mut passed,
+        "Gap with queued transactions",
+        kinds(&issues) == [(0, NonceIssueKind::Gap)],
+        format!("{:?}", kinds(&issues)),
+    );
+
+    // 2. Filling stops at the first live nonce: one cancel, the queued transactions are untouched
+    let filled = nonces.fill_gaps(&chain, signer.as_ref(), fees(), STUCK_AFTER).await;
+    let pending_after_fill = chain.rpc().get_transaction_count(address, BlockTag::Pending).await;
+    check(
+        &mut passed,
+        "Fill up to the first live nonce",
+        filled.as_ref().is_ok_and(|hashes| hashes.len() == 1)
+            && node.requests("eth_sendRawTransaction").len() == 3
+            && matches!(pending_after_fill, Ok(3)),
+        format!(
+            "{:?}, node pending nonce {:?}",
+            filled.as_ref().map(|hashes| hashes.len()).map_err(|e| e.to_string()),
+            pending_after_fill.map_err(|e| e.to_string())
+        ),
+    );
+
+    // 3. Once the gap is mined the transaction the node never had is dropped and filled
+    node.mine();
+    let issues = nonces.find_issues(&chain, address, STUCK_AFTER).await;
+    let filled = nonces.fill_gaps(&chain, signer.as_ref(), fees(), STUCK_AFTER).await;
+    node.mine();
+    let mined_queued = queued.iter().all(|hash| node.state().mined.get(hash).is_some_and(|tx| tx.success));
+    check(
+        &mut passed,
+        "Dropped after the gap",
+        kinds(&issues) == [(3, NonceIssueKind::Dropped)]
+            && filled.as_ref().is_ok_and(|hashes| hashes.len() == 1)
+            && mined_queued
+            && node.state().nonce(address) == 4,
+        format!(
+            "{:?}, queued transactions mined: {}, node nonce {}",
+            kinds(&issues),
+            mined_queued,
+            node.state().nonce(address)
+        ),
+    );
+
+    // 4. A pending transaction older than the threshold is stuck and not cancelled
+    let _ = nonces.resync(&chain, address).await;
+    let nonce = nonces.reserve(&chain, address).await.unwrap_or_default();
+    broadcast(&chain, signer.as_ref(), nonce).await;
+    let issues = nonces.find_issues(&chain, address, Duration::ZERO).await;
+    let filled = nonces.fill_gaps(&chain, signer.as_ref(), fees(), Duration::ZERO).await;
+    check(
+        &mut passed,
+        "Stuck is left alone",
+        kinds(&issues) == [(nonce, NonceIssueKind::Stuck)] && filled.as_ref().is_ok_and(|hashes| hashes.is_empty()),
+        format!("{:?}", kinds(&issues)),
+    );
+
+    // 5. A send that times out because the node dropped it has its nonce cancelled
+    node.mine();
+    let _ = nonces.resync(&chain, address).await;
+    let nonce = nonces.reserve(&chain, address).await.unwrap_or_default();
+    let dropper = node.clone();
+    let dropping = tokio::spawn(async move {
+        let started = tokio::time::Instant::now();
+        while dropper.state().pool.is_empty() && started.elapsed() < Duration::from_secs(5) {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        dropper.state().pool.clear();
+    });
+    let options = SendOptions {
+        confirmations: Some(1),
+        poll_interval: Duration::from_millis(20),
+        timeout: Duration::from_millis(300),
+        ..SendOptions::default()
+    };
+    let result = send_transaction(&chain, signer.as_ref(), &transfer(nonce), &options).await;
+    let _ = dropping.await;
+    let cancel = node
+        .state()
+        .pool
+        .iter()
+        .find(|tx| tx.tx.nonce == nonce)
+        .map(|tx| (tx.tx.to, tx.tx.value));
+    check(
+        &mut passed,
+        "Repair after timeout",
+        matches!(result, Err(TxError::Timeout { .. })) && cancel == Some((Some(address), U256::ZERO)),
+        format!("{:?}; pooled at nonce {}: {:?}", result.map(|r| r.transaction_hash).map_err(|e| e.to_string()), nonce, cancel),
+    );
+
+    // 6. The maintenance pass fills a gap for every signing account
+    node.mine();
+    let _ = nonces.resync(&chain, address).await;
+    let gap = nonces.reserve(&chain, address).await.unwrap_or_default();
+    let next = nonces.reserve(&chain, address).await.unwrap_or_default();
+    nonces.release(address, gap);
+    broadcast(&chain, signer.as_ref(), next).await;
+    let filled = nonces.maintain(&chain, STUCK_AFTER).await;
+    node.mine();
+    check(
+        &mut passed,
+        "Maintenance pass",
+        filled.len() == 1 && node.state().nonce(address) == next + 1,
+        format!("{} cancel(s), node nonce {}", filled.len(), node.state().nonce(address)),
+    );
+
+    if !passed {
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,622 @@
+// Test program for the multi-step Li.Fi route executor
+//
+// Starts a local mock that answers both JSON-RPC (a fixed native balance) and
+// the Li.Fi stepTransaction and status endpoints, then checks the progress
+// store, resuming a step that was sent before a restart without resending it,
+// that the next step is quoted with the previous step's actual output, and
+// tracking of bridge transfers through /status. A second mock node then mines
+// a two-step swap route end to end (recording its approval in the ledger),
+// both in one run and resumed from the store by a new executor, and once more with a reorg that orphans the first
+// swap's block after it was confirmed. No network access needed.
+//
+// Usage: cargo run --bin test_route_executor
+
+mod support;
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use alloy_primitives::{Address, B256, U256};
+use serde_json::{json, Value};
+use worker::chain::rpc::EndpointConfig;
+use worker::chain::{ethereum, Chain, ChainSet, EvmChain, LocalSigner, Signer, WalletAccount};
+use worker::module::erc20::approvals::ApprovalLedger;
+use worker::module::lifi::progress::{CompletedStep, PendingStep};
+use worker::module::lifi::{
+    LifiClient, LifiConfig, LifiError, LifiRoute, RouteExecutor, RouteProgress, RouteStatus, RouteStore,
+    StatusRequest, TransferStatus,
+};
+
+use support::node::{MockNode, NodeState, NodeTx};
+use support::{check, Request, Response};
+
+/// Throwaway key; holds no funds.
+const TEST_KEY: &str = "0x0123456789012345678901234567890123456789012345678901234567890123";
+/// Native balance the mock reports for every account.
+const BALANCE: u64 = 1_500;
+/// Bridge source transactions the mock /status reports as completed, refunded,
+/// still waiting for the destination, and unknown (404).
+const COMPLETED_TX: B256 = B256::repeat_byte(1);
+const REFUNDED_TX: B256 = B256::repeat_byte(2);
+const WAITING_TX: B256 = B256::repeat_byte(3);
+const UNKNOWN_TX: B256 = B256::repeat_byte(4);
+/// Destination transaction reported for `COMPLETED_TX`.
+const RECEIVING_TX: B256 = B256::repeat_byte(9);
+/// Router and tokens of the mined swap route: ETH -> TOKEN_A -> TOKEN_B.
+const ROUTER: Address = Address::repeat_byte(0x77);
+const TOKEN_A: Address = Address::repeat_byte(0xa0);
+const TOKEN_B: Address = Address::repeat_byte(0xb0);
+/// TOKEN_A the router pays for the first swap, unlike the quoted 1000.
+const SWAP_OUTPUT: u64 = 987;
+
+type Requests = Arc<Mutex<Vec<Request>>>;
+
+async fn spawn_mock() -> (String, Requests) {
+    let requests: Requests = Arc::default();
+    let recorded = requests.clone();
+    let url = support::serve(move |request| {
+        let body = request.json();
+        let response = if request.path == "/advanced/stepTransaction" {
+            Response::json(json!({"message": "Quote expired", "code": 1007})).with_status(400)
+        } else if request.path.starts_with("/status") {
+            let (status, reply) = transfer_status(&request.path);
+            Response::json(reply).with_status(status)
+        } else {
+            let reply = match body["method"].as_str() {
+                Some("eth_getBalance") => json!({"result": format!("{:#x}", BALANCE)}),
+                _ => support::rpc_error(-32601, "method not found"),
+            };
+            support::rpc_reply(&body, reply)
+        };
+        recorded.lock().unwrap().push(request);
+        response
+    })
+    .await;
+    (url, requests)
+}
+
+/// Li.Fi mock whose stepTransaction returns a router call carrying the swap
+/// direction (1: ETH -> TOKEN_A, 2: TOKEN_A -> TOKEN_B) and the quoted amount.
+async fn spawn_swap_lifi() -> (String, Requests) {
+    let requests: Requests = Arc::default();
+    let recorded = requests.clone();
+    let url = support::serve(move |request| {
+        let mut step = request.json();
+        let amount: U256 = step["action"]["fromAmount"].as_str().and_then(|a| a.parse().ok()).unwrap_or_default();
+        let native = step["action"]["fromToken"]["address"] == format!("{:#x}", Address::ZERO).as_str();
+        let mut data = vec![if native { 1 } else { 2 }];
+        data.extend_from_slice(&amount.to_be_bytes::<32>());
+        step["transactionRequest"] = json!({
+            "data": format!("0x{}", hex::encode(data)),
+            "to": format!("{:#x}", ROUTER),
+            "value": if native { amount.to_string() } else { "0".to_string() },
+            "from": step["action"]["fromAddress"],
+            "chainId": ethereum::CHAIN_ID,
+            "gasLimit": "200000",
+            "gasPrice": "2000000000"
+        });
+        recorded.lock().unwrap().push(request);
+        Response::json(step)
+    })
+    .await;
+    (url, requests)
+}
+
+/// Forwards every request to `node`. Once a transaction is mined after the
+/// proxy started, the block of the latest one is replaced by a fork right
+/// before the second head poll: after the receipt was confirmed on the old
+/// fork, before the executor checks it again.
+async fn spawn_reorging_proxy(node: &MockNode) -> String {
+    let node = node.clone();
+    let mined_before = node.state().mined.len();
+    let head_polls = Arc::new(AtomicU64::new(0));
+    support::serve(move |request| {
+        let request = request.json();
+        if request["method"] == "eth_getBlockByNumber"
+            && node.state().mined.len() > mined_before
+            && head_polls.fetch_add(1, Ordering::SeqCst) == 1
+        {
+            node.state().reorg(1);
+        }
+        node.handle(&request)
+    })
+    .await
+}
+
+/// Router of the swap route: pays SWAP_OUTPUT of TOKEN_A for ETH, and half the
+/// amount in TOKEN_B for TOKEN_A it is allowed to pull.
+fn router(state: &mut NodeState, tx: &NodeTx) -> bool {
+    if tx.tx.to != Some(ROUTER) || tx.tx.data.len() < 33 {
+        return true;
+    }
+    let amount = U256::from_be_slice(&tx.tx.data[1..33]);
+    let (token_out, amount_out) = match tx.tx.data[0] {
+        1 => (TOKEN_A, U256::from(SWAP_OUTPUT)),
+        _ => {
+            let allowance = state.allowances.get(&(TOKEN_A, tx.from, ROUTER)).copied().unwrap_or_default();
+            if allowance < amount || !state.transfer(TOKEN_A, tx.from, ROUTER, amount) {
+                return false;
+            }
+            state.allowances.insert((TOKEN_A, tx.from, ROUTER), allowance - amount);
+            (TOKEN_B, amount / U256::from(2))
+        }
+    };
+    *state.token_balances.entry((token_out, tx.from)).or_default() += amount_out;
+    true
+}
+
+fn transfer_status(path: &str) -> (u16, Value) {
+    let receiving = |amount: &str, symbol: &str| {
+        json!({"txHash": format!("{:#x}", RECEIVING_TX), "amount": amount, "token": token(symbol), "chainId": 1})
+    };
+    if path.contains(&format!("{:#x}", COMPLETED_TX)) {
+        (200, json!({"status": "DONE", "substatus": "COMPLETED", "tool": "stargate", "receiving": receiving("123", "ETH")}))
+    } else if path.contains(&format!("{:#x}", REFUNDED_TX)) {
+        (
+            200,
+            json!({
+                "status": "DONE",
+                "substatus": "REFUNDED",
+                "substatusMessage": "The tokens were refunded to the sender address",
+                "tool": "stargate",
+                "receiving": receiving("990", "USDC")
+            }),
+        )
+    } else if path.contains(&format!("{:#x}", WAITING_TX)) {
+        (200, json!({"status": "PENDING", "substatus": "WAIT_DESTINATION_TRANSACTION", "tool": "stargate"}))
+    } else {
+        (404, json!({"message": "Not found", "code": 1011}))
+    }
+}
+
+fn token(symbol: &str) -> Value {
+    token_at(symbol, Address::ZERO)
+}
+
+fn token_at(symbol: &str, address: Address) -> Value {
+    json!({
+        "address": format!("{:#x}", address),
+        "chainId": 1,
+        "symbol": symbol,
+        "decimals": 18,
+        "name": symbol,
+        "coinKey": symbol,
+        "logoURI": "",
+        "priceUSD": "1"
+    })
+}
+
+fn step(id: &str) -> Value {
+    json!({
+        "id": id,
+        "type": "swap",
+        "tool": "mock",
+        "toolDetails": {"key": "mock", "name": "Mock", "logoURI": ""},
+        "action": {
+            "fromToken": token("ETH"),
+            "fromAmount": "1000",
+            "toToken": token("ETH"),
+            "fromChainId": 1,
+            "toChainId": 1,
+            "slippage": 0.003
+        },
+        "estimate": {
+            "fromAmount": "1000",
+            "toAmount": "1000",
+            "toAmountMin": "990",
+            "tool": "mock",
+            "approvalAddress": "0x0000000000000000000000000000000000000000",
+            "executionDuration": 0,
+            "feeCosts": [],
+            "gasCosts": []
+        },
+        "includedSteps": [],
+        "integrator": "test",
+        "referrer": ""
+    })
+}
+
+fn route(id: &str, steps: usize) -> LifiRoute {
+    route_with(id, (1..=steps).map(|n| step(&format!("{}-step-{}", id, n))).collect())
+}
+
+/// ETH -> TOKEN_A -> TOKEN_B through ROUTER.
+fn swap_route(id: &str) -> LifiRoute {
+    let mut steps = Vec::new();
+    for (n, (from, to)) in [(("ETH", Address::ZERO), ("A", TOKEN_A)), (("A", TOKEN_A), ("B", TOKEN_B))]
+        .into_iter()
+        .enumerate()
+    {
+        let mut step = step(&format!("{}-step-{}", id, n + 1));
+        step["action"]["fromToken"] = token_at(from.0, from.1);
+        step["action"]["toToken"] = token_at(to.0, to.1);
+        step["estimate"]["approvalAddress"] = format!("{:#x}", ROUTER).into();
+        steps.push(step);
+    }
+    route_with(id, steps)
+}
+
+fn route_with(id: &str, steps: Vec<Value>) -> LifiRoute {
+    serde_json::from_value(json!({
+        "id": id,
+        "fromChainId": 1,
+        "fromAmountUSD": "1.00",
+        "fromAmount": "1000",
+        "fromToken": token("ETH"),
+        "toChainId": 1,
+        "toAmountUSD": "1.00",
+        "toAmount": "1000",
+        "toAmountMin": "990",
+        "toToken": token("ETH"),
+        "gasCostUSD": "0.10",
+        "containsSwitchChain": false,
+        "steps": steps,
+        "tags": []
+    }))
+    .expect("valid route fixture")
+}
+
+fn pending(index: usize, recipient: Address, balance_before: u64, min_amount: u64) -> PendingStep {
+    PendingStep {
+        index,
+        chain_id: ethereum::CHAIN_ID,
+        token: Address::ZERO,
+        recipient,
+        balance_before: U256::from(balance_before),
+        min_amount: U256::from(min_amount),
+        timeout_secs: 0,
+        tx_hash: None,
+    }
+}
+
+fn completed(step_id: &str, received: u64) -> CompletedStep {
+    CompletedStep {
+        step_id: step_id.to_string(),
+        tool: "mock".to_string(),
+        chain_id: ethereum::CHAIN_ID,
+        tx_hash: None,
+        received: U256::from(received),
+        destination_tx_hash: None,
+    }
+}
+
+/// `fromAmount` of every stepTransaction request for `route_id`, in order.
+fn quoted_amounts(requests: &Requests, route_id: &str) -> Vec<String> {
+    requests
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|request| request.json())
+        .filter(|step| step["id"].as_str().is_some_and(|id| id.starts_with(route_id)))
+        .map(|step| step["action"]["fromAmount"].as_str().unwrap_or_default().to_string())
+        .collect()
+}
+
+fn sent(requests: &Requests) -> usize {
+    requests
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|request| request.json()["method"] == "eth_sendRawTransaction")
+        .count()
+}
+
+#[tokio::main]
+async fn main() {
+    let mut passed = true;
+    let (url, requests) = spawn_mock().await;
+    let signer: Arc<dyn Signer> = Arc::new(LocalSigner::from_hex(TEST_KEY).expect("test key"));
+    let from = signer.address();
+
+    let mut config = ethereum::default_config();
+    config.rpc_endpoints = vec![EndpointConfig::new(&url)];
+    let chain: Arc<dyn Chain> =
+        Arc::new(EvmChain::new(config, vec![WalletAccount::Signer(signer.clone())]).expect("valid config"));
+    let lifi = Arc::new(
+        LifiClient::new(LifiConfig {
+            base_url: url.clone(),
+            status_poll_interval: Duration::from_millis(10),
+            ..LifiConfig::default()
+        })
+        .expect("Li.Fi client"),
+    );
+    let chains = Arc::new(ChainSet::new(chain));
+
+    let dir = std::env::temp_dir().join(format!("test_route_executor_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let store = RouteStore::new(&dir);
+    let executor = RouteExecutor::new(lifi.clone(), chains.clone(), store.clone());
+
+    // 1. Progress survives a round trip through the store; finished routes are not resumed
+    let mut saved = RouteProgress::new(&route("0xabc:1/2", 2), from);
+    let first_save = store.save(&mut saved);
+    let listed = store.in_progress().unwrap_or_default().len();
+    saved.status = RouteStatus::Completed;
+    let second_save = store.save(&mut saved);
+    let loaded = store.load("0xabc:1/2").ok().flatten();
+    check(
+        &mut passed,
+        "Progress store",
+        first_save.is_ok()
+            && second_save.is_ok()
+            && listed == 1
+            && store.in_progress().is_ok_and(|routes| routes.is_empty())
+            && loaded.as_ref().is_some_and(|p| p.steps.len() == 2 && p.status == RouteStatus::Completed),
+        format!("{} file(s) in {}", std::fs::read_dir(&dir).map(|d| d.count()).unwrap_or(0), dir.display()),
+    );
+
+    // 2. A step sent before a restart is settled from the balance, not sent again
+    let mut arrived = RouteProgress::new(&route("arrived", 2), from);
+    arrived.completed.push(completed("arrived-step-1", 1_000));
+    arrived.pending = Some(pending(1, from, 1_000, 400));
+    let _ = store.save(&mut arrived);
+    // 3. If the funds never show up the route is failed for a manual check instead of resent
+    let mut lost = RouteProgress::new(&route("lost", 1), from);
+    lost.pending = Some(pending(0, from, 1_000, 1_000));
+    let _ = store.save(&mut lost);
+
+    let resumed = executor.resume_pending().await.unwrap_or_default();
+    let arrived = store.load("arrived").ok().flatten();
+    let lost = store.load("lost").ok().flatten();
+    check(
+        &mut passed,
+        "Resume sent step",
+        resumed.len() == 1
+            && arrived.as_ref().is_some_and(|p| {
+                p.status == RouteStatus::Completed
+                    && p.pending.is_none()
+                    && p.completed.len() == 2
+                    && p.completed[1].tx_hash.is_none()
+                    && p.completed[1].received == U256::from(BALANCE - 1_000)
+            }),
+        format!("{:?}", arrived.map(|p| (p.status, p.completed.len()))),
+    );
+    check(
+        &mut passed,
+        "Unknown outcome",
+        lost.as_ref().is_some_and(|p| matches!(&p.status, RouteStatus::Failed(reason) if reason.contains("unknown")))
+            && sent(&requests) == 0,
+        format!("{:?}", lost.map(|p| p.status)),
+    );
+
+    // 4. The next step is quoted with the previous step's actual output and a failure keeps it resumable
+    let two_steps = route("chained", 2);
+    let mut chained = RouteProgress::new(&two_steps, from);
+    chained.completed.push(completed("chained-step-1", 777));
+    let _ = store.save(&mut chained);
+    let result = executor.execute(&two_steps, from).await;
+    let step_request = requests
+        .lock()
+        .unwrap()
+        .iter()
+        .rev()
+        .find(|request| request.path == "/advanced/stepTransaction")
+        .cloned();
+    let chained = store.load("chained").ok().flatten();
+    check(
+        &mut passed,
+        "Chained amounts",
+        result.as_ref().is_err_and(|e| e.to_string().contains("step 2/2"))
+            && step_request.as_ref().is_some_and(|r| {
+                r.json()["id"] == "chained-step-2"
+                    && r.json()["action"]["fromAmount"] == "777"
+                    && r.json()["action"]["fromAddress"] == format!("{:#x}", from).as_str()
+            })
+            && chained.as_ref().is_some_and(|p| {
+                p.status == RouteStatus::InProgress && p.pending.is_none() && p.last_error.is_some()
+            })
+            && sent(&requests) == 0,
+        format!(
+            "{}; {:?}",
+            result.err().map(|e| e.to_string()).unwrap_or_default(),
+            step_request.map(|r| r.json()["action"]["fromAmount"].clone())
+        ),
+    );
+
+    // 5. Finished routes and routes without steps are not executed
+    let before = requests.lock().unwrap().len();
+    let again = executor.execute(&route("arrived", 2), from).await;
+    let empty = executor.execute(&route("empty", 0), from).await;
+    check(
+        &mut passed,
+        "Finished and empty routes",
+        again.is_err() && empty.is_err() && requests.lock().unwrap().len() == before,
+        format!(
+            "{}; {}",
+            again.err().map(|e| e.to_string()).unwrap_or_default(),
+            empty.err().map(|e| e.to_string()).unwrap_or_default()
+        ),
+    );
+
+    // 6. The status poller reports the final state, the received amount and the destination tx
+    let request = |tx_hash| StatusRequest::new(tx_hash, "stargate", 42161, 1);
+    let completed_status = lifi.get_status(&request(COMPLETED_TX)).await;
+    let status_request = requests.lock().unwrap().last().cloned();
+    let unknown = lifi.get_status(&request(UNKNOWN_TX)).await;
+    let waiting = lifi.wait_for_transfer(&request(WAITING_TX), Duration::from_millis(50)).await;
+    check(
+        &mut passed,
+        "Status poller",
+        completed_status.as_ref().is_ok_and(|s| {
+            s.is_completed() && s.received_amount() == Some(U256::from(123)) && s.receiving_tx_hash() == Some(RECEIVING_TX)
+        }) && status_request.as_ref().is_some_and(|r| {
+            r.path.contains("bridge=stargate") && r.path.contains("fromChain=42161") && r.path.contains("toChain=1")
+        }) && unknown.as_ref().is_ok_and(|s| s.status == TransferStatus::NotFound)
+            && matches!(&waiting, Err(LifiError::TransferTimeout { last_status: Some(last), .. }) if last.contains("WaitDestinationTransaction")),
+        format!(
+            "{:?}; {}",
+            status_request.map(|r| r.path),
+            waiting.err().map(|e| e.to_string()).unwrap_or_default()
+        ),
+    );
+
+    // 7. A bridge step sent before a restart is settled from /status; a refund fails the route
+    let bridged_store = RouteStore::new(dir.join("bridged"));
+    let bridged_executor = RouteExecutor::new(lifi.clone(), chains.clone(), bridged_store.clone());
+    let bridge_route = |id: &str| {
+        let mut route = route(id, 1);
+        route.steps[0].action.from_chain_id = 42161;
+        route.steps[0].tool = "stargate".to_string();
+        route
+    };
+    for (id, tx_hash) in [("bridged", COMPLETED_TX), ("refunded", REFUNDED_TX)] {
+        let mut progress = RouteProgress::new(&bridge_route(id), from);
+        progress.pending = Some(PendingStep {
+            tx_hash: Some(tx_hash),
+            ..pending(0, from, 1_000, 100)
+        });
+        let _ = bridged_store.save(&mut progress);
+    }
+    let _ = bridged_executor.resume_pending().await;
+    let bridged = bridged_store.load("bridged").ok().flatten();
+    let refunded = bridged_store.load("refunded").ok().flatten();
+    check(
+        &mut passed,
+        "Resume bridge step",
+        bridged.as_ref().is_some_and(|p| {
+            p.status == RouteStatus::Completed
+                && p.completed[0].received == U256::from(123)
+                && p.completed[0].tx_hash == Some(COMPLETED_TX)
+                && p.completed[0].destination_tx_hash == Some(RECEIVING_TX)
+        }) && refunded.as_ref().is_some_and(|p| {
+            p.pending.is_none()
+                && matches!(&p.status, RouteStatus::Failed(reason) if reason.contains("Refunded") && reason.contains("990 USDC"))
+        }) && sent(&requests) == 0,
+        format!("{:?}", refunded.map(|p| p.status)),
+    );
+
+    // 8. A mined two-step route: the second step is quoted with the first step's measured output
+    let node = MockNode::start(ethereum::CHAIN_ID).await;
+    {
+        let mut state = node.state();
+        state.balances.insert(from, U256::from(10u64).pow(U256::from(18)));
+        state.on_mined = Some(Box::new(router));
+    }
+    std::env::set_var("APPROVAL_LEDGER_PATH", dir.join("approvals.jsonl"));
+    let mut config = ethereum::default_config();
+    config.rpc_endpoints = vec![EndpointConfig::new(&node.url)];
+    config.confirmations = 1;
+    // The mock node has no eth_simulateV1; the output is measured after mining instead
+    config.allow_unchecked_output = true;
+    let swap_chain: Arc<dyn Chain> =
+        Arc::new(EvmChain::new(config, vec![WalletAccount::Signer(signer.clone())]).expect("valid config"));
+    let swap_chains = Arc::new(ChainSet::new(swap_chain));
+    let (swap_url, swap_requests) = spawn_swap_lifi().await;
+    let swap_lifi = Arc::new(
+        LifiClient::new(LifiConfig {
+            base_url: swap_url,
+            ..LifiConfig::default()
+        })
+        .expect("Li.Fi client"),
+    );
+    let swap_dir = dir.join("swaps");
+    let swap_executor = RouteExecutor::new(swap_lifi.clone(), swap_chains.clone(), RouteStore::new(&swap_dir));
+
+    let result = swap_executor.execute(&swap_route("mined"), from).await;
+    let amounts = quoted_amounts(&swap_requests, "mined");
+    let mined = node.state().mined.len();
+    check(
+        &mut passed,
+        "Mined route",
+        result.as_ref().is_ok_and(|p| {
+            p.status == RouteStatus::Completed
+                && p.completed.len() == 2
+                && p.completed.iter().all(|step| step.tx_hash.is_some())
+                && p.completed[0].received == U256::from(SWAP_OUTPUT)
+                && p.completed[1].received == U256::from(SWAP_OUTPUT / 2)
+        }) && amounts == ["1000", SWAP_OUTPUT.to_string().as_str()]
+            // swap, approve, swap
+            && mined == 3
+            && node.state().token_balance(TOKEN_B, from) == U256::from(SWAP_OUTPUT / 2),
+        format!(
+            "{:?}; quoted {:?}; {} transaction(s) mined",
+            result.map(|p| p.completed.iter().map(|step| step.received).collect::<Vec<_>>()).map_err(|e| e.to_string()),
+            amounts,
+            mined
+        ),
+    );
+
+    // 9. The executor's approval of the step's approvalAddress is in the ledger for the inventory
+    let ledger = ApprovalLedger::new(dir.join("approvals.jsonl")).entries().map_err(|e| e.to_string());
+    check(
+        &mut passed,
+        "Approval recorded",
+        ledger.as_ref().is_ok_and(|entries| {
+            entries.iter().any(|entry| {
+                entry.token == TOKEN_A
+                    && entry.owner == from
+                    && entry.spender == ROUTER
+                    && entry.amount == U256::from(SWAP_OUTPUT)
+                    && entry.chain_id == ethereum::CHAIN_ID
+            })
+        }),
+        format!("{:?}", ledger.map(|entries| entries.iter().map(|e| (e.token, e.spender, e.amount)).collect::<Vec<_>>())),
+    );
+
+    // 10. After a restart a new executor picks the route up from the store at the next step
+    let mut interrupted = RouteProgress::new(&swap_route("interrupted"), from);
+    interrupted.completed.push(CompletedStep {
+        tx_hash: Some(B256::repeat_byte(5)),
+        ..completed("interrupted-step-1", 600)
+    });
+    let _ = RouteStore::new(&swap_dir).save(&mut interrupted);
+    *node.state().token_balances.entry((TOKEN_A, from)).or_default() += U256::from(600);
+
+    let restarted = RouteExecutor::new(swap_lifi.clone(), swap_chains, RouteStore::new(&swap_dir));
+    let resumed = restarted.resume_pending().await.unwrap_or_default();
+    let amounts = quoted_amounts(&swap_requests, "interrupted");
+    let interrupted = RouteStore::new(&swap_dir).load("interrupted").ok().flatten();
+    check(
+        &mut passed,
+        "Resume from store",
+        resumed.len() == 1
+            && amounts == ["600"]
+            && interrupted.as_ref().is_some_and(|p| {
+                p.status == RouteStatus::Completed && p.completed.len() == 2 && p.completed[1].received == U256::from(300)
+            }),
+        format!("quoted {:?}; {:?}", amounts, interrupted.map(|p| (p.status, p.completed.len()))),
+    );
+
+    // 11. A reorg that orphans a confirmed swap makes the executor wait for its re-inclusion
+    let proxy = spawn_reorging_proxy(&node).await;
+    let mut config = ethereum::default_config();
+    config.rpc_endpoints = vec![EndpointConfig::new(&proxy)];
+    config.confirmations = 1;
+    config.allow_unchecked_output = true;
+    let reorg_chain = Arc::new(EvmChain::new(config, vec![WalletAccount::Signer(signer.clone())]).expect("valid config"));
+    let reorg_executor = RouteExecutor::new(
+        swap_lifi,
+        Arc::new(ChainSet::new(reorg_chain.clone())),
+        RouteStore::new(dir.join("reorged")),
+    );
+    let result = reorg_executor.execute(&swap_route("reorged"), from).await;
+    let swap = result.as_ref().ok().and_then(|p| p.completed[0].tx_hash).unwrap_or_default();
+    let block = node.state().mined.get(&swap).map(|mined| mined.block).unwrap_or_default();
+    let receipt_polls = node
+        .requests("eth_getTransactionReceipt")
+        .iter()
+        .filter(|params| params[0] == format!("{:#x}", swap).as_str())
+        .count();
+    let canonical = reorg_chain.blocks().canonical_hash(block);
+    check(
+        &mut passed,
+        "Reorg after confirmation",
+        result.as_ref().is_ok_and(|p| p.status == RouteStatus::Completed && p.completed.len() == 2)
+            && node.state().forks.len() == 1
+            && receipt_polls >= 2
+            && canonical == Some(node.state().block_hash(block)),
+        format!(
+            "{:?}; {} receipt poll(s) for {:#x}, canonical {:?}",
+            result.map(|p| p.status).map_err(|e| e.to_string()),
+            receipt_polls,
+            swap,
+            canonical
+        ),
+    );
+
+    let _ = std::fs::remove_dir_all(&dir);
+    if !passed {
+        std::process::exit(1);
+    }
+}
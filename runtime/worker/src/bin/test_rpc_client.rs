@@ -0,0 +1,233 @@
+// Test program for the JSON-RPC client owned by a Chain
+//
+// Points an EvmChain at a local mock node and checks the typed reads
+// (chain id, blocks, balances, nonces, fees, eth_call), raw transaction
+// submission and the request parameters sent, then maps node errors, HTTP
+// errors and malformed results from small mock servers to RpcError. No network
+// access needed.
+//
+// Usage: cargo run --bin test_rpc_client
+
+mod support;
+
+use std::sync::{Arc, Mutex};
+
+use alloy_primitives::{keccak256, Address, B256, U256};
+use serde_json::{json, Value};
+use worker::chain::rpc::{CallRequest, EndpointConfig};
+use worker::chain::{ethereum, BlockTag, Chain, EvmChain, RpcClient, RpcError};
+
+use support::{check, Response};
+
+const WALLET: Address = Address::repeat_byte(0x11);
+const TOKEN: Address = Address::repeat_byte(0x70);
+const HEAD: u64 = 100;
+
+type Requests = Arc<Mutex<Vec<(String, Value)>>>;
+
+fn describe<T: std::fmt::Debug>(result: &Result<T, RpcError>) -> String {
+    match result {
+        Ok(value) => format!("{:?}", value),
+        Err(e) => e.to_string(),
+    }
+}
+
+fn block_hash(number: u64) -> B256 {
+    let mut hash = [0xb1; 32];
+    hash[24..].copy_from_slice(&number.to_be_bytes());
+    B256::from(hash)
+}
+
+fn block(number: u64) -> Value {
+    json!({
+        "number": format!("{:#x}", number),
+        "hash": block_hash(number),
+        "parentHash": block_hash(number - 1),
+        "timestamp": format!("{:#x}", 1_700_000_000 + number * 12),
+        "gasLimit": "0x1c9c380",
+        "gasUsed": "0x0",
+        "baseFeePerGas": "0x3b9aca00",
+    })
+}
+
+/// Answers the reads the client makes with fixed values and records every call.
+fn node_reply(request: &Value) -> Value {
+    let params = &request["params"];
+    match request["method"].as_str().unwrap_or_default() {
+        "eth_chainId" => json!({"result": "0x1"}),
+        "eth_blockNumber" => json!({"result": format!("{:#x}", HEAD)}),
+        "eth_getBlockByNumber" => match params[0].as_str() {
+            Some("latest") => json!({"result": block(HEAD)}),
+            Some(number) => {
+                let number = u64::from_str_radix(number.trim_start_matches("0x"), 16).unwrap_or(u64::MAX);
+                if number <= HEAD {
+                    json!({"result": block(number)})
+                } else {
+                    json!({"result": null})
+                }
+            }
+            None => support::rpc_error(-32602, "invalid block"),
+        },
+        "eth_getBalance" => json!({"result": "0xde0b6b3a7640000"}),
+        "eth_getTransactionCount" => match params[1].as_str() {
+            Some("pending") => json!({"result": "0x4"}),
+            _ => json!({"result": "0x3"}),
+        },
+        "eth_gasPrice" => json!({"result": "0x4190ab00"}),
+        "eth_feeHistory" => json!({"result": {
+            "oldestBlock": "0x61",
+            "baseFeePerGas": ["0x3b9aca00", "0x3b9aca00", "0x3b9aca00", "0x3b9aca00", "0x3b9aca00"],
+            "gasUsedRatio": [0.5, 0.5, 0.5, 0.5],
+            "reward": [["0x5f5e100", "0x5f5e100"], ["0x5f5e100", "0x5f5e100"], ["0x5f5e100", "0x5f5e100"], ["0x5f5e100", "0x5f5e100"]],
+        }}),
+        "eth_call" => json!({"result": format!("0x{:064x}", 4_321)}),
+        "eth_sendRawTransaction" => match params[0].as_str().and_then(|raw| hex::decode(raw.trim_start_matches("0x")).ok()) {
+            Some(raw) if raw.len() > 2 => json!({"result": keccak256(&raw)}),
+            _ => support::rpc_error(-32000, "rlp: value size exceeds available input length"),
+        },
+        _ => support::rpc_error(-32601, "the method does not exist/is not available"),
+    }
+}
+
+async fn spawn_mock_node() -> (String, Requests) {
+    let requests: Requests = Arc::default();
+    let recorded = requests.clone();
+    let url = support::serve(move |request| {
+        let request = request.json();
+        let method = request["method"].as_str().unwrap_or_default().to_string();
+        recorded.lock().unwrap().push((method, request["params"].clone()));
+        support::rpc_reply(&request, node_reply(&request))
+    })
+    .await;
+    (url, requests)
+}
+
+/// Parameters of the last call to `method`.
+fn last_params(requests: &Requests, method: &str) -> Value {
+    let requests = requests.lock().unwrap();
+    requests
+        .iter()
+        .rev()
+        .find(|(name, _)| name == method)
+        .map(|(_, params)| params.clone())
+        .unwrap_or_default()
+}
+
+#[tokio::main]
+async fn main() {
+    let mut passed = true;
+    let (url, requests) = spawn_mock_node().await;
+    let mut config = ethereum::default_config();
+    config.rpc_endpoints = vec![EndpointConfig::new(&url)];
+    let chain = EvmChain::new(config, Vec::new()).expect("valid config");
+    let rpc = chain.rpc();
+
+    // 1. Chain id and the head block with its parent
+    let chain_id = rpc.chain_id().await;
+    let head = rpc.block_number().await;
+    let latest = rpc.get_latest_block().await;
+    let parent = rpc.get_block(BlockTag::Number(HEAD - 1)).await;
+    let future = rpc.get_block(BlockTag::Number(HEAD + 1)).await;
+    check(
+        &mut passed,
+        "Chain id and head",
+        matches!(chain_id, Ok(1))
+            && matches!(head, Ok(HEAD))
+            && matches!((&latest, &parent), (Ok(latest), Ok(Some(parent)))
+                if latest.number.to::<u64>() == HEAD && Some(latest.parent_hash) == parent.hash)
+            && matches!(future, Ok(None))
+            && last_params(&requests, "eth_getBlockByNumber") == json!(["0x65", false]),
+        format!("chain {}, head {}", describe(&chain_id), describe(&head)),
+    );
+
+    // 2. Balances and nonces at the requested block, raw transactions by hash
+    let balance = rpc.get_balance(WALLET, BlockTag::Latest).await;
+    let counts = (
+        rpc.get_transaction_count(WALLET, BlockTag::Latest).await.ok(),
+        rpc.get_transaction_count(WALLET, BlockTag::Pending).await.ok(),
+    );
+    let raw = [0x02, 0xf8, 0x6c, 0x01];
+    let sent = rpc.send_raw_transaction(&raw).await;
+    check(
+        &mut passed,
+        "Balance, nonces and raw transaction",
+        matches!(balance, Ok(balance) if balance == U256::from(10u64).pow(U256::from(18)))
+            && counts == (Some(3), Some(4))
+            && last_params(&requests, "eth_getTransactionCount") == json!([format!("{:#x}", WALLET), "pending"])
+            && matches!(sent, Ok(hash) if hash == keccak256(raw))
+            && last_params(&requests, "eth_sendRawTransaction") == json!(["0x02f86c01"]),
+        format!("balance {}, nonces {:?}, sent {}", describe(&balance), counts, describe(&sent)),
+    );
+
+    // 3. Gas price and fee history with the parameters in the node's format
+    let gas_price = rpc.gas_price().await;
+    let history = rpc.fee_history(4, BlockTag::Latest, &[25.0, 75.0]).await;
+    let params = last_params(&requests, "eth_feeHistory");
+    check(
+        &mut passed,
+        "Fees",
+        matches!(gas_price, Ok(price) if price == U256::from(1_100_000_000u64))
+            && history.as_ref().is_ok_and(|history| {
+                history.base_fee_per_gas.len() == 5
+                    && history.reward.as_ref().is_some_and(|reward| reward.len() == 4 && reward[0].len() == 2)
+            })
+            && params == json!(["0x4", "latest", [25.0, 75.0]]),
+        format!("gas price {}, params {}", describe(&gas_price), params),
+    );
+
+    // 4. eth_call returns the raw output
+    let mut data = vec![0x70, 0xa0, 0x82, 0x31];
+    data.extend_from_slice(WALLET.into_word().as_slice());
+    let call = CallRequest {
+        to: Some(TOKEN),
+        data: Some(data.into()),
+        ..Default::default()
+    };
+    let output = rpc.call(&call, BlockTag::Latest).await;
+    let params = last_params(&requests, "eth_call");
+    check(
+        &mut passed,
+        "eth_call",
+        matches!(&output, Ok(output) if output.len() == 32 && U256::from_be_slice(output) == U256::from(4_321))
+            && params[0]["to"] == format!("{:#x}", TOKEN)
+            && params[1] == "latest",
+        format!("{}, params {}", describe(&output), params),
+    );
+
+    // 5. JSON-RPC error objects keep their code
+    let rejected = rpc.send_raw_transaction(&[0xde, 0xad]).await;
+    let unknown = rpc.request::<_, Value>("eth_unknownMethod", ()).await;
+    check(
+        &mut passed,
+        "Node errors",
+        matches!(rejected, Err(RpcError::Rpc { code: -32000, .. }))
+            && matches!(unknown, Err(RpcError::Rpc { code: -32601, .. })),
+        format!("{}; {}", describe(&rejected), describe(&unknown)),
+    );
+
+    // 6. A null block where one is required
+    let empty = support::serve(|request| support::rpc_reply(&request.json(), json!({"result": null}))).await;
+    let missing = RpcClient::new(&empty).get_latest_block().await;
+    check(
+        &mut passed,
+        "Missing block",
+        matches!(missing, Err(RpcError::Missing(_))),
+        describe(&missing),
+    );
+
+    // 7. HTTP errors and results of the wrong shape
+    let failing = support::serve(|_| Response::text(500, "upstream unavailable")).await;
+    let http = RpcClient::new(&failing).chain_id().await;
+    let malformed = support::serve(|request| support::rpc_reply(&request.json(), json!({"result": "not a number"}))).await;
+    let decode = RpcClient::new(&malformed).chain_id().await;
+    check(
+        &mut passed,
+        "HTTP and decode errors",
+        matches!(http, Err(RpcError::Http { status: 500, .. })) && matches!(decode, Err(RpcError::Decode(_))),
+        format!("{}; {}", describe(&http), describe(&decode)),
+    );
+
+    if !passed {
+        std::process::exit(1);
+    }
+}
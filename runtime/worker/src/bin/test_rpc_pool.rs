@@ -0,0 +1,322 @@
+// Test program for RPC failover, health scoring, rate limits and quorum reads
+//
+// Starts several local mock JSON-RPC servers that misbehave in scripted ways
+// and checks how the RpcClient endpoint pool reacts, including a transaction
+// broadcast that fails over after the first endpoint already accepted it.
+// No network access needed.
+//
+// Usage: cargo run --bin test_rpc_pool
+
+mod support;
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use alloy_primitives::{Address, Bytes, U256};
+use worker::chain::rpc::{EndpointConfig, RpcClient, RpcError};
+use worker::chain::tx::{send_transaction, SendOptions, TxError, TxFees, TypedTransaction};
+use worker::chain::{ethereum, Chain, EvmChain, LocalSigner, Signer, WalletAccount};
+
+use support::node::MockNode;
+use support::{check, Response};
+
+/// Throwaway key; holds no funds.
+const TEST_KEY: &str = "0x0123456789012345678901234567890123456789012345678901234567890123";
+
+/// Scripted behaviour of a mock server.
+#[derive(Clone, Copy, Debug)]
+enum Behavior {
+    /// Answers `eth_blockNumber` with this block number.
+    Healthy(u64),
+    /// Healthy, but every response is delayed.
+    Slow(u64, Duration),
+    /// HTTP 500 for every request.
+    Down,
+    /// HTTP 200 with a body that is not JSON.
+    Garbage,
+    /// HTTP 429 for every request.
+    RateLimited,
+    /// HTTP 400 for every request, as for a request the endpoint refuses.
+    BadRequest,
+    /// Fails the first `n` requests, then behaves like `Healthy`.
+    FailFirst(u64, u64),
+}
+
+struct MockServer {
+    url: String,
+    hits: Arc<AtomicU64>,
+}
+
+async fn spawn_mock(behavior: Behavior) -> MockServer {
+    let hits = Arc::new(AtomicU64::new(0));
+    let counter = hits.clone();
+    let url = support::serve_async(move |request| {
+        let hit = counter.fetch_add(1, Ordering::SeqCst);
+        async move { respond(behavior, hit, &request.json()).await }
+    })
+    .await;
+    MockServer { url, hits }
+}
+
+async fn respond(behavior: Behavior, hit: u64, request: &serde_json::Value) -> Response {
+    let result = |block: u64| support::rpc_reply(request, serde_json::json!({"result": format!("{:#x}", block)}));
+
+    match behavior {
+        Behavior::Healthy(block) => result(block),
+        Behavior::Slow(block, delay) => {
+            tokio::time::sleep(delay).await;
+            result(block)
+        }
+        Behavior::Down => Response::text(500, "upstream unavailable"),
+        Behavior::Garbage => Response::text(200, "<html>bad gateway</html>"),
+        Behavior::RateLimited => Response::text(429, "slow down"),
+        Behavior::BadRequest => Response::text(400, "invalid request"),
+        Behavior::FailFirst(failures, _) if hit < failures => Response::text(503, "warming up"),
+        Behavior::FailFirst(_, block) => result(block),
+    }
+}
+
+fn endpoints(servers: &[&MockServer]) -> Vec<EndpointConfig> {
+    servers.iter().map(|server| EndpointConfig::new(&server.url)).collect()
+}
+
+/// Forwards every request to `node`, but answers broadcasts with a 504 as if
+/// the gateway timed out after the node accepted the transaction.
+async fn spawn_timing_out_gateway(node: &MockNode) -> String {
+    let node = node.clone();
+    support::serve(move |request| {
+        let request = request.json();
+        let response = node.handle(&request);
+        match request["method"].as_str() {
+            Some("eth_sendRawTransaction") => Response::text(504, "gateway timeout"),
+            _ => response,
+        }
+    })
+    .await
+}
+
+fn transfer(nonce: u64) -> TypedTransaction {
+    TypedTransaction {
+        chain_id: ethereum::CHAIN_ID,
+        nonce,
+        gas_limit: 21_000,
+        fees: TxFees::Legacy {
+            gas_price: U256::from(2_000_000_000u64),
+        },
+        to: Some(Address::repeat_byte(0x22)),
+        value: U256::from(1),
+        data: Bytes::new(),
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let mut passed = true;
+
+    // 1. Failover: down, garbage and 429 endpoints are skipped
+    let down = spawn_mock(Behavior::Down).await;
+    let garbage = spawn_mock(Behavior::Garbage).await;
+    let limited = spawn_mock(Behavior::RateLimited).await;
+    let healthy = spawn_mock(Behavior::Healthy(100)).await;
+    let client = RpcClient::with_endpoints(endpoints(&[&down, &garbage, &limited, &healthy]), 1);
+    let result = client.block_number().await;
+    check(&mut passed, "Failover", matches!(result, Ok(100)), format!("{:?}", result));
+    let cooling: Vec<bool> = client.endpoint_health().iter().map(|h| h.cooling_down).collect();
+    check(
+        &mut passed,
+        "Failed endpoints cool down",
+        cooling == [true, true, true, false],
+        format!("{:?}", cooling),
+    );
+    let before = down.hits.load(Ordering::SeqCst);
+    let _ = client.block_number().await;
+    check(
+        &mut passed,
+        "Cooling endpoints are not retried",
+        down.hits.load(Ordering::SeqCst) == before,
+        format!("down endpoint hits stayed at {}", before),
+    );
+
+    // 2. All endpoints down: the last error is returned
+    let client = RpcClient::with_endpoints(endpoints(&[&down, &limited]), 1);
+    let result = client.block_number().await;
+    check(
+        &mut passed,
+        "All endpoints failing",
+        matches!(result, Err(RpcError::Http { .. })),
+        format!("{:?}", result.map_err(|e| e.to_string())),
+    );
+
+    // 3. Client errors: a 400 is returned as is instead of failing over
+    let rejecting = spawn_mock(Behavior::BadRequest).await;
+    let fallback = spawn_mock(Behavior::Healthy(100)).await;
+    let client = RpcClient::with_endpoints(endpoints(&[&rejecting, &fallback]), 1);
+    let result = client.block_number().await;
+    let cooling: Vec<bool> = client.endpoint_health().iter().map(|h| h.cooling_down).collect();
+    check(
+        &mut passed,
+        "Client errors are not retried",
+        matches!(result, Err(RpcError::Http { status: 400, .. }))
+            && fallback.hits.load(Ordering::SeqCst) == 0
+            && cooling == [false, false],
+        format!("{:?}, cooling {:?}", result.map_err(|e| e.to_string()), cooling),
+    );
+
+    // 4. Health scoring: traffic moves to the fast endpoint
+    let slow = spawn_mock(Behavior::Slow(100, Duration::from_millis(300))).await;
+    let fast = spawn_mock(Behavior::Healthy(100)).await;
+    let client = RpcClient::with_endpoints(endpoints(&[&slow, &fast]), 1);
+    for _ in 0..6 {
+        let _ = client.block_number().await;
+    }
+    check(
+        &mut passed,
+        "Latency scoring",
+        client.url() == fast.url && slow.hits.load(Ordering::SeqCst) <= 1,
+        format!(
+            "preferred {} (slow hits {}, fast hits {})",
+            client.url(),
+            slow.hits.load(Ordering::SeqCst),
+            fast.hits.load(Ordering::SeqCst)
+        ),
+    );
+
+    // 5. Recovery: an endpoint that fails at first is used again after its cooldown
+    let flaky = spawn_mock(Behavior::FailFirst(1, 100)).await;
+    let client = RpcClient::with_endpoints(endpoints(&[&flaky]), 1);
+    let first = client.block_number().await;
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    let second = client.block_number().await;
+    check(
+        &mut passed,
+        "Recovery after cooldown",
+        first.is_err() && matches!(second, Ok(100)),
+        format!("first: {}, second: {:?}", first.is_err(), second.map_err(|e| e.to_string())),
+    );
+
+    // 6. Rate limits: 2 req/s on the first endpoint, the rest spills over
+    let primary = spawn_mock(Behavior::Healthy(100)).await;
+    let overflow = spawn_mock(Behavior::Slow(100, Duration::from_millis(50))).await;
+    let client = RpcClient::with_endpoints(
+        vec![
+            EndpointConfig {
+                url: primary.url.clone(),
+                rate_limit: Some(2),
+            },
+            EndpointConfig::new(&overflow.url),
+        ],
+        1,
+    );
+    for _ in 0..6 {
+        let _ = client.block_number().await;
+    }
+    check(
+        &mut passed,
+        "Per-endpoint rate limit",
+        primary.hits.load(Ordering::SeqCst) <= 3 && overflow.hits.load(Ordering::SeqCst) >= 3,
+        format!(
+            "limited hits {}, overflow hits {}",
+            primary.hits.load(Ordering::SeqCst),
+            overflow.hits.load(Ordering::SeqCst)
+        ),
+    );
+
+    // 7. Rate limit only: requests wait for a token instead of failing
+    let only = spawn_mock(Behavior::Healthy(100)).await;
+    let client = RpcClient::with_endpoints(
+        vec![EndpointConfig {
+            url: only.url.clone(),
+            rate_limit: Some(5),
+        }],
+        1,
+    );
+    let started = Instant::now();
+    for _ in 0..8 {
+        let _ = client.block_number().await;
+    }
+    check(
+        &mut passed,
+        "Rate limit waits",
+        started.elapsed() >= Duration::from_millis(500),
+        format!("8 requests at 5 req/s took {:?}", started.elapsed()),
+    );
+
+    // 8. Quorum: two agreeing endpoints outvote a lagging one
+    let a = spawn_mock(Behavior::Healthy(100)).await;
+    let b = spawn_mock(Behavior::Healthy(100)).await;
+    let lagging = spawn_mock(Behavior::Healthy(99)).await;
+    let client = RpcClient::with_endpoints(endpoints(&[&a, &lagging, &b]), 2);
+    let result: Result<alloy_primitives::U64, _> = client.request_quorum("eth_blockNumber", ()).await;
+    check(
+        &mut passed,
+        "Quorum reached",
+        matches!(result, Ok(n) if n.to::<u64>() == 100),
+        format!("{:?}", result.map_err(|e| e.to_string())),
+    );
+
+    // 9. Quorum not reached: disagreeing and failing endpoints
+    let down = spawn_mock(Behavior::Down).await;
+    let client = RpcClient::with_endpoints(endpoints(&[&a, &lagging, &down]), 2);
+    let result: Result<alloy_primitives::U64, _> = client.request_quorum("eth_blockNumber", ()).await;
+    check(
+        &mut passed,
+        "Quorum not reached",
+        matches!(result, Err(RpcError::NoQuorum { agreeing: 1, .. })),
+        format!("{:?}", result.map_err(|e| e.to_string())),
+    );
+
+    for health in client.endpoint_health() {
+        println!(
+            "   {} latency {:.1}ms, error rate {:.2}, requests {}",
+            health.url, health.latency_ms, health.error_rate, health.requests
+        );
+    }
+
+    // 10. A broadcast accepted by an endpoint that then timed out is tracked, not released
+    let node = MockNode::start(ethereum::CHAIN_ID).await;
+    let signer: Arc<dyn Signer> = Arc::new(LocalSigner::from_hex(TEST_KEY).expect("test key"));
+    node.state().balances.insert(signer.address(), U256::from(10u64).pow(U256::from(18)));
+    let gateway = spawn_timing_out_gateway(&node).await;
+    let mut config = ethereum::default_config();
+    config.rpc_endpoints = vec![EndpointConfig::new(&gateway), EndpointConfig::new(&node.url)];
+    config.confirmations = 1;
+    let chain = EvmChain::new(config, vec![WalletAccount::Signer(signer.clone())]).expect("valid config");
+    let options = SendOptions {
+        poll_interval: Duration::from_millis(20),
+        timeout: Duration::from_secs(5),
+        ..SendOptions::default()
+    };
+    let nonce = chain.nonces().reserve(&chain, signer.address()).await.unwrap_or(u64::MAX);
+    let receipt = send_transaction(&chain, signer.as_ref(), &transfer(nonce), &options).await;
+    let next = chain.nonces().reserve(&chain, signer.address()).await;
+    check(
+        &mut passed,
+        "Broadcast after failover",
+        receipt.as_ref().is_ok_and(|r| r.is_success())
+            && node.state().mined.len() == 1
+            && node.requests("eth_sendRawTransaction").len() == 2
+            && matches!(next, Ok(n) if n == nonce + 1),
+        format!(
+            "{:?}, next nonce {:?}",
+            receipt.map(|r| r.transaction_hash).map_err(|e| e.to_string()),
+            next
+        ),
+    );
+    chain.nonces().release(signer.address(), nonce + 1);
+
+    // 11. "nonce too low" for a transaction the node does not know is an error and the nonce is skipped
+    let stale = transfer(nonce);
+    let result = send_transaction(&chain, signer.as_ref(), &TypedTransaction { value: U256::from(2), ..stale }, &options).await;
+    let next = chain.nonces().reserve(&chain, signer.address()).await;
+    check(
+        &mut passed,
+        "Nonce used elsewhere",
+        matches!(&result, Err(TxError::Rpc(e)) if e.is_nonce_too_low()) && matches!(next, Ok(n) if n == nonce + 1),
+        format!("{:?}, next nonce {:?}", result.map(|r| r.transaction_hash).map_err(|e| e.to_string()), next),
+    );
+
+    if !passed {
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,157 @@
+// Test program for pre-flight transaction simulation
+//
+// Decodes revert payloads, then runs simulate() against a local mock node:
+// reverts, gas limits below the estimate, output checks measured with
+// eth_simulateV1 and nodes without it (refused unless unchecked output is
+// allowed). No network access needed.
+//
+// Usage: cargo run --bin test_simulate
+
+mod support;
+
+use alloy_primitives::{Address, Bytes, U256, U64};
+use serde_json::json;
+use worker::chain::rpc::RpcClient;
+use worker::chain::tx::{decode_revert_reason, simulate, OutputCheck, Simulation, TxError};
+use worker::chain::{ethereum, BlockTag, CallRequest};
+
+use support::check;
+use support::node::MockNode;
+
+const ROUTER: Address = Address::repeat_byte(0x77);
+const TOKEN: Address = Address::repeat_byte(0x70);
+const SENDER: Address = Address::repeat_byte(0x11);
+
+/// `Error(string)` revert payload.
+fn error_string(message: &str) -> Bytes {
+    let mut data = vec![0x08, 0xc3, 0x79, 0xa0];
+    data.extend_from_slice(&U256::from(32).to_be_bytes::<32>());
+    data.extend_from_slice(&U256::from(message.len()).to_be_bytes::<32>());
+    let mut padded = message.as_bytes().to_vec();
+    padded.resize(message.len().div_ceil(32) * 32, 0);
+    data.extend_from_slice(&padded);
+    data.into()
+}
+
+fn swap_call(gas: u64) -> CallRequest {
+    CallRequest {
+        from: Some(SENDER),
+        to: Some(ROUTER),
+        gas: Some(U64::from(gas)),
+        data: Some(Bytes::from_static(&[0x12, 0x34, 0x56, 0x78])),
+        ..Default::default()
+    }
+}
+
+fn output_check(min_amount: u64, allow_unchecked: bool) -> OutputCheck {
+    OutputCheck {
+        token: TOKEN,
+        recipient: SENDER,
+        min_amount: U256::from(min_amount),
+        allow_unchecked,
+    }
+}
+
+/// `eth_simulateV1` result for balance read, transaction, balance read.
+fn simulated(before: u64, after: u64) -> serde_json::Value {
+    let balance = |amount: u64| {
+        json!({"returnData": format!("0x{}", hex::encode(U256::from(amount).to_be_bytes::<32>())), "gasUsed": "0x5208", "status": "0x1"})
+    };
+    json!({"result": [{"calls": [balance(before), {"returnData": "0x", "gasUsed": "0xea60", "status": "0x1"}, balance(after)]}]})
+}
+
+fn describe(result: &Result<Simulation, TxError>) -> String {
+    match result {
+        Ok(simulation) => format!("gas {}, output {:?}", simulation.gas_estimate, simulation.output_delta),
+        Err(e) => e.to_string(),
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let mut passed = true;
+
+    // 1. Revert payloads
+    let mut panic = vec![0x4e, 0x48, 0x7b, 0x71];
+    panic.extend_from_slice(&U256::from(0x11).to_be_bytes::<32>());
+    for (name, data, expected) in [
+        ("Error(string)", error_string("Too little received").to_vec(), "Too little received"),
+        ("Panic(uint256)", panic, "panic 0x11 (arithmetic overflow or underflow)"),
+        ("Custom error", vec![0xde, 0xad, 0xbe, 0xef, 0x01], "custom error 0xdeadbeef01"),
+        ("Empty revert", Vec::new(), "reverted without reason"),
+        ("Malformed revert", vec![0x08, 0xc3], "malformed revert data 0x08c3"),
+    ] {
+        let reason = decode_revert_reason(&data);
+        check(&mut passed, name, reason == expected, reason);
+    }
+
+    let node = MockNode::start(ethereum::CHAIN_ID).await;
+    let rpc = RpcClient::new(&node.url);
+
+    // 2. A reverting call is refused with its decoded reason
+    node.state().reverts.insert(ROUTER, error_string("Too little received"));
+    let result = simulate(&rpc, &swap_call(100_000), BlockTag::Pending, None).await;
+    check(
+        &mut passed,
+        "Revert",
+        matches!(&result, Err(TxError::SimulationReverted { reason, .. }) if reason == "Too little received"),
+        describe(&result),
+    );
+    node.state().reverts.clear();
+
+    // 3. The estimate is taken without the call's gas and compared with it afterwards
+    let result = simulate(&rpc, &swap_call(30_000), BlockTag::Pending, None).await;
+    let estimate_requests = node.requests("eth_estimateGas");
+    check(
+        &mut passed,
+        "Gas limit below estimate",
+        matches!(&result, Err(TxError::InvalidRequest(reason)) if reason.contains("60000"))
+            && estimate_requests.last().is_some_and(|params| params[0].get("gas").is_none()),
+        describe(&result),
+    );
+    let result = simulate(&rpc, &swap_call(100_000), BlockTag::Pending, None).await;
+    check(
+        &mut passed,
+        "Gas limit above estimate",
+        matches!(&result, Ok(simulation) if simulation.gas_estimate == 60_000 && simulation.output_delta.is_none()),
+        describe(&result),
+    );
+
+    // 4. Without eth_simulateV1 the output cannot be measured: refused unless allowed
+    let result = simulate(&rpc, &swap_call(100_000), BlockTag::Pending, Some(&output_check(100, false))).await;
+    check(
+        &mut passed,
+        "No eth_simulateV1",
+        matches!(result, Err(TxError::OutputUnchecked(_))),
+        describe(&result),
+    );
+    let result = simulate(&rpc, &swap_call(100_000), BlockTag::Pending, Some(&output_check(100, true))).await;
+    check(
+        &mut passed,
+        "No eth_simulateV1, unchecked output allowed",
+        matches!(&result, Ok(simulation) if simulation.output_delta.is_none()),
+        describe(&result),
+    );
+
+    // 5. Measured output against the minimum
+    node.state().replies.insert("eth_simulateV1".to_string(), simulated(1_000, 1_050));
+    let result = simulate(&rpc, &swap_call(100_000), BlockTag::Pending, Some(&output_check(100, false))).await;
+    check(
+        &mut passed,
+        "Output below minimum",
+        matches!(result, Err(TxError::OutputBelowMinimum { simulated, .. }) if simulated == U256::from(50)),
+        describe(&result),
+    );
+    node.state().replies.insert("eth_simulateV1".to_string(), simulated(1_000, 1_150));
+    let result = simulate(&rpc, &swap_call(100_000), BlockTag::Pending, Some(&output_check(100, false))).await;
+    check(
+        &mut passed,
+        "Output above minimum",
+        matches!(&result, Ok(simulation) if simulation.output_delta == Some(U256::from(150))),
+        describe(&result),
+    );
+
+    if !passed {
+        std::process::exit(1);
+    }
+}
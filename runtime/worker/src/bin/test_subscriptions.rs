@@ -0,0 +1,170 @@
+// Test program for WebSocket subscriptions with reconnect and resubscribe
+//
+// Starts a local mock WebSocket node that drops the first connection after a
+// few blocks and rejects newPendingTransactions, then consumes the streams
+// directly and through a chain's block tracker. No network access needed.
+//
+// Usage: cargo run --bin test_subscriptions
+
+mod support;
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use alloy_primitives::{Address, B256};
+use futures::{SinkExt, StreamExt};
+use tokio::net::TcpListener;
+use tokio_tungstenite::tungstenite::Message;
+use worker::chain::rpc::{EndpointConfig, LogFilter};
+use worker::chain::{ethereum, Chain, EvmChain, SubscriptionClient};
+
+use support::check;
+
+/// Blocks sent on the first connection before the server drops it.
+const BLOCKS_BEFORE_DROP: u64 = 3;
+
+async fn spawn_mock_node() -> (String, Arc<AtomicU64>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind mock node");
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    let connections = Arc::new(AtomicU64::new(0));
+    // Block numbers continue across connections, like a real chain
+    let next_block = Arc::new(AtomicU64::new(1));
+
+    let counter = connections.clone();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let connection = counter.fetch_add(1, Ordering::SeqCst);
+            let next_block = next_block.clone();
+            tokio::spawn(async move {
+                let mut socket = match tokio_tungstenite::accept_async(stream).await {
+                    Ok(socket) => socket,
+                    Err(_) => return,
+                };
+
+                let request = match socket.next().await {
+                    Some(Ok(Message::Text(text))) => serde_json::from_str::<serde_json::Value>(&text).unwrap_or_default(),
+                    _ => return,
+                };
+                let kind = request["params"][0].as_str().unwrap_or_default().to_string();
+                if kind == "newPendingTransactions" {
+                    let reply = serde_json::json!({"jsonrpc": "2.0", "id": request["id"], "error": {"code": -32601, "message": "pending transactions not supported"}});
+                    let _ = socket.send(Message::Text(reply.to_string())).await;
+                    return;
+                }
+
+                let subscription = format!("0xsub{}", connection);
+                let reply = serde_json::json!({"jsonrpc": "2.0", "id": request["id"], "result": subscription});
+                let _ = socket.send(Message::Text(reply.to_string())).await;
+
+                for sent in 0.. {
+                    if kind == "newHeads" && connection == 0 && sent == BLOCKS_BEFORE_DROP {
+                        // Drop the connection without a close frame
+                        return;
+                    }
+                    let result = if kind == "logs" {
+                        serde_json::json!({
+                            "address": request["params"][1]["address"][0],
+                            "topics": [format!("{:#x}", B256::with_last_byte(7))],
+                            "data": "0x",
+                        })
+                    } else {
+                        let number = next_block.fetch_add(1, Ordering::SeqCst);
+                        serde_json::json!({
+                            "number": format!("{:#x}", number),
+                            "hash": format!("{:#x}", B256::with_last_byte(number as u8)),
+                            "parentHash": format!("{:#x}", B256::with_last_byte(number as u8 - 1)),
+                            "timestamp": "0x0",
+                            "gasLimit": "0x1c9c380",
+                            "gasUsed": "0x0",
+                        })
+                    };
+                    let notification = serde_json::json!({
+                        "jsonrpc": "2.0",
+                        "method": "eth_subscription",
+                        "params": {"subscription": subscription, "result": result},
+                    });
+                    if socket.send(Message::Text(notification.to_string())).await.is_err() {
+                        return;
+                    }
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                }
+            });
+        }
+    });
+
+    (url, connections)
+}
+
+#[tokio::main]
+async fn main() {
+    let mut passed = true;
+    let (url, connections) = spawn_mock_node().await;
+    let client = SubscriptionClient::new(vec![url]);
+
+    // 1. newHeads survives a dropped connection
+    let mut heads = client.new_heads();
+    let mut numbers = Vec::new();
+    while numbers.len() < 6 {
+        match tokio::time::timeout(Duration::from_secs(10), heads.next()).await {
+            Ok(Some(block)) => numbers.push(block.number.to::<u64>()),
+            _ => break,
+        }
+    }
+    check(
+        &mut passed,
+        "newHeads with reconnect",
+        numbers == [1, 2, 3, 4, 5, 6] && connections.load(Ordering::SeqCst) >= 2,
+        format!("blocks {:?} over {} connections", numbers, connections.load(Ordering::SeqCst)),
+    );
+    drop(heads);
+
+    // 2. Filtered logs
+    let contract = Address::repeat_byte(0x11);
+    let mut logs = client.logs(LogFilter {
+        address: vec![contract],
+        topics: vec![Some(vec![B256::with_last_byte(7)])],
+        ..Default::default()
+    });
+    let log = tokio::time::timeout(Duration::from_secs(5), logs.next()).await;
+    check(
+        &mut passed,
+        "logs subscription",
+        matches!(&log, Ok(Some(log)) if log.address == contract),
+        format!("{:?}", log.ok().flatten().map(|log| log.address)),
+    );
+
+    // 3. Unsupported subscription ends the stream instead of retrying forever
+    let mut pending = client.pending_transactions();
+    let item = tokio::time::timeout(Duration::from_secs(5), pending.next()).await;
+    check(
+        &mut passed,
+        "Rejected newPendingTransactions",
+        matches!(item, Ok(None)),
+        format!("{:?}", item),
+    );
+
+    // 4. A chain with ws_urls feeds its block tracker from newHeads, without HTTP polling
+    let (url, _) = spawn_mock_node().await;
+    let mut config = ethereum::default_config();
+    config.ws_urls = vec![url];
+    // Never contacted: heads arrive over the subscription and connect to each other
+    config.rpc_endpoints = vec![EndpointConfig::new("http://127.0.0.1:9")];
+    let chain = EvmChain::new(config, Vec::new()).expect("valid config");
+    let mut events = chain.blocks().subscribe();
+    let _ = tokio::time::timeout(Duration::from_millis(500), chain.blocks().run(&chain)).await;
+    let mut followed = 0;
+    while events.try_recv().is_ok() {
+        followed += 1;
+    }
+    check(
+        &mut passed,
+        "Block tracker follows newHeads",
+        chain.subscriptions().is_some() && followed >= 3 && chain.blocks().head().is_some(),
+        format!("{} block events, head {:?}", followed, chain.blocks().head().map(|(number, _)| number)),
+    );
+
+    if !passed {
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,182 @@
+// Test program for transaction signing, broadcast and receipt waiting
+//
+// Checks the EIP-155 example transaction from the EIP byte for byte, that
+// EIP-1559 envelopes decode back to the signer, how Li.Fi transaction requests
+// map to legacy or EIP-1559 fees, and that send_transaction waits for the
+// configured confirmations, reports reverts and times out on a mock node.
+// No network access needed.
+//
+// Usage: cargo run --bin test_tx_pipeline
+
+mod support;
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use alloy_primitives::{address, b256, bytes, keccak256, Address, Bytes, U256};
+use worker::chain::rpc::EndpointConfig;
+use worker::chain::tx::{send_transaction, SendOptions, TxError, TxFees, TypedTransaction};
+use worker::chain::{ethereum, Chain, EvmChain, LocalSigner, Signer, WalletAccount};
+use worker::module::lifi::TransactionRequest;
+
+use support::check;
+use support::node::{decode_transaction, Mining, MockNode};
+
+/// Throwaway key; holds no funds.
+const TEST_KEY: &str = "0x0123456789012345678901234567890123456789012345678901234567890123";
+/// Key of the EIP-155 example.
+const EIP155_KEY: &str = "0x4646464646464646464646464646464646464646464646464646464646464646";
+const RECIPIENT: Address = Address::repeat_byte(0x22);
+const REVERTING: Address = Address::repeat_byte(0x66);
+
+fn lifi_request(gas_price: Option<&str>, max_fee: Option<&str>) -> TransactionRequest {
+    TransactionRequest {
+        data: "0x1234".to_string(),
+        to: format!("{:#x}", RECIPIENT),
+        value: "0x0de0b6b3a7640000".to_string(),
+        from: format!("{:#x}", Address::repeat_byte(0x11)),
+        chain_id: ethereum::CHAIN_ID,
+        gas_limit: "0x30d40".to_string(),
+        gas_price: gas_price.map(str::to_string),
+        max_fee_per_gas: max_fee.map(str::to_string),
+        max_priority_fee_per_gas: max_fee.map(|_| "0x3b9aca00".to_string()),
+    }
+}
+
+fn transfer(nonce: u64, to: Address) -> TypedTransaction {
+    TypedTransaction {
+        chain_id: ethereum::CHAIN_ID,
+        nonce,
+        gas_limit: 100_000,
+        fees: TxFees::Eip1559 {
+            max_fee_per_gas: U256::from(3_000_000_000u64),
+            max_priority_fee_per_gas: U256::from(100_000_000u64),
+        },
+        to: Some(to),
+        value: U256::from(1),
+        data: Bytes::new(),
+    }
+}
+
+fn options(confirmations: u64, timeout: Duration) -> SendOptions {
+    SendOptions {
+        confirmations: Some(confirmations),
+        poll_interval: Duration::from_millis(20),
+        timeout,
+        ..SendOptions::default()
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let mut passed = true;
+
+    // 1. The EIP-155 example: signing hash and signed envelope
+    let example = TypedTransaction {
+        chain_id: 1,
+        nonce: 9,
+        gas_limit: 21_000,
+        fees: TxFees::Legacy {
+            gas_price: U256::from(20_000_000_000u64),
+        },
+        to: Some(address!("3535353535353535353535353535353535353535")),
+        value: U256::from(1_000_000_000_000_000_000u64),
+        data: Bytes::new(),
+    };
+    let signed = LocalSigner::from_hex(EIP155_KEY).expect("example key").sign_transaction(&example);
+    let expected = bytes!("f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83");
+    check(
+        &mut passed,
+        "EIP-155 example",
+        example.signature_hash() == b256!("daf5a779ae972f972197303d7b574746c7ef83eadac0f2791ad23db92e4c8e53")
+            && signed.as_ref().is_ok_and(|signed| signed.raw == expected && signed.hash == keccak256(&expected)),
+        format!("{:?}", signed.map(|signed| signed.raw).map_err(|e| e.to_string())),
+    );
+
+    // 2. EIP-1559 envelopes carry the type byte and recover to the signer
+    let signer: Arc<dyn Signer> = Arc::new(LocalSigner::from_hex(TEST_KEY).expect("test key"));
+    let address = signer.address();
+    let tx = TypedTransaction {
+        data: bytes!("a9059cbb"),
+        ..transfer(7, RECIPIENT)
+    };
+    let signed = signer.sign_transaction(&tx).expect("sign");
+    let decoded = decode_transaction(&signed.raw);
+    check(
+        &mut passed,
+        "EIP-1559 envelope",
+        signed.raw.first() == Some(&0x02)
+            && signed.hash == keccak256(&signed.raw)
+            && decoded.as_ref().is_some_and(|decoded| decoded.from == address && decoded.tx == tx),
+        format!("from {:?}", decoded.map(|decoded| decoded.from)),
+    );
+
+    // 3. Li.Fi requests: gasPrice gives legacy, maxFeePerGas gives EIP-1559, neither is refused
+    let legacy = lifi_request(Some("0x77359400"), None).to_typed_transaction(3);
+    let eip1559 = lifi_request(Some("0x77359400"), Some("0xb2d05e00")).to_typed_transaction(3);
+    let neither = lifi_request(None, None).to_typed_transaction(3);
+    check(
+        &mut passed,
+        "Li.Fi fees",
+        matches!(&legacy, Ok(tx) if tx.fees == TxFees::Legacy { gas_price: U256::from(2_000_000_000u64) }
+            && tx.nonce == 3 && tx.gas_limit == 200_000 && tx.value == U256::from(10u64).pow(U256::from(18)))
+            && matches!(&eip1559, Ok(tx) if tx.fees == TxFees::Eip1559 {
+                max_fee_per_gas: U256::from(3_000_000_000u64),
+                max_priority_fee_per_gas: U256::from(1_000_000_000u64),
+            })
+            && matches!(neither, Err(TxError::InvalidRequest(_))),
+        format!("{:?}; {:?}", legacy.map(|tx| tx.fees), eip1559.map(|tx| tx.fees)),
+    );
+
+    let node = MockNode::start(ethereum::CHAIN_ID).await;
+    node.state().balances.insert(address, U256::from(10u64).pow(U256::from(18)));
+    node.state().reverts.insert(REVERTING, Bytes::new());
+    let mut config = ethereum::default_config();
+    config.rpc_endpoints = vec![EndpointConfig::new(&node.url)];
+    let chain = EvmChain::new(config, vec![WalletAccount::Signer(signer.clone())]).expect("valid config");
+    let nonces = chain.nonces();
+
+    // 4. The receipt is returned once the block is buried deep enough
+    let buried = node.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        buried.state().advance(2);
+    });
+    let started = Instant::now();
+    let nonce = nonces.reserve(&chain, address).await.unwrap_or_default();
+    let receipt = send_transaction(&chain, signer.as_ref(), &transfer(nonce, RECIPIENT), &options(3, Duration::from_secs(5))).await;
+    let waited = started.elapsed();
+    check(
+        &mut passed,
+        "Confirmations",
+        matches!(&receipt, Ok(receipt) if receipt.is_success()) && waited >= Duration::from_millis(200),
+        format!("waited {:?}: {:?}", waited, receipt.map(|r| r.block_number).map_err(|e| e.to_string())),
+    );
+
+    // 5. A mined revert is an error that still consumes the nonce
+    let nonce = nonces.reserve(&chain, address).await.unwrap_or_default();
+    let reverted = send_transaction(&chain, signer.as_ref(), &transfer(nonce, REVERTING), &options(1, Duration::from_secs(5))).await;
+    let next = nonces.reserve(&chain, address).await.unwrap_or_default();
+    nonces.release(address, next);
+    check(
+        &mut passed,
+        "Reverted",
+        matches!(&reverted, Err(TxError::Reverted { receipt, .. }) if !receipt.is_success()) && next == nonce + 1,
+        format!("{:?}, next nonce {}", reverted.as_ref().err().map(|e| e.to_string()), next),
+    );
+
+    // 6. A transaction that is never mined times out
+    node.state().mining = Mining::Manual;
+    let nonce = nonces.reserve(&chain, address).await.unwrap_or_default();
+    let pending = send_transaction(&chain, signer.as_ref(), &transfer(nonce, RECIPIENT), &options(1, Duration::from_millis(300))).await;
+    check(
+        &mut passed,
+        "Timeout",
+        matches!(&pending, Err(TxError::Timeout { .. })),
+        format!("{:?}", pending.err().map(|e| e.to_string())),
+    );
+
+    if !passed {
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,243 @@
+// Canonical block window with reorg detection
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use alloy_primitives::B256;
+use futures::StreamExt;
+use tokio::sync::broadcast;
+
+use crate::chain::rpc::{Block, RpcClient, RpcError, TransactionReceipt};
+use crate::chain::Chain;
+
+/// Smallest number of recent blocks remembered, whatever the confirmation depth.
+const MIN_WINDOW: usize = 64;
+/// Events buffered per subscriber before slow receivers start lagging.
+const EVENT_CAPACITY: usize = 256;
+
+/// Change to the canonical chain seen by a `BlockTracker`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BlockEvent {
+    /// A block was added on top of the canonical chain.
+    NewBlock { number: u64, hash: B256 },
+    /// Blocks above `common_ancestor` were replaced by another fork.
+    /// `orphaned` holds the replaced `(number, hash)` pairs, lowest first;
+    /// `NewBlock` events for the new fork follow.
+    Reorg {
+        common_ancestor: u64,
+        orphaned: Vec<(u64, B256)>,
+    },
+}
+
+impl BlockEvent {
+    /// Whether this event removed the block `receipt` was mined in.
+    pub fn orphans(&self, receipt: &TransactionReceipt) -> bool {
+        match (self, receipt.block_hash) {
+            (BlockEvent::Reorg { orphaned, .. }, Some(block_hash)) => {
+                orphaned.iter().any(|(_, hash)| *hash == block_hash)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Keeps the hashes of the most recent canonical blocks of one chain and
+/// reports reorgs to subscribers.
+///
+/// Heads come from `run` (newHeads subscription or polling) and from `poll`,
+/// which the transaction pipeline calls while waiting for receipts, so the
+/// window stays current even when nothing runs in the background.
+#[derive(Debug)]
+pub struct BlockTracker {
+    confirmations: u64,
+    window_size: usize,
+    window: Mutex<BTreeMap<u64, B256>>,
+    /// Serializes `ingest` so that concurrent callers cannot apply stale forks.
+    ingest_lock: tokio::sync::Mutex<()>,
+    events: broadcast::Sender<BlockEvent>,
+}
+
+impl BlockTracker {
+    /// `confirmations` is the chain's default depth (including the inclusion
+    /// block) before a transaction counts as final; `0` is treated as `1`.
+    pub fn new(confirmations: u64) -> Self {
+        let confirmations = confirmations.max(1);
+        let (events, _) = broadcast::channel(EVENT_CAPACITY);
+        Self {
+            confirmations,
+            window_size: MIN_WINDOW.max(confirmations as usize * 2),
+            window: Mutex::new(BTreeMap::new()),
+            ingest_lock: tokio::sync::Mutex::new(()),
+            events,
+        }
+    }
+
+    pub fn confirmations(&self) -> u64 {
+        self.confirmations
+    }
+
+    /// Receives every `BlockEvent` from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<BlockEvent> {
+        self.events.subscribe()
+    }
+
+    /// Highest canonical block seen so far.
+    pub fn head(&self) -> Option<(u64, B256)> {
+        let window = self.window.lock().unwrap();
+        window.iter().next_back().map(|(number, hash)| (*number, *hash))
+    }
+
+    /// Hash of the canonical block at `number`, if it is inside the window.
+    pub fn canonical_hash(&self, number: u64) -> Option<B256> {
+        self.window.lock().unwrap().get(&number).copied()
+    }
+
+    /// `Some(false)` if another block is canonical at `number`; `None` if
+    /// `number` is outside the window and cannot be checked.
+    pub fn is_canonical(&self, number: u64, hash: B256) -> Option<bool> {
+        self.canonical_hash(number).map(|canonical| canonical == hash)
+    }
+
+    /// Blocks on top of (and including) `number`; `0` if it is above the head.
+    pub fn depth(&self, number: u64) -> u64 {
+        match self.head() {
+            Some((head, _)) if head >= number => head - number + 1,
+            _ => 0,
+        }
+    }
+
+    /// Whether a block at `number` has the chain's confirmation depth.
+    pub fn is_final(&self, number: u64) -> bool {
+        self.depth(number) >= self.confirmations
+    }
+
+    /// Fetches the latest block and ingests it.
+    pub async fn poll(&self, rpc: &RpcClient) -> Result<(), RpcError> {
+        let head = rpc.get_latest_block().await?;
+        self.ingest(rpc, &head).await
+    }
+
+    /// Adds `head` to the window. Missing ancestors are fetched by parent hash
+    /// until the new blocks connect to the window; window blocks they displace
+    /// are reported as a `Reorg`.
+    pub async fn ingest(&self, rpc: &RpcClient, head: &Block) -> Result<(), RpcError> {
+        let hash = match head.hash {
+            Some(hash) => hash,
+            None => return Ok(()),
+        };
+        let number = head.number.to::<u64>();
+
+        let _guard = self.ingest_lock.lock().await;
+        {
+            let window = self.window.lock().unwrap();
+            if window.get(&number) == Some(&hash) {
+                return Ok(());
+            }
+            // A lagging endpoint reporting a block we already dropped from the window
+            if window.first_key_value().is_some_and(|(first, _)| number < *first) {
+                return Ok(());
+            }
+        }
+
+        // New blocks, highest first: (number, hash, parent hash)
+        let mut segment = vec![(number, hash, head.parent_hash)];
+        let connected = loop {
+            let (number, _, parent_hash) = *segment.last().unwrap();
+            if number == 0 {
+                break true;
+            }
+            {
+                let window = self.window.lock().unwrap();
+                match window.first_key_value() {
+                    None => break true,
+                    Some((first, _)) if number - 1 < *first => break true,
+                    _ => {}
+                }
+                if window.get(&(number - 1)) == Some(&parent_hash) {
+                    break true;
+                }
+            }
+            if segment.len() >= self.window_size {
+                break false;
+            }
+            let parent = rpc
+                .get_block_by_hash(parent_hash)
+                .await?
+                .ok_or_else(|| RpcError::Missing(format!("block {:#x}", parent_hash)))?;
+            segment.push((parent.number.to(), parent_hash, parent.parent_hash));
+        };
+        segment.reverse();
+
+        let mut events = Vec::new();
+        {
+            let mut window = self.window.lock().unwrap();
+            let lowest = segment[0].0;
+            if connected {
+                let orphaned: Vec<(u64, B256)> = window
+                    .split_off(&lowest)
+                    .into_iter()
+                    .filter(|(number, hash)| !segment.iter().any(|(n, h, _)| n == number && h == hash))
+                    .collect();
+                if !orphaned.is_empty() {
+                    events.push(BlockEvent::Reorg {
+                        common_ancestor: lowest - 1,
+                        orphaned,
+                    });
+                }
+            } else {
+                // Gap wider than the window (e.g. after a long outage): start over
+                window.clear();
+            }
+            for (number, hash, _) in &segment {
+                window.insert(*number, *hash);
+                events.push(BlockEvent::NewBlock {
+                    number: *number,
+                    hash: *hash,
+                });
+            }
+            while window.len() > self.window_size {
+                window.pop_first();
+            }
+        }
+
+        for event in events {
+            if let BlockEvent::Reorg {
+                common_ancestor,
+                orphaned,
+            } = &event
+            {
+                println!(
+                    "Reorg detected: {} block(s) above {} replaced (new head {})",
+                    orphaned.len(),
+                    common_ancestor,
+                    number
+                );
+            }
+            // No receivers is fine
+            let _ = self.events.send(event);
+        }
+        Ok(())
+    }
+
+    /// Follows the chain head until the task is dropped: newHeads when the
+    /// chain has WebSocket endpoints, otherwise polling once per block time.
+    pub async fn run(&self, chain: &dyn Chain) {
+        if let Some(subscriptions) = chain.subscriptions() {
+            let mut heads = subscriptions.new_heads();
+            while let Some(head) = heads.next().await {
+                if let Err(e) = self.ingest(chain.rpc(), &head).await {
+                    println!("[{}] Failed to ingest block {}: {}", chain.config().name, head.number, e);
+                }
+            }
+            println!("[{}] newHeads subscription ended, polling instead", chain.config().name);
+        }
+
+        let mut interval = tokio::time::interval(chain.config().block_time);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.poll(chain.rpc()).await {
+                println!("[{}] Block poll failed: {}", chain.config().name, e);
+            }
+        }
+    }
+}
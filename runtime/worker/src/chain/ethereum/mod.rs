@@ -1,28 +1,38 @@
-use std::error::Error;
-use alloy_primitives::Address;
+use std::time::Duration;
 
-pub struct EthereumChain;
+use crate::chain::evm::{ChainConfig, NativeToken};
+use crate::chain::gas::FeeOracleConfig;
+use crate::chain::multicall::MulticallConfig;
+use crate::chain::rpc::EndpointConfig;
 
-impl EthereumChain {
-    pub fn new() -> Self {
-        Self
-    }
-}
+pub const CHAIN_ID: u64 = 1;
 
-impl crate::chain::Chain for EthereumChain {
-    fn connect(&self) {
-        println!("Connecting to Ethereum Chain");
-    }
+/// Public endpoint used when `config.toml` does not set `[chains.ethereum] rpc_urls`.
+pub const DEFAULT_RPC_URL: &str = "https://ethereum-rpc.publicnode.com";
+pub const DEFAULT_WS_URL: &str = "wss://ethereum-rpc.publicnode.com";
 
-    fn get_primary_wallet_address(&self) -> Result<Address, Box<dyn Error>> {
-        get_primary_wallet_address()
+/// Built-in defaults for the `[chains.ethereum]` section.
+pub fn default_config() -> ChainConfig {
+    ChainConfig {
+        key: "ethereum".to_string(),
+        chain_id: CHAIN_ID,
+        name: "Ethereum".to_string(),
+        rpc_endpoints: vec![EndpointConfig::new(DEFAULT_RPC_URL)],
+        ws_urls: vec![DEFAULT_WS_URL.to_string()],
+        rpc_quorum: 1,
+        native_token: NativeToken {
+            symbol: "ETH".to_string(),
+            decimals: 18,
+        },
+        block_time: Duration::from_secs(12),
+        confirmations: 12,
+        explorer_url: Some("https://etherscan.io".to_string()),
+        eip1559: true,
+        lifi_key: Some("eth".to_string()),
+        fee: FeeOracleConfig::default(),
+        multicall: MulticallConfig::default(),
+        allow_unchecked_output: false,
+        hypercore_api_url: None,
+        dual_blocks: None,
     }
 }
-
-/// Gets the first wallet address from WALLET_SECRETS_EVM for Ethereum.
-/// Returns an error if no valid addresses are found.
-pub fn get_primary_wallet_address() -> Result<Address, Box<dyn Error>> {
-    let addresses = crate::chain::parse_wallet_secrets_evm()?;
-    addresses.into_iter().next()
-        .ok_or_else(|| "No wallet addresses available for Ethereum".into())
-}
@@ -0,0 +1,158 @@
+// Generic EVM chain described entirely by configuration
+use std::time::Duration;
+
+use crate::chain::blocks::BlockTracker;
+use crate::chain::gas::{FeeOracle, FeeOracleConfig};
+use crate::chain::hyperevm::{DualBlockConfig, DualBlocks, HyperCoreClient};
+use crate::chain::multicall::{Multicall, MulticallConfig};
+use crate::chain::rpc::EndpointConfig;
+use crate::chain::{NonceManager, RpcClient, SubscriptionClient, WalletAccount};
+
+/// Native gas token of a chain.
+#[derive(Debug, Clone)]
+pub struct NativeToken {
+    pub symbol: String,
+    pub decimals: u8,
+}
+
+/// Static description of an EVM chain, read from a `[chains.<key>]` section.
+#[derive(Debug, Clone)]
+pub struct ChainConfig {
+    /// Section name in `config.toml`, e.g. `arbitrum`.
+    pub key: String,
+    pub chain_id: u64,
+    pub name: String,
+    /// RPC endpoints; requests go to the healthiest one and fail over to the rest.
+    pub rpc_endpoints: Vec<EndpointConfig>,
+    /// WebSocket endpoints for `eth_subscribe`; tried in order on reconnect.
+    pub ws_urls: Vec<String>,
+    /// Endpoints that must agree for quorum reads such as `get_balance_quorum`.
+    pub rpc_quorum: usize,
+    pub native_token: NativeToken,
+    pub block_time: Duration,
+    /// Blocks (including the inclusion block) before a transaction is treated as final.
+    pub confirmations: u64,
+    /// Block explorer base URL, e.g. `https://etherscan.io`.
+    pub explorer_url: Option<String>,
+    /// Whether the chain accepts type-2 (EIP-1559) transactions.
+    pub eip1559: bool,
+    /// Li.Fi chain key (`eth`, `arb`, `hyp`, ...).
+    pub lifi_key: Option<String>,
+    pub fee: FeeOracleConfig,
+    pub multicall: MulticallConfig,
+    /// Send transactions with an output check even when the node lacks
+    /// `eth_simulateV1` to verify it; by default they are refused.
+    pub allow_unchecked_output: bool,
+    /// HyperCore API base URL; only set on HyperEVM.
+    pub hypercore_api_url: Option<String>,
+    /// Small/big block gas limits; only set on HyperEVM.
+    pub dual_blocks: Option<DualBlockConfig>,
+}
+
+impl ChainConfig {
+    /// Explorer link for a transaction hash, if an explorer is configured.
+    pub fn explorer_tx_url(&self, hash: &alloy_primitives::B256) -> Option<String> {
+        self.explorer_url
+            .as_ref()
+            .map(|url| format!("{}/tx/{:#x}", url.trim_end_matches('/'), hash))
+    }
+}
+
+/// `Chain` implementation shared by every EVM network.
+pub struct EvmChain {
+    config: ChainConfig,
+    rpc: RpcClient,
+    accounts: Vec<WalletAccount>,
+    nonces: NonceManager,
+    fee_oracle: FeeOracle,
+    subscriptions: Option<SubscriptionClient>,
+    blocks: BlockTracker,
+    multicall: Multicall,
+    hypercore: Option<HyperCoreClient>,
+    dual_blocks: Option<DualBlocks>,
+}
+
+impl EvmChain {
+    pub fn new(config: ChainConfig, accounts: Vec<WalletAccount>) -> Result<Self, String> {
+        if config.rpc_endpoints.is_empty() {
+            return Err(format!("[chains.{}] has no rpc_urls", config.key));
+        }
+        if config.rpc_quorum > config.rpc_endpoints.len() {
+            return Err(format!(
+                "[chains.{}] rpc_quorum {} exceeds the {} configured endpoints",
+                config.key,
+                config.rpc_quorum,
+                config.rpc_endpoints.len()
+            ));
+        }
+        let fee_oracle = FeeOracle::new(FeeOracleConfig {
+            eip1559: config.eip1559,
+            ..config.fee.clone()
+        });
+
+        Ok(Self {
+            rpc: RpcClient::with_endpoints(config.rpc_endpoints.clone(), config.rpc_quorum),
+            accounts,
+            nonces: NonceManager::new(),
+            fee_oracle,
+            subscriptions: (!config.ws_urls.is_empty()).then(|| SubscriptionClient::new(config.ws_urls.clone())),
+            blocks: BlockTracker::new(config.confirmations),
+            multicall: Multicall::new(config.multicall.clone()),
+            hypercore: config.hypercore_api_url.as_deref().map(HyperCoreClient::new),
+            dual_blocks: config.dual_blocks.clone().map(DualBlocks::new),
+            config,
+        })
+    }
+}
+
+impl crate::chain::Chain for EvmChain {
+    fn connect(&self) {
+        println!(
+            "Connecting to {} (chain id {}) via {} ({} endpoints)",
+            self.config.name,
+            self.config.chain_id,
+            self.rpc.url(),
+            self.config.rpc_endpoints.len()
+        );
+    }
+
+    fn config(&self) -> &ChainConfig {
+        &self.config
+    }
+
+    fn rpc(&self) -> &RpcClient {
+        &self.rpc
+    }
+
+    fn accounts(&self) -> &[WalletAccount] {
+        &self.accounts
+    }
+
+    fn nonces(&self) -> &NonceManager {
+        &self.nonces
+    }
+
+    fn fee_oracle(&self) -> &FeeOracle {
+        &self.fee_oracle
+    }
+
+    fn subscriptions(&self) -> Option<&SubscriptionClient> {
+        self.subscriptions.as_ref()
+    }
+
+    fn blocks(&self) -> &BlockTracker {
+        &self.blocks
+    }
+
+    fn multicall(&self) -> &Multicall {
+        &self.multicall
+    }
+
+    fn hypercore(&self) -> Option<&HyperCoreClient> {
+        self.hypercore.as_ref()
+    }
+
+    fn dual_blocks(&self) -> Option<&DualBlocks> {
+        self.dual_blocks.as_ref()
+    }
+}
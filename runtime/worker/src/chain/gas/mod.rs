@@ -0,0 +1,210 @@
+// Fee estimation from eth_feeHistory / eth_gasPrice
+use alloy_primitives::U256;
+
+use crate::chain::rpc::{BlockTag, RpcClient, RpcError};
+use crate::chain::tx::TxFees;
+
+/// Nodes reject a replacement unless both fee fields rise by at least 10%.
+pub const MIN_REPLACEMENT_BUMP_PERCENT: u64 = 10;
+
+/// Which priority-fee percentile of recent blocks to pay.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FeeStrategy {
+    /// 10th percentile
+    Slow,
+    /// 50th percentile
+    Standard,
+    /// 90th percentile
+    Fast,
+    /// Custom percentile in `0..=100`.
+    Percentile(f64),
+}
+
+impl FeeStrategy {
+    pub fn percentile(&self) -> f64 {
+        match self {
+            FeeStrategy::Slow => 10.0,
+            FeeStrategy::Standard => 50.0,
+            FeeStrategy::Fast => 90.0,
+            FeeStrategy::Percentile(p) => p.clamp(0.0, 100.0),
+        }
+    }
+
+    /// Parses `slow`, `standard`, `fast` or a number such as `75`.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "slow" => Some(FeeStrategy::Slow),
+            "standard" => Some(FeeStrategy::Standard),
+            "fast" => Some(FeeStrategy::Fast),
+            other => other.parse().ok().map(FeeStrategy::Percentile),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FeeOracleConfig {
+    pub strategy: FeeStrategy,
+    /// Number of recent blocks sampled by `eth_feeHistory`.
+    pub block_count: u64,
+    /// `max_fee_per_gas = base_fee * base_fee_multiplier + priority fee`,
+    /// leaving headroom for base fee increases over the next blocks.
+    pub base_fee_multiplier: u64,
+    /// Upper bound for `max_fee_per_gas` (or the legacy gas price).
+    pub max_fee_cap: Option<U256>,
+    /// Upper bound for `max_priority_fee_per_gas`.
+    pub max_priority_fee_cap: Option<U256>,
+    /// `false` for chains without type-2 transactions: always quote a legacy gas price.
+    pub eip1559: bool,
+}
+
+impl Default for FeeOracleConfig {
+    fn default() -> Self {
+        Self {
+            strategy: FeeStrategy::Standard,
+            block_count: 10,
+            base_fee_multiplier: 2,
+            max_fee_cap: None,
+            max_priority_fee_cap: None,
+            eip1559: true,
+        }
+    }
+}
+
+/// Suggests transaction fees from recent chain data, bounded by the configured caps.
+#[derive(Debug, Clone, Default)]
+pub struct FeeOracle {
+    config: FeeOracleConfig,
+}
+
+impl FeeOracle {
+    pub fn new(config: FeeOracleConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn config(&self) -> &FeeOracleConfig {
+        &self.config
+    }
+
+    /// EIP-1559 fees when the chain reports a base fee, otherwise a legacy gas price.
+    pub async fn estimate(&self, rpc: &RpcClient) -> Result<TxFees, RpcError> {
+        if !self.config.eip1559 {
+            return self.estimate_legacy(rpc).await;
+        }
+        let percentile = self.config.strategy.percentile();
+        let history = match rpc
+            .fee_history(self.config.block_count.max(1), BlockTag::Latest, &[percentile])
+            .await
+        {
+            Ok(history) => history,
+            // Pre-London chains and some L2 nodes do not implement eth_feeHistory
+            Err(RpcError::Rpc { .. }) => return self.estimate_legacy(rpc).await,
+            Err(e) => return Err(e),
+        };
+
+        // The last entry is the base fee of the next block
+        let base_fee = match history.base_fee_per_gas.last() {
+            Some(base_fee) if !base_fee.is_zero() => *base_fee,
+            _ => return self.estimate_legacy(rpc).await,
+        };
+
+        let mut rewards: Vec<U256> = history
+            .reward
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|block| block.first().copied())
+            .collect();
+        rewards.sort();
+        let priority_fee = match rewards.get(rewards.len() / 2) {
+            Some(reward) => *reward,
+            None => rpc.max_priority_fee_per_gas().await?,
+        };
+
+        let max_fee = base_fee * U256::from(self.config.base_fee_multiplier.max(1)) + priority_fee;
+        Ok(self.apply_caps(TxFees::Eip1559 {
+            max_fee_per_gas: max_fee,
+            max_priority_fee_per_gas: priority_fee,
+        }))
+    }
+
+    async fn estimate_legacy(&self, rpc: &RpcClient) -> Result<TxFees, RpcError> {
+        let gas_price = rpc.gas_price().await?;
+        Ok(self.apply_caps(TxFees::Legacy { gas_price }))
+    }
+
+    /// Raises `suggested` (e.g. Li.Fi's quote) to at least the oracle's estimate
+    /// and clamps it to the caps. A suggestion of the other fee type is replaced.
+    pub async fn adjust(&self, rpc: &RpcClient, suggested: &TxFees) -> Result<TxFees, RpcError> {
+        let estimate = self.estimate(rpc).await?;
+        let fees = match (suggested, estimate) {
+            (TxFees::Legacy { gas_price }, TxFees::Legacy { gas_price: estimated }) => TxFees::Legacy {
+                gas_price: (*gas_price).max(estimated),
+            },
+            (
+                TxFees::Eip1559 {
+                    max_fee_per_gas,
+                    max_priority_fee_per_gas,
+                },
+                TxFees::Eip1559 {
+                    max_fee_per_gas: estimated_max,
+                    max_priority_fee_per_gas: estimated_priority,
+                },
+            ) => TxFees::Eip1559 {
+                max_fee_per_gas: (*max_fee_per_gas).max(estimated_max),
+                max_priority_fee_per_gas: (*max_priority_fee_per_gas).max(estimated_priority),
+            },
+            (_, estimate) => estimate,
+        };
+        Ok(self.apply_caps(fees))
+    }
+
+    /// Fees for replacing a pending transaction: every field raised by `percent`
+    /// (at least the 10% nodes require). Returns `None` if the caps leave no
+    /// room for a valid replacement.
+    pub fn bump(&self, fees: &TxFees, percent: u64) -> Option<TxFees> {
+        let percent = percent.max(MIN_REPLACEMENT_BUMP_PERCENT);
+        let raise = |value: U256| {
+            // Round up so that tiny values still increase
+            (value * U256::from(100 + percent)).div_ceil(U256::from(100))
+        };
+        let bumped = match fees {
+            TxFees::Legacy { gas_price } => TxFees::Legacy {
+                gas_price: raise(*gas_price),
+            },
+            TxFees::Eip1559 {
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+            } => TxFees::Eip1559 {
+                max_fee_per_gas: raise(*max_fee_per_gas),
+                max_priority_fee_per_gas: raise(*max_priority_fee_per_gas),
+            },
+        };
+
+        let capped = self.apply_caps(bumped.clone());
+        if capped == bumped {
+            Some(bumped)
+        } else {
+            None
+        }
+    }
+
+    fn apply_caps(&self, fees: TxFees) -> TxFees {
+        let cap = |value: U256, cap: Option<U256>| cap.map_or(value, |cap| value.min(cap));
+        match fees {
+            TxFees::Legacy { gas_price } => TxFees::Legacy {
+                gas_price: cap(gas_price, self.config.max_fee_cap),
+            },
+            TxFees::Eip1559 {
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+            } => {
+                let max_fee_per_gas = cap(max_fee_per_gas, self.config.max_fee_cap);
+                let max_priority_fee_per_gas =
+                    cap(max_priority_fee_per_gas, self.config.max_priority_fee_cap).min(max_fee_per_gas);
+                TxFees::Eip1559 {
+                    max_fee_per_gas,
+                    max_priority_fee_per_gas,
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,173 @@
+// HyperEVM small/big block routing
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use alloy_primitives::{Address, U256};
+
+use crate::chain::rpc::{RpcClient, RpcError};
+use crate::chain::signer::Signer;
+use crate::chain::tx::{SendOptions, TxError, TxFees};
+use crate::chain::Chain;
+
+const NO_PARAMS: [u8; 0] = [];
+
+/// Which HyperEVM block stream an account's transactions are included in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockType {
+    /// Fast blocks (about 1s) with a small gas limit.
+    Small,
+    /// Slow blocks (about 1 min) with a large gas limit.
+    Big,
+}
+
+/// Gas limits and cadence of the two block streams.
+#[derive(Debug, Clone)]
+pub struct DualBlockConfig {
+    pub small_block_gas_limit: u64,
+    pub big_block_gas_limit: u64,
+    pub big_block_time: Duration,
+}
+
+impl Default for DualBlockConfig {
+    fn default() -> Self {
+        Self {
+            small_block_gas_limit: 2_000_000,
+            big_block_gas_limit: 30_000_000,
+            big_block_time: Duration::from_secs(60),
+        }
+    }
+}
+
+/// `eth_usingBigBlocks`: whether `address` currently sends to big blocks.
+pub async fn using_big_blocks(rpc: &RpcClient, address: Address) -> Result<bool, RpcError> {
+    rpc.request("eth_usingBigBlocks", (address,)).await
+}
+
+/// `eth_bigBlockGasPrice`: base fee of the next big block.
+pub async fn big_block_gas_price(rpc: &RpcClient) -> Result<U256, RpcError> {
+    rpc.request("eth_bigBlockGasPrice", NO_PARAMS).await
+}
+
+/// Tracks the block type of each account and switches it through HyperCore
+/// (`evmUserModify`) when a transaction needs the other stream.
+///
+/// The setting is per account, so switching also moves that account's other
+/// pending transactions to the new stream.
+pub struct DualBlocks {
+    config: DualBlockConfig,
+    modes: Mutex<HashMap<Address, BlockType>>,
+    /// Serializes switches so concurrent sends do not toggle back and forth.
+    switching: tokio::sync::Mutex<()>,
+}
+
+impl DualBlocks {
+    pub fn new(config: DualBlockConfig) -> Self {
+        Self {
+            config,
+            modes: Mutex::new(HashMap::new()),
+            switching: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    pub fn config(&self) -> &DualBlockConfig {
+        &self.config
+    }
+
+    /// Smallest block type that fits `gas_limit`; `None` if it fits neither.
+    pub fn required(&self, gas_limit: u64) -> Option<BlockType> {
+        if gas_limit <= self.config.small_block_gas_limit {
+            Some(BlockType::Small)
+        } else if gas_limit <= self.config.big_block_gas_limit {
+            Some(BlockType::Big)
+        } else {
+            None
+        }
+    }
+
+    /// Current block type of `address`, cached after the first lookup.
+    pub async fn mode(&self, rpc: &RpcClient, address: Address) -> Result<BlockType, RpcError> {
+        if let Some(mode) = self.modes.lock().unwrap().get(&address) {
+            return Ok(*mode);
+        }
+        let mode = if using_big_blocks(rpc, address).await? {
+            BlockType::Big
+        } else {
+            BlockType::Small
+        };
+        self.modes.lock().unwrap().insert(address, mode);
+        Ok(mode)
+    }
+
+    /// Puts the signer on the block type `gas_limit` needs before it sends:
+    /// big blocks when the transaction does not fit a small block, and back to
+    /// small blocks otherwise so ordinary transactions are not held for a minute.
+    pub async fn route(&self, chain: &dyn Chain, signer: &dyn Signer, gas_limit: u64) -> Result<BlockType, TxError> {
+        let required = self.required(gas_limit).ok_or_else(|| {
+            TxError::InvalidRequest(format!(
+                "gas limit {} exceeds the big block gas limit {}",
+                gas_limit, self.config.big_block_gas_limit
+            ))
+        })?;
+        let address = signer.address();
+
+        let _switching = self.switching.lock().await;
+        if self.mode(chain.rpc(), address).await? == required {
+            return Ok(required);
+        }
+        let hypercore = chain.hypercore().ok_or_else(|| {
+            TxError::InvalidRequest(format!("[chains.{}] has no hypercore_api_url to switch block type", chain.config().key))
+        })?;
+        println!(
+            "[{}] Switching {:#x} to {:?} blocks for gas limit {}",
+            chain.config().name,
+            address,
+            required,
+            gas_limit
+        );
+        hypercore
+            .set_big_blocks(signer, required == BlockType::Big)
+            .await
+            .map_err(|e| TxError::InvalidRequest(format!("could not switch block type: {}", e)))?;
+        self.modes.lock().unwrap().insert(address, required);
+        Ok(required)
+    }
+
+    /// `fees` raised to the big block base fee if needed; the fee oracle
+    /// prices against the small blocks that make up most of the chain.
+    pub async fn fit_fees(&self, rpc: &RpcClient, block_type: BlockType, fees: &TxFees) -> Result<TxFees, RpcError> {
+        if block_type == BlockType::Small {
+            return Ok(fees.clone());
+        }
+        let base_fee = big_block_gas_price(rpc).await?;
+        Ok(match fees {
+            TxFees::Legacy { gas_price } => TxFees::Legacy {
+                gas_price: (*gas_price).max(base_fee),
+            },
+            TxFees::Eip1559 {
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+            } => TxFees::Eip1559 {
+                max_fee_per_gas: (*max_fee_per_gas).max(base_fee + *max_priority_fee_per_gas),
+                max_priority_fee_per_gas: *max_priority_fee_per_gas,
+            },
+        })
+    }
+
+    /// `options` stretched so a big block transaction is not replaced or
+    /// given up on before the next big block can include it.
+    pub fn send_options(&self, block_type: BlockType, options: &SendOptions) -> SendOptions {
+        match block_type {
+            BlockType::Small => options.clone(),
+            BlockType::Big => {
+                let interval = self.config.big_block_time;
+                SendOptions {
+                    timeout: options.timeout.max(interval * 3),
+                    replace_after: options.replace_after.map(|after| after.max(interval + interval / 2)),
+                    poll_interval: options.poll_interval.max(Duration::from_secs(5)),
+                    ..options.clone()
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::chain::signer::SignerError;
+
+/// Errors surfaced by [`HyperCoreClient`](super::HyperCoreClient).
+#[derive(Debug)]
+pub enum HyperCoreError {
+    /// The HTTP request could not be sent or the body could not be read.
+    Transport(reqwest::Error),
+    /// The API answered with a non-2xx status (429 when rate limited).
+    Http { status: u16, body: String },
+    /// The response body did not match the expected shape.
+    Decode(serde_json::Error),
+    /// The response did not contain the requested item (unknown coin, ...).
+    Missing(String),
+    /// The action cannot be built (e.g. a price with more than 8 decimals).
+    InvalidAction(String),
+    /// The action could not be msgpack-encoded for hashing.
+    Encode(rmp_serde::encode::Error),
+    Signing(SignerError),
+    /// The exchange rejected the action (`{"status": "err"}`).
+    Exchange(String),
+}
+
+impl HyperCoreError {
+    pub fn is_rate_limited(&self) -> bool {
+        matches!(self, HyperCoreError::Http { status: 429, .. })
+    }
+}
+
+impl fmt::Display for HyperCoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HyperCoreError::Transport(e) => write!(f, "HyperCore transport error: {}", e),
+            HyperCoreError::Http { status, body } => write!(f, "HyperCore HTTP error {}: {}", status, body),
+            HyperCoreError::Decode(e) => write!(f, "HyperCore decode error: {}", e),
+            HyperCoreError::Missing(what) => write!(f, "HyperCore returned no {}", what),
+            HyperCoreError::InvalidAction(message) => write!(f, "Invalid HyperCore action: {}", message),
+            HyperCoreError::Encode(e) => write!(f, "HyperCore action encoding failed: {}", e),
+            HyperCoreError::Signing(e) => write!(f, "HyperCore action signing failed: {}", e),
+            HyperCoreError::Exchange(message) => write!(f, "HyperCore rejected the action: {}", message),
+        }
+    }
+}
+
+impl Error for HyperCoreError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            HyperCoreError::Transport(e) => Some(e),
+            HyperCoreError::Decode(e) => Some(e),
+            HyperCoreError::Encode(e) => Some(e),
+            HyperCoreError::Signing(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for HyperCoreError {
+    fn from(e: reqwest::Error) -> Self {
+        HyperCoreError::Transport(e)
+    }
+}
+
+impl From<serde_json::Error> for HyperCoreError {
+    fn from(e: serde_json::Error) -> Self {
+        HyperCoreError::Decode(e)
+    }
+}
+
+impl From<rmp_serde::encode::Error> for HyperCoreError {
+    fn from(e: rmp_serde::encode::Error) -> Self {
+        HyperCoreError::Encode(e)
+    }
+}
+
+impl From<SignerError> for HyperCoreError {
+    fn from(e: SignerError) -> Self {
+        HyperCoreError::Signing(e)
+    }
+}
@@ -0,0 +1,421 @@
+use alloy_primitives::{keccak256, Address, B256, U256};
+use serde::{Deserialize, Serialize};
+
+use crate::chain::signer::{Eip712Domain, Signature, Signer};
+
+use super::error::HyperCoreError;
+use super::types::SpotToken;
+
+/// Chain id of the EIP-712 domain for L1 actions (orders, cancels).
+const L1_CHAIN_ID: u64 = 1337;
+/// Chain id the official SDKs sign user actions (transfers) with.
+pub const SIGNATURE_CHAIN_ID: u64 = 0x66eee;
+/// Spot assets are addressed as `10000 + spot index` in orders.
+const SPOT_ASSET_OFFSET: u32 = 10_000;
+
+/// `Agent(string source,bytes32 connectionId)`
+const AGENT_TYPE: &str = "Agent(string source,bytes32 connectionId)";
+const USD_CLASS_TRANSFER_TYPE: &str =
+    "HyperliquidTransaction:UsdClassTransfer(string hyperliquidChain,string amount,bool toPerp,uint64 nonce)";
+const SPOT_SEND_TYPE: &str =
+    "HyperliquidTransaction:SpotSend(string hyperliquidChain,string destination,string token,string amount,uint64 time)";
+
+/// Asset id of spot market `index` (e.g. `@107`) for [`OrderRequest::asset`].
+pub fn spot_asset(index: u32) -> u32 {
+    SPOT_ASSET_OFFSET + index
+}
+
+/// Address that bridges a spot token between HyperCore and HyperEVM:
+/// `0x20` followed by the big-endian token index, or `0x2222…2222` for HYPE.
+pub fn system_address(token: &SpotToken) -> Address {
+    if token.name == "HYPE" {
+        return Address::repeat_byte(0x22);
+    }
+    let mut bytes = [0u8; 20];
+    bytes[0] = 0x20;
+    bytes[16..].copy_from_slice(&token.index.to_be_bytes());
+    Address::from(bytes)
+}
+
+/// Decimal string the exchange accepts: at most 8 decimals, no trailing zeros.
+pub fn float_to_wire(value: f64) -> Result<String, HyperCoreError> {
+    let rounded = format!("{:.8}", value);
+    let parsed: f64 = rounded
+        .parse()
+        .map_err(|_| HyperCoreError::InvalidAction(format!("{} is not a decimal", value)))?;
+    if !value.is_finite() || (parsed - value).abs() >= 1e-12 {
+        return Err(HyperCoreError::InvalidAction(format!("{} has more than 8 decimals", value)));
+    }
+    let trimmed = rounded.trim_end_matches('0').trim_end_matches('.');
+    Ok(match trimmed {
+        "-0" | "" => "0".to_string(),
+        other => other.to_string(),
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum TimeInForce {
+    /// Good til cancelled
+    Gtc,
+    /// Immediate or cancel; unfilled size is cancelled
+    Ioc,
+    /// Add liquidity only (post-only)
+    Alo,
+}
+
+/// Limit order on a perp (asset index from `meta`) or spot market ([`spot_asset`]).
+#[derive(Debug, Clone)]
+pub struct OrderRequest {
+    pub asset: u32,
+    pub is_buy: bool,
+    pub limit_px: f64,
+    pub sz: f64,
+    pub reduce_only: bool,
+    pub tif: TimeInForce,
+    /// Client order id (16 bytes) for cancelling or querying by cloid.
+    pub cloid: Option<u128>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+enum OrderType {
+    Limit { tif: TimeInForce },
+}
+
+/// Field names and order are part of the signed msgpack encoding.
+#[derive(Debug, Clone, Serialize)]
+struct OrderWire {
+    a: u32,
+    b: bool,
+    p: String,
+    s: String,
+    r: bool,
+    t: OrderType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    c: Option<String>,
+}
+
+impl OrderRequest {
+    fn to_wire(&self) -> Result<OrderWire, HyperCoreError> {
+        Ok(OrderWire {
+            a: self.asset,
+            b: self.is_buy,
+            p: float_to_wire(self.limit_px)?,
+            s: float_to_wire(self.sz)?,
+            r: self.reduce_only,
+            t: OrderType::Limit { tif: self.tif },
+            c: self.cloid.map(|cloid| format!("{:#034x}", cloid)),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CancelRequest {
+    #[serde(rename = "a")]
+    pub asset: u32,
+    #[serde(rename = "o")]
+    pub oid: u64,
+}
+
+/// Exchange actions; the JSON form is posted and the msgpack form is hashed.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum Action {
+    Order { orders: Vec<OrderWire>, grouping: String },
+    Cancel { cancels: Vec<CancelRequest> },
+    #[serde(rename_all = "camelCase")]
+    EvmUserModify { using_big_blocks: bool },
+    #[serde(rename_all = "camelCase")]
+    UsdClassTransfer {
+        hyperliquid_chain: String,
+        signature_chain_id: String,
+        amount: String,
+        to_perp: bool,
+        nonce: u64,
+    },
+    #[serde(rename_all = "camelCase")]
+    SpotSend {
+        hyperliquid_chain: String,
+        signature_chain_id: String,
+        destination: String,
+        token: String,
+        amount: String,
+        time: u64,
+    },
+}
+
+/// `keccak256(msgpack(action) || nonce || vault flag [|| vault] [|| 0x00 || expiresAfter])`
+pub fn action_hash<T: Serialize>(
+    action: &T,
+    vault_address: Option<Address>,
+    nonce: u64,
+    expires_after: Option<u64>,
+) -> Result<B256, HyperCoreError> {
+    let mut data = rmp_serde::to_vec_named(action)?;
+    data.extend_from_slice(&nonce.to_be_bytes());
+    match vault_address {
+        Some(vault) => {
+            data.push(1);
+            data.extend_from_slice(vault.as_slice());
+        }
+        None => data.push(0),
+    }
+    if let Some(expires_after) = expires_after {
+        data.push(0);
+        data.extend_from_slice(&expires_after.to_be_bytes());
+    }
+    Ok(keccak256(data))
+}
+
+fn exchange_domain(name: &str, chain_id: u64) -> Eip712Domain {
+    Eip712Domain {
+        name: Some(name.to_string()),
+        version: Some("1".to_string()),
+        chain_id: Some(chain_id),
+        verifying_contract: Some(Address::ZERO),
+        salt: None,
+    }
+}
+
+/// Signs an L1 action as the "phantom agent" `{source, connectionId: action_hash}`.
+pub fn sign_l1_action<T: Serialize>(
+    signer: &dyn Signer,
+    action: &T,
+    vault_address: Option<Address>,
+    nonce: u64,
+    expires_after: Option<u64>,
+    mainnet: bool,
+) -> Result<Signature, HyperCoreError> {
+    let connection_id = action_hash(action, vault_address, nonce, expires_after)?;
+    let source = if mainnet { "a" } else { "b" };
+
+    let mut encoded = keccak256(AGENT_TYPE).to_vec();
+    encoded.extend_from_slice(keccak256(source).as_slice());
+    encoded.extend_from_slice(connection_id.as_slice());
+    Ok(signer.sign_typed_data(&exchange_domain("Exchange", L1_CHAIN_ID), &keccak256(encoded))?)
+}
+
+/// EIP-712 field encodings used by the user-signed transaction types.
+enum Field<'a> {
+    String(&'a str),
+    Bool(bool),
+    Uint(u64),
+}
+
+fn sign_user_action(signer: &dyn Signer, type_string: &str, fields: &[Field]) -> Result<Signature, HyperCoreError> {
+    let mut encoded = keccak256(type_string).to_vec();
+    for field in fields {
+        match field {
+            Field::String(value) => encoded.extend_from_slice(keccak256(value).as_slice()),
+            Field::Bool(value) => encoded.extend_from_slice(&U256::from(*value as u8).to_be_bytes::<32>()),
+            Field::Uint(value) => encoded.extend_from_slice(&U256::from(*value).to_be_bytes::<32>()),
+        }
+    }
+    let domain = exchange_domain("HyperliquidSignTransaction", SIGNATURE_CHAIN_ID);
+    Ok(signer.sign_typed_data(&domain, &keccak256(encoded))?)
+}
+
+fn hyperliquid_chain(mainnet: bool) -> String {
+    if mainnet { "Mainnet" } else { "Testnet" }.to_string()
+}
+
+/// Signed action ready to post to `/exchange`.
+#[derive(Debug, Clone)]
+pub struct SignedAction {
+    action: serde_json::Value,
+    nonce: u64,
+    signature: Signature,
+    vault_address: Option<Address>,
+}
+
+impl SignedAction {
+    pub fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    pub fn to_payload(&self) -> serde_json::Value {
+        serde_json::json!({
+            "action": self.action,
+            "nonce": self.nonce,
+            "signature": {
+                "r": format!("{:#066x}", self.signature.r),
+                "s": format!("{:#066x}", self.signature.s),
+                "v": 27 + self.signature.y_parity as u8,
+            },
+            "vaultAddress": self.vault_address.map(|vault| format!("{:#x}", vault)),
+        })
+    }
+}
+
+fn signed_l1(
+    signer: &dyn Signer,
+    action: Action,
+    vault_address: Option<Address>,
+    nonce: u64,
+    mainnet: bool,
+) -> Result<SignedAction, HyperCoreError> {
+    let signature = sign_l1_action(signer, &action, vault_address, nonce, None, mainnet)?;
+    Ok(SignedAction {
+        action: serde_json::to_value(&action)?,
+        nonce,
+        signature,
+        vault_address,
+    })
+}
+
+/// Places `orders` as one `order` action (no TP/SL grouping).
+pub fn order_action(
+    signer: &dyn Signer,
+    orders: &[OrderRequest],
+    vault_address: Option<Address>,
+    nonce: u64,
+    mainnet: bool,
+) -> Result<SignedAction, HyperCoreError> {
+    let action = Action::Order {
+        orders: orders.iter().map(OrderRequest::to_wire).collect::<Result<_, _>>()?,
+        grouping: "na".to_string(),
+    };
+    signed_l1(signer, action, vault_address, nonce, mainnet)
+}
+
+pub fn cancel_action(
+    signer: &dyn Signer,
+    cancels: &[CancelRequest],
+    vault_address: Option<Address>,
+    nonce: u64,
+    mainnet: bool,
+) -> Result<SignedAction, HyperCoreError> {
+    let action = Action::Cancel {
+        cancels: cancels.to_vec(),
+    };
+    signed_l1(signer, action, vault_address, nonce, mainnet)
+}
+
+/// Sends the signer's HyperEVM transactions to big blocks (`true`) or small blocks.
+pub fn evm_user_modify_action(
+    signer: &dyn Signer,
+    using_big_blocks: bool,
+    nonce: u64,
+    mainnet: bool,
+) -> Result<SignedAction, HyperCoreError> {
+    signed_l1(signer, Action::EvmUserModify { using_big_blocks }, None, nonce, mainnet)
+}
+
+/// Moves USDC between the spot and perp balances of the signer.
+pub fn usd_class_transfer_action(
+    signer: &dyn Signer,
+    amount: f64,
+    to_perp: bool,
+    nonce: u64,
+    mainnet: bool,
+) -> Result<SignedAction, HyperCoreError> {
+    let chain = hyperliquid_chain(mainnet);
+    let amount = float_to_wire(amount)?;
+    let signature = sign_user_action(
+        signer,
+        USD_CLASS_TRANSFER_TYPE,
+        &[
+            Field::String(&chain),
+            Field::String(&amount),
+            Field::Bool(to_perp),
+            Field::Uint(nonce),
+        ],
+    )?;
+    let action = Action::UsdClassTransfer {
+        hyperliquid_chain: chain,
+        signature_chain_id: format!("{:#x}", SIGNATURE_CHAIN_ID),
+        amount,
+        to_perp,
+        nonce,
+    };
+    Ok(SignedAction {
+        action: serde_json::to_value(&action)?,
+        nonce,
+        signature,
+        vault_address: None,
+    })
+}
+
+/// Sends a spot token on HyperCore. Sending to [`system_address`] of the
+/// token credits the signer's address on HyperEVM.
+pub fn spot_send_action(
+    signer: &dyn Signer,
+    destination: Address,
+    token: &SpotToken,
+    amount: f64,
+    time: u64,
+    mainnet: bool,
+) -> Result<SignedAction, HyperCoreError> {
+    let chain = hyperliquid_chain(mainnet);
+    let destination = format!("{:#x}", destination);
+    let token = format!("{}:{}", token.name, token.token_id);
+    let amount = float_to_wire(amount)?;
+    let signature = sign_user_action(
+        signer,
+        SPOT_SEND_TYPE,
+        &[
+            Field::String(&chain),
+            Field::String(&destination),
+            Field::String(&token),
+            Field::String(&amount),
+            Field::Uint(time),
+        ],
+    )?;
+    let action = Action::SpotSend {
+        hyperliquid_chain: chain,
+        signature_chain_id: format!("{:#x}", SIGNATURE_CHAIN_ID),
+        destination,
+        token,
+        amount,
+        time,
+    };
+    Ok(SignedAction {
+        action: serde_json::to_value(&action)?,
+        nonce: time,
+        signature,
+        vault_address: None,
+    })
+}
+
+/// Outcome of one order in an `order` action.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum OrderStatus {
+    Resting {
+        oid: u64,
+    },
+    Filled {
+        #[serde(rename = "totalSz")]
+        total_sz: String,
+        #[serde(rename = "avgPx")]
+        avg_px: String,
+        oid: u64,
+    },
+    Error(String),
+}
+
+/// Outcome of one cancel in a `cancel` action.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum CancelStatus {
+    Success,
+    Error(String),
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct StatusesData<T> {
+    pub statuses: Vec<T>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct ActionResponse<T> {
+    pub data: T,
+}
+
+/// `{"status": "ok", "response": ...}` or `{"status": "err", "response": "message"}`
+#[derive(Debug, Deserialize)]
+#[serde(tag = "status", content = "response", rename_all = "camelCase")]
+pub(super) enum ExchangeResponse {
+    Ok(serde_json::Value),
+    Err(String),
+}
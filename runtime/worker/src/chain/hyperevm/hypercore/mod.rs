@@ -0,0 +1,265 @@
+// Hyperliquid L1 (HyperCore) info and exchange API client
+pub mod error;
+pub mod exchange;
+pub mod types;
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use alloy_primitives::Address;
+use reqwest::header::{HeaderValue, CONTENT_TYPE};
+use serde::de::DeserializeOwned;
+use serde_json::json;
+
+use crate::chain::signer::Signer;
+
+pub use error::HyperCoreError;
+pub use exchange::{
+    spot_asset, system_address, CancelRequest, CancelStatus, OrderRequest, OrderStatus, SignedAction, TimeInForce,
+};
+pub use types::{
+    parse_decimal, AssetPosition, BookLevel, ClearinghouseState, EvmContract, FundingRate, L2Book, Leverage,
+    MarginSummary, PerpAsset, PerpAssetContext, PerpMeta, Position, SpotBalance, SpotClearinghouseState, SpotMeta,
+    SpotPair, SpotToken,
+};
+
+/// Mainnet API; the info endpoint is `<url>/info`.
+pub const DEFAULT_API_URL: &str = "https://api.hyperliquid.xyz";
+pub const TESTNET_API_URL: &str = "https://api.hyperliquid-testnet.xyz";
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Client for the HyperCore `/info` and `/exchange` endpoints.
+///
+/// Every query is a POST of `{"type": ...}` to the same URL, so pointing
+/// `api_url` at a local mock is enough for tests. Actions are signed for
+/// mainnet unless the URL is the testnet API.
+pub struct HyperCoreClient {
+    api_url: String,
+    http: reqwest::Client,
+    mainnet: bool,
+    /// Last nonce handed out; nonces are millisecond timestamps and must be unique per signer.
+    last_nonce: AtomicU64,
+}
+
+impl HyperCoreClient {
+    pub fn new(api_url: &str) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(DEFAULT_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+
+        Self {
+            api_url: api_url.trim_end_matches('/').to_string(),
+            http,
+            mainnet: !api_url.contains("testnet"),
+            last_nonce: AtomicU64::new(0),
+        }
+    }
+
+    pub fn api_url(&self) -> &str {
+        &self.api_url
+    }
+
+    /// Whether actions are signed for mainnet (`source: "a"`, `Mainnet`).
+    pub fn is_mainnet(&self) -> bool {
+        self.mainnet
+    }
+
+    /// Current time in milliseconds, bumped past the last nonce if needed.
+    pub fn next_nonce(&self) -> u64 {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or_default();
+        let mut last = self.last_nonce.load(Ordering::SeqCst);
+        loop {
+            let nonce = now.max(last + 1);
+            match self
+                .last_nonce
+                .compare_exchange(last, nonce, Ordering::SeqCst, Ordering::SeqCst)
+            {
+                Ok(_) => return nonce,
+                Err(current) => last = current,
+            }
+        }
+    }
+
+    /// Posts a raw info request and decodes the response into `R`.
+    pub async fn info<R: DeserializeOwned>(&self, request: serde_json::Value) -> Result<R, HyperCoreError> {
+        let response = self
+            .http
+            .post(format!("{}/info", self.api_url))
+            .header(CONTENT_TYPE, HeaderValue::from_static("application/json"))
+            .json(&request)
+            .send()
+            .await?;
+
+        let status = response.status();
+        let text = response.text().await?;
+        if !status.is_success() {
+            return Err(HyperCoreError::Http {
+                status: status.as_u16(),
+                body: text,
+            });
+        }
+        Ok(serde_json::from_str(&text)?)
+    }
+
+    /// Perpetual universe; an asset's index is its position in `universe`.
+    pub async fn meta(&self) -> Result<PerpMeta, HyperCoreError> {
+        self.info(json!({"type": "meta"})).await
+    }
+
+    pub async fn spot_meta(&self) -> Result<SpotMeta, HyperCoreError> {
+        self.info(json!({"type": "spotMeta"})).await
+    }
+
+    /// Perp metadata together with mark/oracle prices, funding and open interest.
+    pub async fn meta_and_asset_contexts(&self) -> Result<(PerpMeta, Vec<PerpAssetContext>), HyperCoreError> {
+        self.info(json!({"type": "metaAndAssetCtxs"})).await
+    }
+
+    /// Order book snapshot for a perp (`BTC`) or spot market (`PURR/USDC`, `@107`).
+    pub async fn l2_book(&self, coin: &str) -> Result<L2Book, HyperCoreError> {
+        self.info(json!({"type": "l2Book", "coin": coin})).await
+    }
+
+    /// Mid price of every market, keyed by coin name.
+    pub async fn all_mids(&self) -> Result<HashMap<String, String>, HyperCoreError> {
+        self.info(json!({"type": "allMids"})).await
+    }
+
+    pub async fn mid_price(&self, coin: &str) -> Result<f64, HyperCoreError> {
+        self.all_mids()
+            .await?
+            .get(coin)
+            .and_then(|mid| parse_decimal(mid))
+            .ok_or_else(|| HyperCoreError::Missing(format!("mid price for {}", coin)))
+    }
+
+    /// Current hourly funding rate of every perp, keyed by coin name.
+    pub async fn funding_rates(&self) -> Result<HashMap<String, f64>, HyperCoreError> {
+        let (meta, contexts) = self.meta_and_asset_contexts().await?;
+        Ok(meta
+            .universe
+            .into_iter()
+            .zip(contexts)
+            .filter_map(|(asset, context)| Some((asset.name, parse_decimal(&context.funding)?)))
+            .collect())
+    }
+
+    /// Past funding rates of `coin` between two millisecond timestamps.
+    pub async fn funding_history(
+        &self,
+        coin: &str,
+        start_time: u64,
+        end_time: Option<u64>,
+    ) -> Result<Vec<FundingRate>, HyperCoreError> {
+        let mut request = json!({"type": "fundingHistory", "coin": coin, "startTime": start_time});
+        if let Some(end_time) = end_time {
+            request["endTime"] = end_time.into();
+        }
+        self.info(request).await
+    }
+
+    /// Perp positions and margin of `user`.
+    pub async fn clearinghouse_state(&self, user: Address) -> Result<ClearinghouseState, HyperCoreError> {
+        self.info(json!({"type": "clearinghouseState", "user": format!("{:#x}", user)}))
+            .await
+    }
+
+    /// Spot balances of `user`.
+    pub async fn spot_clearinghouse_state(&self, user: Address) -> Result<SpotClearinghouseState, HyperCoreError> {
+        self.info(json!({"type": "spotClearinghouseState", "user": format!("{:#x}", user)}))
+            .await
+    }
+
+    /// Posts a signed action and returns the `response` of an `ok` status.
+    pub async fn exchange(&self, action: &SignedAction) -> Result<serde_json::Value, HyperCoreError> {
+        let response = self
+            .http
+            .post(format!("{}/exchange", self.api_url))
+            .header(CONTENT_TYPE, HeaderValue::from_static("application/json"))
+            .json(&action.to_payload())
+            .send()
+            .await?;
+
+        let status = response.status();
+        let text = response.text().await?;
+        if !status.is_success() {
+            return Err(HyperCoreError::Http {
+                status: status.as_u16(),
+                body: text,
+            });
+        }
+        match serde_json::from_str(&text)? {
+            exchange::ExchangeResponse::Ok(response) => Ok(response),
+            exchange::ExchangeResponse::Err(message) => Err(HyperCoreError::Exchange(message)),
+        }
+    }
+
+    async fn statuses<T: DeserializeOwned>(&self, action: &SignedAction) -> Result<Vec<T>, HyperCoreError> {
+        let response: exchange::ActionResponse<exchange::StatusesData<T>> =
+            serde_json::from_value(self.exchange(action).await?)?;
+        Ok(response.data.statuses)
+    }
+
+    /// Places limit orders (GTC, IOC or post-only); one status per order, in order.
+    pub async fn place_orders(
+        &self,
+        signer: &dyn Signer,
+        orders: &[OrderRequest],
+        vault_address: Option<Address>,
+    ) -> Result<Vec<OrderStatus>, HyperCoreError> {
+        let action = exchange::order_action(signer, orders, vault_address, self.next_nonce(), self.mainnet)?;
+        self.statuses(&action).await
+    }
+
+    pub async fn cancel_orders(
+        &self,
+        signer: &dyn Signer,
+        cancels: &[CancelRequest],
+        vault_address: Option<Address>,
+    ) -> Result<Vec<CancelStatus>, HyperCoreError> {
+        let action = exchange::cancel_action(signer, cancels, vault_address, self.next_nonce(), self.mainnet)?;
+        self.statuses(&action).await
+    }
+
+    /// Switches the signer's HyperEVM transactions between big and small blocks.
+    pub async fn set_big_blocks(&self, signer: &dyn Signer, enabled: bool) -> Result<(), HyperCoreError> {
+        let action = exchange::evm_user_modify_action(signer, enabled, self.next_nonce(), self.mainnet)?;
+        self.exchange(&action).await.map(|_| ())
+    }
+
+    /// Moves `amount` USDC from the spot to the perp balance (`to_perp`) or back.
+    pub async fn usd_class_transfer(&self, signer: &dyn Signer, amount: f64, to_perp: bool) -> Result<(), HyperCoreError> {
+        let action = exchange::usd_class_transfer_action(signer, amount, to_perp, self.next_nonce(), self.mainnet)?;
+        self.exchange(&action).await.map(|_| ())
+    }
+
+    /// Sends `amount` of a spot token to another HyperCore address.
+    pub async fn spot_send(
+        &self,
+        signer: &dyn Signer,
+        destination: Address,
+        token: &SpotToken,
+        amount: f64,
+    ) -> Result<(), HyperCoreError> {
+        let action =
+            exchange::spot_send_action(signer, destination, token, amount, self.next_nonce(), self.mainnet)?;
+        self.exchange(&action).await.map(|_| ())
+    }
+
+    /// Moves a spot token from HyperCore to the signer's address on HyperEVM.
+    ///
+    /// The reverse direction is a plain HyperEVM transfer of the linked
+    /// ERC-20 (or HYPE) to [`system_address`].
+    pub async fn transfer_to_evm(&self, signer: &dyn Signer, token: &SpotToken, amount: f64) -> Result<(), HyperCoreError> {
+        if token.evm_contract.is_none() && token.name != "HYPE" {
+            return Err(HyperCoreError::InvalidAction(format!("{} is not linked to HyperEVM", token.name)));
+        }
+        self.spot_send(signer, system_address(token), token, amount).await
+    }
+}
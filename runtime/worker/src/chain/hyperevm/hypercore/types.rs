@@ -0,0 +1,235 @@
+use alloy_primitives::Address;
+use serde::{Deserialize, Serialize};
+
+/// Prices, sizes and USD amounts come back as decimal strings; `None` for garbage.
+pub fn parse_decimal(value: &str) -> Option<f64> {
+    value.parse().ok()
+}
+
+/// One perpetual in `meta.universe`; its position is the asset index.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PerpAsset {
+    pub name: String,
+    pub sz_decimals: u32,
+    pub max_leverage: u32,
+    #[serde(default)]
+    pub only_isolated: bool,
+    #[serde(default)]
+    pub is_delisted: bool,
+}
+
+/// Response of `{"type": "meta"}`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PerpMeta {
+    pub universe: Vec<PerpAsset>,
+}
+
+impl PerpMeta {
+    /// Asset index and metadata of `coin` (e.g. `BTC`).
+    pub fn asset(&self, coin: &str) -> Option<(u32, &PerpAsset)> {
+        self.universe
+            .iter()
+            .enumerate()
+            .find(|(_, asset)| asset.name == coin)
+            .map(|(index, asset)| (index as u32, asset))
+    }
+}
+
+/// ERC-20 linked to a HyperCore spot token.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct EvmContract {
+    pub address: Address,
+    /// EVM decimals minus HyperCore `wei_decimals`.
+    pub evm_extra_wei_decimals: i32,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpotToken {
+    pub name: String,
+    pub sz_decimals: u32,
+    pub wei_decimals: u32,
+    pub index: u32,
+    pub token_id: String,
+    #[serde(default)]
+    pub is_canonical: bool,
+    #[serde(default)]
+    pub evm_contract: Option<EvmContract>,
+}
+
+/// Spot market; `tokens` are the base and quote token indices.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpotPair {
+    pub name: String,
+    pub tokens: [u32; 2],
+    pub index: u32,
+    #[serde(default)]
+    pub is_canonical: bool,
+}
+
+/// Response of `{"type": "spotMeta"}`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SpotMeta {
+    pub universe: Vec<SpotPair>,
+    pub tokens: Vec<SpotToken>,
+}
+
+impl SpotMeta {
+    pub fn token(&self, name: &str) -> Option<&SpotToken> {
+        self.tokens.iter().find(|token| token.name == name)
+    }
+
+    /// Spot token linked to an ERC-20 on HyperEVM.
+    pub fn token_by_evm_address(&self, address: Address) -> Option<&SpotToken> {
+        self.tokens
+            .iter()
+            .find(|token| token.evm_contract.as_ref().is_some_and(|contract| contract.address == address))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BookLevel {
+    pub px: String,
+    pub sz: String,
+    /// Number of orders at this price.
+    pub n: u32,
+}
+
+/// Response of `{"type": "l2Book"}`; `levels` is `[bids, asks]`, best first.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct L2Book {
+    pub coin: String,
+    pub time: u64,
+    pub levels: (Vec<BookLevel>, Vec<BookLevel>),
+}
+
+impl L2Book {
+    pub fn bids(&self) -> &[BookLevel] {
+        &self.levels.0
+    }
+
+    pub fn asks(&self) -> &[BookLevel] {
+        &self.levels.1
+    }
+
+    pub fn best_bid(&self) -> Option<f64> {
+        self.bids().first().and_then(|level| parse_decimal(&level.px))
+    }
+
+    pub fn best_ask(&self) -> Option<f64> {
+        self.asks().first().and_then(|level| parse_decimal(&level.px))
+    }
+}
+
+/// Per-asset state from `{"type": "metaAndAssetCtxs"}`, in `meta.universe` order.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PerpAssetContext {
+    /// Current hourly funding rate.
+    pub funding: String,
+    pub open_interest: String,
+    pub oracle_px: String,
+    pub mark_px: String,
+    #[serde(default)]
+    pub mid_px: Option<String>,
+    #[serde(default)]
+    pub premium: Option<String>,
+    pub prev_day_px: String,
+    pub day_ntl_vlm: String,
+}
+
+/// Entry of `{"type": "fundingHistory"}`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FundingRate {
+    pub coin: String,
+    pub funding_rate: String,
+    pub premium: String,
+    /// Milliseconds since the epoch.
+    pub time: u64,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Leverage {
+    /// `cross` or `isolated`.
+    #[serde(rename = "type")]
+    pub leverage_type: String,
+    pub value: u32,
+    #[serde(default)]
+    pub raw_usd: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Position {
+    pub coin: String,
+    /// Signed size; negative for shorts.
+    pub szi: String,
+    #[serde(default)]
+    pub entry_px: Option<String>,
+    pub position_value: String,
+    pub unrealized_pnl: String,
+    #[serde(default)]
+    pub liquidation_px: Option<String>,
+    pub margin_used: String,
+    pub leverage: Leverage,
+    pub max_leverage: u32,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AssetPosition {
+    pub position: Position,
+    /// `oneWay` for every position today.
+    #[serde(rename = "type")]
+    pub position_type: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarginSummary {
+    pub account_value: String,
+    pub total_ntl_pos: String,
+    pub total_raw_usd: String,
+    pub total_margin_used: String,
+}
+
+/// Response of `{"type": "clearinghouseState"}`: a user's perp account.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClearinghouseState {
+    pub asset_positions: Vec<AssetPosition>,
+    pub margin_summary: MarginSummary,
+    pub cross_margin_summary: MarginSummary,
+    pub withdrawable: String,
+    pub time: u64,
+}
+
+impl ClearinghouseState {
+    pub fn position(&self, coin: &str) -> Option<&Position> {
+        self.asset_positions
+            .iter()
+            .map(|asset_position| &asset_position.position)
+            .find(|position| position.coin == coin)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpotBalance {
+    pub coin: String,
+    /// Spot token index.
+    pub token: u32,
+    /// Amount locked in open orders.
+    pub hold: String,
+    pub total: String,
+    pub entry_ntl: String,
+}
+
+/// Response of `{"type": "spotClearinghouseState"}`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SpotClearinghouseState {
+    pub balances: Vec<SpotBalance>,
+}
@@ -1,28 +1,48 @@
-use std::error::Error;
-use alloy_primitives::Address;
+// HyperEVM chain preset and HyperCore integration
+pub mod blocks;
+pub mod hypercore;
+pub mod precompiles;
 
-pub struct HyperEVMChain;
+use std::time::Duration;
 
-impl HyperEVMChain {
-    pub fn new() -> Self {
-        Self
-    }
-}
+use crate::chain::evm::{ChainConfig, NativeToken};
+use crate::chain::gas::FeeOracleConfig;
+use crate::chain::multicall::MulticallConfig;
+use crate::chain::rpc::EndpointConfig;
 
-impl crate::chain::Chain for HyperEVMChain {
-    fn connect(&self) {
-        println!("Connecting to HyperEVM Chain");
-    }
+pub use blocks::{BlockType, DualBlockConfig, DualBlocks};
+pub use hypercore::HyperCoreClient;
 
-    fn get_primary_wallet_address(&self) -> Result<Address, Box<dyn Error>> {
-        get_primary_wallet_address()
-    }
-}
+pub const CHAIN_ID: u64 = 999;
+
+/// Public endpoint used when `config.toml` does not set `[chains.hyperevm] rpc_urls`.
+pub const DEFAULT_RPC_URL: &str = "https://rpc.hyperliquid.xyz/evm";
 
-/// Gets the first wallet address from WALLET_SECRETS_EVM for HyperEVM.
-/// Returns an error if no valid addresses are found.
-pub fn get_primary_wallet_address() -> Result<Address, Box<dyn Error>> {
-    let addresses = crate::chain::parse_wallet_secrets_evm()?;
-    addresses.into_iter().next()
-        .ok_or_else(|| "No wallet addresses available for HyperEVM".into())
+/// Built-in defaults for the `[chains.hyperevm]` section.
+pub fn default_config() -> ChainConfig {
+    ChainConfig {
+        key: "hyperevm".to_string(),
+        chain_id: CHAIN_ID,
+        name: "HyperEVM".to_string(),
+        rpc_endpoints: vec![EndpointConfig::new(DEFAULT_RPC_URL)],
+        // The public HyperEVM RPC has no WebSocket endpoint
+        ws_urls: Vec::new(),
+        rpc_quorum: 1,
+        native_token: NativeToken {
+            symbol: "HYPE".to_string(),
+            decimals: 18,
+        },
+        // Small blocks; big blocks come roughly once a minute
+        block_time: Duration::from_secs(1),
+        // HyperBFT finalizes blocks on commit
+        confirmations: 1,
+        explorer_url: Some("https://hyperevmscan.io".to_string()),
+        eip1559: true,
+        lifi_key: Some("hyp".to_string()),
+        fee: FeeOracleConfig::default(),
+        multicall: MulticallConfig::default(),
+        allow_unchecked_output: false,
+        hypercore_api_url: Some(hypercore::DEFAULT_API_URL.to_string()),
+        dual_blocks: Some(DualBlockConfig::default()),
+    }
 }
@@ -0,0 +1,127 @@
+// HyperEVM read precompiles exposing HyperCore state
+use alloy_primitives::{address, Address, Bytes, U256};
+
+use crate::chain::rpc::{BlockTag, CallRequest, RpcClient, RpcError};
+
+/// `position(address user, uint16 perp)`
+pub const POSITION: Address = address!("0000000000000000000000000000000000000800");
+/// `spotBalance(address user, uint64 token)`
+pub const SPOT_BALANCE: Address = address!("0000000000000000000000000000000000000801");
+/// `withdrawable(address user)`
+pub const WITHDRAWABLE: Address = address!("0000000000000000000000000000000000000803");
+/// `markPx(uint32 perp)`
+pub const MARK_PX: Address = address!("0000000000000000000000000000000000000806");
+/// `oraclePx(uint32 perp)`
+pub const ORACLE_PX: Address = address!("0000000000000000000000000000000000000807");
+/// `spotPx(uint32 spot)`
+pub const SPOT_PX: Address = address!("0000000000000000000000000000000000000808");
+/// `l1BlockNumber()`
+pub const L1_BLOCK_NUMBER: Address = address!("0000000000000000000000000000000000000809");
+
+/// Perp position as stored on HyperCore.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PerpPosition {
+    /// Signed size in units of `10^-szDecimals`.
+    pub szi: i64,
+    /// Entry notional in USD with 6 decimals.
+    pub entry_ntl: u64,
+    pub isolated_raw_usd: i64,
+    pub leverage: u32,
+    pub is_isolated: bool,
+}
+
+/// Spot balance in HyperCore `wei_decimals` units.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpotBalance {
+    pub total: u64,
+    pub hold: u64,
+    pub entry_ntl: u64,
+}
+
+/// Precompiles take the ABI-encoded arguments without a selector.
+async fn read(rpc: &RpcClient, precompile: Address, args: &[U256], words: usize) -> Result<Vec<U256>, RpcError> {
+    let data: Vec<u8> = args.iter().flat_map(|arg| arg.to_be_bytes::<32>()).collect();
+    let call = CallRequest {
+        to: Some(precompile),
+        data: Some(Bytes::from(data)),
+        ..Default::default()
+    };
+    let output = rpc.call(&call, BlockTag::Latest).await?;
+    if output.len() < words * 32 {
+        return Err(RpcError::Missing(format!(
+            "{}-word output from precompile {:#x} (got {} bytes)",
+            words,
+            precompile,
+            output.len()
+        )));
+    }
+    Ok(output.chunks(32).take(words).map(U256::from_be_slice).collect())
+}
+
+fn address_arg(address: Address) -> U256 {
+    U256::from_be_slice(address.into_word().as_slice())
+}
+
+/// Low 64 bits of an ABI word as a two's complement `int64`.
+fn to_i64(word: U256) -> i64 {
+    word.as_limbs()[0] as i64
+}
+
+fn to_u64(word: U256) -> u64 {
+    word.as_limbs()[0]
+}
+
+/// Mark price of perp `index`; see [`perp_price`] for scaling.
+pub async fn mark_px(rpc: &RpcClient, index: u32) -> Result<u64, RpcError> {
+    Ok(to_u64(read(rpc, MARK_PX, &[U256::from(index)], 1).await?[0]))
+}
+
+/// Oracle price of perp `index`; see [`perp_price`] for scaling.
+pub async fn oracle_px(rpc: &RpcClient, index: u32) -> Result<u64, RpcError> {
+    Ok(to_u64(read(rpc, ORACLE_PX, &[U256::from(index)], 1).await?[0]))
+}
+
+/// Price of spot market `index`; see [`spot_price`] for scaling.
+pub async fn spot_px(rpc: &RpcClient, index: u32) -> Result<u64, RpcError> {
+    Ok(to_u64(read(rpc, SPOT_PX, &[U256::from(index)], 1).await?[0]))
+}
+
+/// HyperCore block the HyperEVM block was built on.
+pub async fn l1_block_number(rpc: &RpcClient) -> Result<u64, RpcError> {
+    Ok(to_u64(read(rpc, L1_BLOCK_NUMBER, &[], 1).await?[0]))
+}
+
+pub async fn position(rpc: &RpcClient, user: Address, perp: u16) -> Result<PerpPosition, RpcError> {
+    let words = read(rpc, POSITION, &[address_arg(user), U256::from(perp)], 5).await?;
+    Ok(PerpPosition {
+        szi: to_i64(words[0]),
+        entry_ntl: to_u64(words[1]),
+        isolated_raw_usd: to_i64(words[2]),
+        leverage: to_u64(words[3]) as u32,
+        is_isolated: !words[4].is_zero(),
+    })
+}
+
+pub async fn spot_balance(rpc: &RpcClient, user: Address, token: u64) -> Result<SpotBalance, RpcError> {
+    let words = read(rpc, SPOT_BALANCE, &[address_arg(user), U256::from(token)], 3).await?;
+    Ok(SpotBalance {
+        total: to_u64(words[0]),
+        hold: to_u64(words[1]),
+        entry_ntl: to_u64(words[2]),
+    })
+}
+
+/// Perp USD withdrawable by `user`, with 6 decimals.
+pub async fn withdrawable(rpc: &RpcClient, user: Address) -> Result<u64, RpcError> {
+    Ok(to_u64(read(rpc, WITHDRAWABLE, &[address_arg(user)], 1).await?[0]))
+}
+
+/// Perp prices have `6 - szDecimals` decimals.
+pub fn perp_price(raw: u64, sz_decimals: u32) -> f64 {
+    raw as f64 / 10f64.powi(6 - sz_decimals as i32)
+}
+
+/// Spot prices have `8 - szDecimals` decimals (of the base token).
+pub fn spot_price(raw: u64, base_sz_decimals: u32) -> f64 {
+    raw as f64 / 10f64.powi(8 - base_sz_decimals as i32)
+}
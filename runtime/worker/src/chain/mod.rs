@@ -1,21 +1,108 @@
 use std::env;
 use std::error::Error;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
 use alloy_primitives::{Address, keccak256};
 use k256::ecdsa::SigningKey;
 
+pub mod blocks;
 pub mod ethereum;
+pub mod evm;
+pub mod gas;
 pub mod hyperevm;
+pub mod multicall;
+pub mod nonce;
+pub mod rpc;
+pub mod set;
+pub mod signer;
+pub mod tx;
+pub mod ws;
 
-pub use ethereum::EthereumChain;
-pub use hyperevm::HyperEVMChain;
+pub use blocks::{BlockEvent, BlockTracker};
+pub use evm::{ChainConfig, EvmChain};
+pub use gas::FeeOracle;
+pub use hyperevm::{BlockType, DualBlocks, HyperCoreClient};
+pub use multicall::{Multicall, TypedCall};
+pub use nonce::NonceManager;
+pub use set::ChainSet;
+pub use rpc::{BlockTag, CallRequest, RpcClient, RpcError};
+pub use signer::{LocalSigner, Signer, WalletAccount};
+pub use ws::{Subscription, SubscriptionClient};
 
-pub trait Chain {
+pub trait Chain: Send + Sync {
     fn connect(&self);
-    fn get_primary_wallet_address(&self) -> Result<Address, Box<dyn Error>>;
+    /// Static chain description (id, name, endpoints, native token, ...).
+    fn config(&self) -> &ChainConfig;
+    /// JSON-RPC client bound to this chain's configured endpoint.
+    fn rpc(&self) -> &RpcClient;
+    /// Every configured wallet, signing and watch-only.
+    fn accounts(&self) -> &[WalletAccount];
+    /// Nonce bookkeeping shared by everything sending on this chain.
+    fn nonces(&self) -> &NonceManager;
+    /// Fee suggestions and replacement bumps for this chain.
+    fn fee_oracle(&self) -> &FeeOracle;
+    /// WebSocket subscriptions, or `None` if no `ws_urls` are configured.
+    fn subscriptions(&self) -> Option<&SubscriptionClient>;
+    /// Recent canonical blocks, reorg events and the chain's confirmation depth.
+    fn blocks(&self) -> &BlockTracker;
+    /// Batched `eth_call`s through Multicall3, with a plain `eth_call` fallback.
+    fn multicall(&self) -> &Multicall;
+    /// HyperCore info API, or `None` on chains other than HyperEVM.
+    fn hypercore(&self) -> Option<&HyperCoreClient>;
+    /// Small/big block routing, or `None` on chains with a single block type.
+    fn dual_blocks(&self) -> Option<&DualBlocks>;
+
+    /// Signer for `address`, or `None` if it is unknown or watch-only.
+    fn signer(&self, address: Address) -> Option<Arc<dyn Signer>> {
+        self.accounts()
+            .iter()
+            .find(|account| account.address() == address)
+            .and_then(|account| account.signer())
+    }
+
+    /// First account that can sign.
+    fn primary_signer(&self) -> Result<Arc<dyn Signer>, Box<dyn Error>> {
+        self.accounts()
+            .iter()
+            .find_map(|account| account.signer())
+            .ok_or_else(|| "No signing wallet available (all WALLET_SECRETS_EVM entries are watch-only)".into())
+    }
+}
+
+/// Checks that every RPC endpoint of `chain` serves the configured chain id.
+///
+/// An endpoint on another network is an error; unreachable endpoints are only
+/// reported, as long as at least one endpoint confirmed the id.
+pub async fn verify_chain_id(chain: &dyn Chain) -> Result<(), Box<dyn Error>> {
+    let config = chain.config();
+    let mut confirmed = 0;
+    let mut mismatched = Vec::new();
+    for (url, result) in chain.rpc().endpoint_chain_ids().await {
+        match result {
+            Ok(id) if id == config.chain_id => confirmed += 1,
+            Ok(id) => mismatched.push(format!("{} (chain {})", url, id)),
+            Err(e) => println!("⚠️  [{}] Could not verify chain id of {}: {}", config.name, url, e),
+        }
+    }
+
+    if !mismatched.is_empty() {
+        return Err(format!(
+            "[chains.{}] expects chain {} but endpoints serve another chain: {}",
+            config.key,
+            config.chain_id,
+            mismatched.join(", ")
+        )
+        .into());
+    }
+    if confirmed == 0 {
+        return Err(format!("[chains.{}] no RPC endpoint answered eth_chainId", config.key).into());
+    }
+    Ok(())
 }
 
-/// Converts a private key to an Ethereum address using k256 and alloy-primitives
-fn private_key_to_address_evm(private_key: &str) -> Result<Address, Box<dyn Error>> {
+/// Parses a hex private key (with or without 0x prefix) into a k256 signing key
+pub(crate) fn parse_private_key_evm(private_key: &str) -> Result<SigningKey, Box<dyn Error>> {
     let key_str = private_key.strip_prefix("0x").unwrap_or(private_key);
     let key_bytes = hex::decode(key_str)?;
     
@@ -23,38 +110,96 @@ fn private_key_to_address_evm(private_key: &str) -> Result<Address, Box<dyn Erro
         return Err("Private key must be 32 bytes".into());
     }
     
-    // Use k256 for efficient key operations
-    let signing_key = SigningKey::from_slice(&key_bytes)?;
+    Ok(SigningKey::from_slice(&key_bytes)?)
+}
+
+/// Derives the Ethereum address of a k256 signing key
+pub fn signing_key_to_address_evm(signing_key: &SigningKey) -> Address {
     let public_key = signing_key.verifying_key().to_encoded_point(false);
     let public_key_bytes = &public_key.as_bytes()[1..]; // Remove 0x04 prefix
     
     // Hash and extract address
     let hash = keccak256(public_key_bytes);
-    let address_bytes: [u8; 20] = hash[12..].try_into()?;
-    
-    Ok(Address::from(address_bytes))
+    Address::from_slice(&hash[12..])
 }
 
-/// Parses WALLET_SECRETS_EVM and converts private keys to addresses
-pub fn parse_wallet_secrets_evm() -> Result<Vec<Address>, Box<dyn Error>> {
+/// Parses WALLET_SECRETS_EVM into wallet accounts.
+/// Private keys become local signers; plain addresses become watch-only accounts.
+pub fn parse_wallet_secrets_evm() -> Result<Vec<WalletAccount>, Box<dyn Error>> {
     let secrets = env::var("WALLET_SECRETS_EVM")?;
     
-    let addresses: Vec<Address> = secrets
+    let accounts: Vec<WalletAccount> = secrets
         .split(',')
         .filter_map(|key| {
             let key = key.trim();
             if key.is_empty() { return None; }
             
             // Try private key conversion first, then direct address parsing
-            private_key_to_address_evm(key)
-                .or_else(|_| key.parse::<Address>())
-                .ok()
+            match LocalSigner::from_hex(key) {
+                Ok(signer) => Some(WalletAccount::Signer(Arc::new(signer))),
+                Err(_) => key.parse::<Address>().ok().map(WalletAccount::WatchOnly),
+            }
         })
         .collect();
     
-    if addresses.is_empty() {
+    if accounts.is_empty() {
         Err("No valid wallet addresses found".into())
     } else {
-        Ok(addresses)
+        Ok(accounts)
     }
-}
\ No newline at end of file
+}
+
+/// Loads every configured EVM wallet: raw keys / addresses from WALLET_SECRETS_EVM,
+/// encrypted keystores from WALLET_KEYSTORE_DIR and HD accounts from WALLET_MNEMONIC_EVM.
+/// An address that appears in both is kept once, preferring the signing entry.
+pub fn load_wallet_accounts_evm() -> Result<Vec<WalletAccount>, Box<dyn Error>> {
+    let mut accounts = Vec::new();
+    
+    if env::var("WALLET_SECRETS_EVM").is_ok() {
+        accounts.extend(parse_wallet_secrets_evm()?);
+    }
+    
+    if let Some(dir) = env::var("WALLET_KEYSTORE_DIR").ok().filter(|dir| !dir.is_empty()) {
+        let password = keystore_password()?;
+        let signers = signer::keystore::load_keystore_dir(Path::new(&dir), &password)?;
+        accounts.extend(signers.into_iter().map(|signer| WalletAccount::Signer(Arc::new(signer))));
+    }
+    
+    if let Some(phrase) = env::var("WALLET_MNEMONIC_EVM").ok().filter(|phrase| !phrase.is_empty()) {
+        let passphrase = env::var("WALLET_MNEMONIC_PASSPHRASE").unwrap_or_default();
+        let path_template = env::var("WALLET_DERIVATION_PATH_EVM")
+            .ok()
+            .filter(|path| !path.is_empty())
+            .unwrap_or_else(|| signer::hd::DEFAULT_DERIVATION_PATH.to_string());
+        let signers = signer::hd::signers_from_mnemonic(&phrase, &passphrase, &path_template)?;
+        accounts.extend(signers.into_iter().map(|signer| WalletAccount::Signer(Arc::new(signer))));
+    }
+    
+    let mut unique: Vec<WalletAccount> = Vec::new();
+    for account in accounts {
+        match unique.iter().position(|existing| existing.address() == account.address()) {
+            Some(index) if unique[index].is_watch_only() && !account.is_watch_only() => unique[index] = account,
+            Some(_) => {}
+            None => unique.push(account),
+        }
+    }
+    
+    if unique.is_empty() {
+        Err("No wallets configured (set WALLET_SECRETS_EVM, WALLET_KEYSTORE_DIR or WALLET_MNEMONIC_EVM)".into())
+    } else {
+        Ok(unique)
+    }
+}
+
+/// Keystore passphrase from WALLET_KEYSTORE_PASSWORD, WALLET_KEYSTORE_PASSWORD_FILE
+/// (e.g. a Docker secret), or an interactive prompt as a last resort.
+pub fn keystore_password() -> Result<String, Box<dyn Error>> {
+    if let Ok(password) = env::var("WALLET_KEYSTORE_PASSWORD") {
+        return Ok(password);
+    }
+    if let Some(path) = env::var("WALLET_KEYSTORE_PASSWORD_FILE").ok().filter(|path| !path.is_empty()) {
+        let password = fs::read_to_string(path)?;
+        return Ok(password.trim_end_matches(['\r', '\n']).to_string());
+    }
+    Ok(rpassword::prompt_password("Keystore passphrase: ")?)
+}
@@ -0,0 +1,380 @@
+// Batched eth_call through Multicall3 aggregate3
+use std::error::Error;
+use std::fmt;
+use std::sync::Mutex;
+
+use alloy_primitives::{address, Address, Bytes, U256};
+
+use crate::chain::rpc::{BlockTag, CallRequest, RpcClient, RpcError};
+use crate::chain::tx::decode_revert_reason;
+
+/// Multicall3 is deployed at the same address on almost every EVM chain.
+pub const MULTICALL3_ADDRESS: Address = address!("cA11bde05977b3631167028862bE2a173976CA11");
+
+/// `aggregate3((address,bool,bytes)[])`
+const AGGREGATE3_SELECTOR: [u8; 4] = [0x82, 0xad, 0x56, 0xcb];
+/// `balanceOf(address)`
+const BALANCE_OF_SELECTOR: [u8; 4] = [0x70, 0xa0, 0x82, 0x31];
+
+/// Gas assumed for a call without a `gas_hint`: enough for storage-heavy views.
+pub const DEFAULT_CALL_GAS: u64 = 100_000;
+/// Parallel `eth_call`s when Multicall3 is unavailable.
+const FALLBACK_CONCURRENCY: usize = 16;
+
+#[derive(Debug, Clone)]
+pub struct MulticallConfig {
+    /// Multicall3 deployment; `None` disables batching for the chain.
+    pub address: Option<Address>,
+    /// Sum of `gas_hint`s per `aggregate3` call. Kept below the nodes'
+    /// `eth_call` gas cap (commonly 50M).
+    pub max_batch_gas: u64,
+    /// Encoded calldata size per `aggregate3` call.
+    pub max_batch_bytes: usize,
+}
+
+impl Default for MulticallConfig {
+    fn default() -> Self {
+        Self {
+            address: Some(MULTICALL3_ADDRESS),
+            max_batch_gas: 25_000_000,
+            max_batch_bytes: 128 * 1024,
+        }
+    }
+}
+
+/// One read-only call in a batch.
+#[derive(Debug, Clone)]
+pub struct Call {
+    pub target: Address,
+    pub data: Bytes,
+    /// Expected gas, used only to split batches.
+    pub gas_hint: u64,
+}
+
+impl Call {
+    pub fn new(target: Address, data: impl Into<Bytes>) -> Self {
+        Self {
+            target,
+            data: data.into(),
+            gas_hint: DEFAULT_CALL_GAS,
+        }
+    }
+
+    /// ABI-encoded size of this call inside `aggregate3`: offset, address,
+    /// bool, bytes offset, bytes length and the padded bytes.
+    fn encoded_len(&self) -> usize {
+        32 * 5 + self.data.len().div_ceil(32) * 32
+    }
+}
+
+/// Why a single call in a batch produced no value.
+#[derive(Debug, Clone)]
+pub enum CallError {
+    /// The call reverted; `data` is the raw revert payload.
+    Reverted { reason: String, data: Bytes },
+    /// The call succeeded but its return data did not decode.
+    Decode(String),
+}
+
+impl fmt::Display for CallError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CallError::Reverted { reason, .. } => write!(f, "Call reverted: {}", reason),
+            CallError::Decode(reason) => write!(f, "Failed to decode call result: {}", reason),
+        }
+    }
+}
+
+impl Error for CallError {}
+
+/// A call together with the decoder for its return data.
+pub struct TypedCall<T> {
+    pub call: Call,
+    decode: fn(&[u8]) -> Option<T>,
+}
+
+impl<T> TypedCall<T> {
+    pub fn new(call: Call, decode: fn(&[u8]) -> Option<T>) -> Self {
+        Self { call, decode }
+    }
+
+    pub fn decode(&self, data: &[u8]) -> Result<T, CallError> {
+        (self.decode)(data).ok_or_else(|| {
+            CallError::Decode(format!("unexpected return data 0x{} from {:#x}", hex::encode(data), self.call.target))
+        })
+    }
+}
+
+impl TypedCall<U256> {
+    /// `uint256` returning call.
+    pub fn uint(call: Call) -> Self {
+        Self::new(call, decode_u256)
+    }
+
+    /// ERC-20 `balanceOf(owner)`.
+    pub fn balance_of(token: Address, owner: Address) -> Self {
+        let mut data = BALANCE_OF_SELECTOR.to_vec();
+        data.extend_from_slice(owner.into_word().as_slice());
+        let mut call = Call::new(token, data);
+        call.gas_hint = 30_000;
+        Self::uint(call)
+    }
+}
+
+/// First return word as `uint256`.
+pub fn decode_u256(data: &[u8]) -> Option<U256> {
+    data.get(..32).map(U256::from_be_slice)
+}
+
+/// Batches `eth_call`s into Multicall3 `aggregate3` requests.
+///
+/// Every call is sent with `allowFailure = true`, so one reverting call does
+/// not fail the batch. Batches are split by `gas_hint` and encoded size, and
+/// split further if a node rejects one (gas cap, response size). Chains without
+/// Multicall3 get parallel plain `eth_call`s with the same results.
+#[derive(Debug)]
+pub struct Multicall {
+    config: MulticallConfig,
+    /// Whether code exists at `config.address`; checked once on first use.
+    deployed: Mutex<Option<bool>>,
+}
+
+impl Multicall {
+    pub fn new(config: MulticallConfig) -> Self {
+        Self {
+            config,
+            deployed: Mutex::new(None),
+        }
+    }
+
+    pub fn config(&self) -> &MulticallConfig {
+        &self.config
+    }
+
+    /// Whether batching is possible on this chain.
+    pub async fn is_available(&self, rpc: &RpcClient) -> Result<bool, RpcError> {
+        let address = match self.config.address {
+            Some(address) => address,
+            None => return Ok(false),
+        };
+        if let Some(deployed) = *self.deployed.lock().unwrap() {
+            return Ok(deployed);
+        }
+        let deployed = !rpc.get_code(address, BlockTag::Latest).await?.is_empty();
+        if !deployed {
+            println!("Multicall3 is not deployed at {:#x}; falling back to single eth_call", address);
+        }
+        *self.deployed.lock().unwrap() = Some(deployed);
+        Ok(deployed)
+    }
+
+    /// Executes `calls` on top of `block`; results are in the same order.
+    /// `Err` only for failures of the RPC itself.
+    pub async fn aggregate(
+        &self,
+        rpc: &RpcClient,
+        calls: &[Call],
+        block: BlockTag,
+    ) -> Result<Vec<Result<Bytes, CallError>>, RpcError> {
+        if calls.is_empty() {
+            return Ok(Vec::new());
+        }
+        if !self.is_available(rpc).await? {
+            return call_each(rpc, calls, block).await;
+        }
+
+        let mut results: Vec<Option<Result<Bytes, CallError>>> = vec![None; calls.len()];
+        let mut pending = self.split(calls);
+        while !pending.is_empty() {
+            let responses = futures::future::join_all(
+                pending
+                    .iter()
+                    .map(|range| self.aggregate3(rpc, &calls[range.clone()], block)),
+            )
+            .await;
+
+            let mut retry = Vec::new();
+            for (range, response) in pending.into_iter().zip(responses) {
+                match response {
+                    Ok(batch) => {
+                        for (slot, result) in results[range].iter_mut().zip(batch) {
+                            *slot = Some(result);
+                        }
+                    }
+                    // Every endpoint is down; smaller batches will not help
+                    Err(e) if e.is_endpoint_failure() => return Err(e),
+                    // A node-side limit was hit: halve the batch and try again
+                    Err(e) if range.len() > 1 => {
+                        println!("aggregate3 of {} calls failed ({}), splitting", range.len(), e);
+                        let middle = range.start + range.len() / 2;
+                        retry.push(range.start..middle);
+                        retry.push(middle..range.end);
+                    }
+                    Err(_) => {
+                        let single = call_each(rpc, &calls[range.clone()], block).await?;
+                        for (slot, result) in results[range].iter_mut().zip(single) {
+                            *slot = Some(result);
+                        }
+                    }
+                }
+            }
+            pending = retry;
+        }
+
+        results
+            .into_iter()
+            .enumerate()
+            .map(|(index, result)| result.ok_or_else(|| RpcError::Missing(format!("result for call {}", index))))
+            .collect()
+    }
+
+    /// `aggregate` with every result decoded by its call's decoder.
+    pub async fn call_typed<T>(
+        &self,
+        rpc: &RpcClient,
+        calls: &[TypedCall<T>],
+        block: BlockTag,
+    ) -> Result<Vec<Result<T, CallError>>, RpcError> {
+        let plain: Vec<Call> = calls.iter().map(|typed| typed.call.clone()).collect();
+        let results = self.aggregate(rpc, &plain, block).await?;
+        Ok(calls
+            .iter()
+            .zip(results)
+            .map(|(typed, result)| result.and_then(|data| typed.decode(&data)))
+            .collect())
+    }
+
+    /// Greedy split into index ranges that respect the gas and size limits.
+    fn split(&self, calls: &[Call]) -> Vec<std::ops::Range<usize>> {
+        let mut ranges = Vec::new();
+        let (mut start, mut gas, mut bytes) = (0, 0u64, 0usize);
+        for (index, call) in calls.iter().enumerate() {
+            let over = gas + call.gas_hint > self.config.max_batch_gas
+                || bytes + call.encoded_len() > self.config.max_batch_bytes;
+            if over && index > start {
+                ranges.push(start..index);
+                (start, gas, bytes) = (index, 0, 0);
+            }
+            gas += call.gas_hint;
+            bytes += call.encoded_len();
+        }
+        ranges.push(start..calls.len());
+        ranges
+    }
+
+    async fn aggregate3(
+        &self,
+        rpc: &RpcClient,
+        calls: &[Call],
+        block: BlockTag,
+    ) -> Result<Vec<Result<Bytes, CallError>>, RpcError> {
+        let request = CallRequest {
+            to: self.config.address,
+            data: Some(encode_aggregate3(calls)),
+            ..Default::default()
+        };
+        let output = rpc.call(&request, block).await?;
+        let decoded = decode_aggregate3(&output, calls.len())
+            .ok_or_else(|| RpcError::Missing(format!("aggregate3 results for {} calls", calls.len())))?;
+        Ok(decoded
+            .into_iter()
+            .map(|(success, data)| if success { Ok(data) } else { Err(reverted(data)) })
+            .collect())
+    }
+}
+
+/// Plain `eth_call` per call, a few at a time.
+async fn call_each(
+    rpc: &RpcClient,
+    calls: &[Call],
+    block: BlockTag,
+) -> Result<Vec<Result<Bytes, CallError>>, RpcError> {
+    let mut results = Vec::with_capacity(calls.len());
+    for chunk in calls.chunks(FALLBACK_CONCURRENCY) {
+        let responses = futures::future::join_all(chunk.iter().map(|call| {
+            let request = CallRequest {
+                to: Some(call.target),
+                data: Some(call.data.clone()),
+                ..Default::default()
+            };
+            async move { rpc.call(&request, block).await }
+        }))
+        .await;
+        for response in responses {
+            results.push(match response {
+                Ok(data) => Ok(data),
+                Err(e) if e.is_revert() => Err(reverted(revert_data(&e))),
+                Err(e) => return Err(e),
+            });
+        }
+    }
+    Ok(results)
+}
+
+fn reverted(data: Bytes) -> CallError {
+    CallError::Reverted {
+        reason: decode_revert_reason(&data),
+        data,
+    }
+}
+
+fn revert_data(e: &RpcError) -> Bytes {
+    match e {
+        RpcError::Rpc { data, .. } => data
+            .as_ref()
+            .and_then(|value| value.as_str())
+            .and_then(|hex| hex.parse::<Bytes>().ok())
+            .unwrap_or_default(),
+        _ => Bytes::new(),
+    }
+}
+
+fn word(value: usize) -> [u8; 32] {
+    U256::from(value).to_be_bytes()
+}
+
+/// `aggregate3(calls)` calldata with `allowFailure = true` for every call.
+fn encode_aggregate3(calls: &[Call]) -> Bytes {
+    let mut out = AGGREGATE3_SELECTOR.to_vec();
+    out.extend_from_slice(&word(32));
+    out.extend_from_slice(&word(calls.len()));
+
+    // Tuple offsets, relative to the first offset slot
+    let mut offset = 32 * calls.len();
+    for call in calls {
+        out.extend_from_slice(&word(offset));
+        offset += call.encoded_len() - 32;
+    }
+    for call in calls {
+        out.extend_from_slice(call.target.into_word().as_slice());
+        out.extend_from_slice(&word(1));
+        out.extend_from_slice(&word(96));
+        out.extend_from_slice(&word(call.data.len()));
+        out.extend_from_slice(&call.data);
+        out.resize(out.len() + call.data.len().div_ceil(32) * 32 - call.data.len(), 0);
+    }
+    out.into()
+}
+
+/// Decodes `(bool success, bytes returnData)[]`, expecting `count` entries.
+fn decode_aggregate3(data: &[u8], count: usize) -> Option<Vec<(bool, Bytes)>> {
+    let read = |position: usize| -> Option<usize> {
+        U256::from_be_slice(data.get(position..position.checked_add(32)?)?).try_into().ok()
+    };
+
+    let array = read(0)?;
+    if read(array)? != count {
+        return None;
+    }
+    let elements = array + 32;
+    (0..count)
+        .map(|index| {
+            let tuple = elements.checked_add(read(elements + 32 * index)?)?;
+            let success = read(tuple)? != 0;
+            let bytes = tuple.checked_add(read(tuple + 32)?)?;
+            let length = read(bytes)?;
+            let return_data = data.get(bytes + 32..bytes.checked_add(32 + length)?)?;
+            Some((success, Bytes::copy_from_slice(return_data)))
+        })
+        .collect()
+}
@@ -0,0 +1,331 @@
+// Local nonce bookkeeping per sending address
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use alloy_primitives::{Address, Bytes, B256, U256};
+
+use crate::chain::rpc::{BlockTag, RpcError};
+use crate::chain::signer::Signer;
+use crate::chain::tx::{TxError, TxFees, TypedTransaction};
+use crate::chain::Chain;
+
+/// Gas used by a plain ETH transfer, which is what a cancel transaction is.
+const CANCEL_GAS_LIMIT: u64 = 21_000;
+/// Time between two `maintain` passes of `run`.
+const MAINTENANCE_INTERVAL: Duration = Duration::from_secs(60);
+/// Pending time after which `run` reports a broadcast as stuck.
+const STUCK_AFTER: Duration = Duration::from_secs(300);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NonceIssueKind {
+    /// Reserved locally but released without ever reaching the node.
+    Gap,
+    /// Broadcast, but the node no longer has it in its mempool.
+    Dropped,
+    /// Broadcast and still pending after the stuck threshold.
+    Stuck,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NonceIssue {
+    pub nonce: u64,
+    pub kind: NonceIssueKind,
+    /// Hash of the last broadcast using this nonce, if any.
+    pub hash: Option<B256>,
+}
+
+#[derive(Debug, Clone)]
+struct InFlight {
+    hash: Option<B256>,
+    broadcast_at: Option<Instant>,
+}
+
+#[derive(Debug, Default)]
+struct AccountNonces {
+    /// Next never-used nonce.
+    next: u64,
+    /// Reserved or broadcast nonces that are not mined yet.
+    in_flight: BTreeMap<u64, InFlight>,
+    /// Nonces below `next` that were handed back and must be reused first.
+    released: BTreeSet<u64>,
+}
+
+/// Hands out nonces per address without asking the node every time, so that
+/// several strategies can send from the same wallet concurrently.
+#[derive(Debug, Default)]
+pub struct NonceManager {
+    accounts: Mutex<HashMap<Address, AccountNonces>>,
+}
+
+impl NonceManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reserves the next nonce for `address`, syncing from the node on first use.
+    pub async fn reserve(&self, chain: &dyn Chain, address: Address) -> Result<u64, RpcError> {
+        if !self.accounts.lock().unwrap().contains_key(&address) {
+            let pending = chain.rpc().get_transaction_count(address, BlockTag::Pending).await?;
+            self.accounts
+                .lock()
+                .unwrap()
+                .entry(address)
+                .or_insert_with(|| AccountNonces {
+                    next: pending,
+                    ..Default::default()
+                });
+        }
+
+        let mut accounts = self.accounts.lock().unwrap();
+        let account = accounts.entry(address).or_default();
+        let nonce = match account.released.pop_first() {
+            Some(nonce) => nonce,
+            None => {
+                account.next += 1;
+                account.next - 1
+            }
+        };
+        account.in_flight.insert(
+            nonce,
+            InFlight {
+                hash: None,
+                broadcast_at: None,
+            },
+        );
+        Ok(nonce)
+    }
+
+    /// Records that `nonce` was accepted by the node as `hash`.
+    pub fn mark_broadcast(&self, address: Address, nonce: u64, hash: B256) {
+        let mut accounts = self.accounts.lock().unwrap();
+        let account = accounts.entry(address).or_default();
+        account.in_flight.insert(
+            nonce,
+            InFlight {
+                hash: Some(hash),
+                broadcast_at: Some(Instant::now()),
+            },
+        );
+    }
+
+    /// Hands back a nonce whose transaction never reached the node.
+    pub fn release(&self, address: Address, nonce: u64) {
+        let mut accounts = self.accounts.lock().unwrap();
+        if let Some(account) = accounts.get_mut(&address) {
+            account.in_flight.remove(&nonce);
+            if nonce < account.next {
+                account.released.insert(nonce);
+            }
+            // Shrink `next` instead of leaving trailing gaps behind
+            while account.next > 0 && account.released.remove(&(account.next - 1)) {
+                account.next -= 1;
+            }
+        }
+    }
+
+    /// Marks `nonce` as mined.
+    pub fn confirm(&self, address: Address, nonce: u64) {
+        let mut accounts = self.accounts.lock().unwrap();
+        if let Some(account) = accounts.get_mut(&address) {
+            account.in_flight.remove(&nonce);
+            account.released.remove(&nonce);
+        }
+    }
+
+    /// Reconciles local state with the node: drops mined nonces and moves `next`
+    /// forward if transactions were sent from this address elsewhere.
+    pub async fn resync(&self, chain: &dyn Chain, address: Address) -> Result<(), RpcError> {
+        let rpc = chain.rpc();
+        let mined = rpc.get_transaction_count(address, BlockTag::Latest).await?;
+        let pending = rpc.get_transaction_count(address, BlockTag::Pending).await?;
+
+        let mut accounts = self.accounts.lock().unwrap();
+        let account = accounts.entry(address).or_default();
+        account.in_flight.retain(|nonce, _| *nonce >= mined);
+        account.released.retain(|nonce| *nonce >= mined);
+        if pending > account.next {
+            account.next = pending;
+            account.released.retain(|nonce| *nonce < pending);
+        }
+        account.next = account.next.max(mined);
+        Ok(())
+    }
+
+    /// Finds nonces between the last mined one and the local `next` that will
+    /// block later transactions: gaps, dropped transactions and transactions
+    /// pending for longer than `stuck_after`.
+    ///
+    /// A broadcast at or above the node's pending nonce is only reported as
+    /// dropped if the node does not know its hash and no gap lies below it;
+    /// otherwise it is queued behind the gap and is mined once that is filled.
+    pub async fn find_issues(
+        &self,
+        chain: &dyn Chain,
+        address: Address,
+        stuck_after: Duration,
+    ) -> Result<Vec<NonceIssue>, RpcError> {
+        self.resync(chain, address).await?;
+        let rpc = chain.rpc();
+        let pending = rpc.get_transaction_count(address, BlockTag::Pending).await?;
+
+        let (gaps, broadcasts) = {
+            let accounts = self.accounts.lock().unwrap();
+            let account = match accounts.get(&address) {
+                Some(account) => account,
+                None => return Ok(Vec::new()),
+            };
+            let broadcasts: Vec<(u64, B256, Instant)> = account
+                .in_flight
+                .iter()
+                // Reserved but not sent yet: owned by a running send
+                .filter_map(|(&nonce, in_flight)| Some((nonce, in_flight.hash?, in_flight.broadcast_at?)))
+                .collect();
+            (account.released.clone(), broadcasts)
+        };
+
+        let mut issues: Vec<NonceIssue> = gaps
+            .iter()
+            .map(|&nonce| NonceIssue {
+                nonce,
+                kind: NonceIssueKind::Gap,
+                hash: None,
+            })
+            .collect();
+
+        for (nonce, hash, broadcast_at) in broadcasts {
+            let kind = if nonce < pending {
+                if broadcast_at.elapsed() < stuck_after {
+                    continue;
+                }
+                NonceIssueKind::Stuck
+            } else if gaps.range(..nonce).next().is_some() || rpc.get_transaction_by_hash(hash).await?.is_some() {
+                continue;
+            } else {
+                NonceIssueKind::Dropped
+            };
+            issues.push(NonceIssue {
+                nonce,
+                kind,
+                hash: Some(hash),
+            });
+        }
+
+        issues.sort_by_key(|issue| issue.nonce);
+        Ok(issues)
+    }
+
+    /// Occupies gap and dropped nonces with zero-value self-transfers so that
+    /// later nonces can be mined. Nonces are filled in ascending order from the
+    /// node's pending nonce, stopping at the first one that is still live (sent
+    /// or being sent), since replacing that would cancel a real transaction.
+    /// Stuck nonces are left alone as replacing them needs a fee bump over the
+    /// original transaction. Returns the hashes of the cancel transactions;
+    /// a rejected cancel ends the run, since the nonces above it stay blocked.
+    pub async fn fill_gaps(
+        &self,
+        chain: &dyn Chain,
+        signer: &dyn Signer,
+        fees: TxFees,
+        stuck_after: Duration,
+    ) -> Result<Vec<B256>, TxError> {
+        let address = signer.address();
+        let issues = self.find_issues(chain, address, stuck_after).await?;
+        let rpc = chain.rpc();
+        let pending = rpc.get_transaction_count(address, BlockTag::Pending).await?;
+        let chain_id = rpc.chain_id().await?;
+
+        let mut hashes: Vec<B256> = Vec::new();
+        for issue in issues.iter().filter(|issue| issue.nonce >= pending) {
+            // Every filled nonce moves the node's pending nonce one up
+            if issue.nonce != pending + hashes.len() as u64 || issue.kind == NonceIssueKind::Stuck {
+                break;
+            }
+            let cancel = TypedTransaction {
+                chain_id,
+                nonce: issue.nonce,
+                gas_limit: CANCEL_GAS_LIMIT,
+                fees: fees.clone(),
+                to: Some(address),
+                value: U256::ZERO,
+                data: Bytes::new(),
+            };
+            let signed = signer.sign_transaction(&cancel)?;
+            let hash = match rpc.send_raw_transaction(&signed.raw).await {
+                Ok(hash) => hash,
+                Err(e) if hashes.is_empty() => return Err(e.into()),
+                Err(e) => {
+                    println!("Cancel tx for nonce {} of {:#x} rejected: {}", issue.nonce, address, e);
+                    break;
+                }
+            };
+            println!("Filled nonce {} of {:#x} with cancel tx {:#x}", issue.nonce, address, hash);
+
+            {
+                let mut accounts = self.accounts.lock().unwrap();
+                if let Some(account) = accounts.get_mut(&address) {
+                    account.released.remove(&issue.nonce);
+                }
+            }
+            self.mark_broadcast(address, issue.nonce, hash);
+            hashes.push(hash);
+        }
+        Ok(hashes)
+    }
+
+    /// Logs the issues of `signer`'s nonces and fills what `fill_gaps` can,
+    /// paying the fee oracle's current estimate for the cancel transactions.
+    pub async fn repair(
+        &self,
+        chain: &dyn Chain,
+        signer: &dyn Signer,
+        stuck_after: Duration,
+    ) -> Result<Vec<B256>, TxError> {
+        let issues = self.find_issues(chain, signer.address(), stuck_after).await?;
+        if issues.is_empty() {
+            return Ok(Vec::new());
+        }
+        for issue in &issues {
+            println!(
+                "[{}] Nonce {} of {:#x} is {:?}{}",
+                chain.config().name,
+                issue.nonce,
+                signer.address(),
+                issue.kind,
+                issue.hash.map(|hash| format!(" ({:#x})", hash)).unwrap_or_default()
+            );
+        }
+        let fees = chain.fee_oracle().estimate(chain.rpc()).await?;
+        self.fill_gaps(chain, signer, fees, stuck_after).await
+    }
+
+    /// Maintenance pass over every signing account of `chain`: `repair` for
+    /// each, logging failures instead of stopping. Returns the cancel hashes.
+    pub async fn maintain(&self, chain: &dyn Chain, stuck_after: Duration) -> Vec<B256> {
+        let mut filled = Vec::new();
+        for signer in chain.accounts().iter().filter_map(|account| account.signer()) {
+            match self.repair(chain, signer.as_ref(), stuck_after).await {
+                Ok(hashes) => filled.extend(hashes),
+                Err(e) => println!(
+                    "[{}] Nonce maintenance for {:#x} failed: {}",
+                    chain.config().name,
+                    signer.address(),
+                    e
+                ),
+            }
+        }
+        filled
+    }
+
+    /// Runs `maintain` once per minute until the task is dropped, so gaps left
+    /// by failed sends do not block later transactions. The worker spawns this
+    /// for every chain at startup.
+    pub async fn run(&self, chain: &dyn Chain) {
+        let mut interval = tokio::time::interval(MAINTENANCE_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            self.maintain(chain, STUCK_AFTER).await;
+        }
+    }
+}
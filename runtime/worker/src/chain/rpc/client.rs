@@ -0,0 +1,364 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use alloy_primitives::{Address, Bytes, B256, U256, U64};
+use reqwest::header::{HeaderValue, CONTENT_TYPE};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use super::error::RpcError;
+use super::pool::{Endpoint, EndpointConfig, EndpointHealth};
+use super::types::{
+    Block, BlockTag, CallRequest, FeeHistory, Log, LogFilter, SimulatePayload, SimulatedBlock, Transaction,
+    TransactionReceipt,
+};
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Serialized as `[]`; some nodes reject `"params": null`.
+const NO_PARAMS: [u8; 0] = [];
+
+#[derive(Serialize)]
+struct JsonRpcRequest<'a, P> {
+    jsonrpc: &'static str,
+    id: u64,
+    method: &'a str,
+    params: P,
+}
+
+#[derive(Deserialize)]
+struct JsonRpcResponse {
+    #[serde(default)]
+    result: Option<serde_json::Value>,
+    #[serde(default)]
+    error: Option<JsonRpcErrorObject>,
+}
+
+#[derive(Deserialize)]
+struct JsonRpcErrorObject {
+    code: i64,
+    message: String,
+    #[serde(default)]
+    data: Option<serde_json::Value>,
+}
+
+/// JSON-RPC 2.0 client over HTTP for EVM nodes.
+///
+/// Requests go to the healthiest of the configured endpoints and fail over to
+/// the next one on transport errors, HTTP errors and rate limiting. JSON-RPC
+/// error objects (reverts, invalid params) are returned as-is since another
+/// node would answer the same. The URLs are the only chain-specific input, so
+/// the client can be pointed at public RPCs, a local dev node or mock servers.
+pub struct RpcClient {
+    endpoints: Vec<Endpoint>,
+    http: reqwest::Client,
+    next_id: AtomicU64,
+    /// Number of endpoints that must agree in `request_quorum`.
+    quorum: usize,
+}
+
+impl RpcClient {
+    pub fn new(url: &str) -> Self {
+        Self::with_endpoints(vec![EndpointConfig::new(url)], 1)
+    }
+
+    /// Client over several endpoints; `quorum` applies to `request_quorum` only.
+    pub fn with_endpoints(endpoints: Vec<EndpointConfig>, quorum: usize) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(DEFAULT_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+
+        Self {
+            endpoints: endpoints.into_iter().map(Endpoint::new).collect(),
+            http,
+            next_id: AtomicU64::new(1),
+            quorum: quorum.max(1),
+        }
+    }
+
+    /// URL of the endpoint the next request would go to.
+    pub fn url(&self) -> &str {
+        self.ranked_endpoints()
+            .first()
+            .map(|endpoint| endpoint.config.url.as_str())
+            .unwrap_or_default()
+    }
+
+    pub fn endpoint_health(&self) -> Vec<EndpointHealth> {
+        self.endpoints.iter().map(Endpoint::health).collect()
+    }
+
+    /// Endpoints by score, with the ones in cooldown last.
+    fn ranked_endpoints(&self) -> Vec<&Endpoint> {
+        let mut ranked: Vec<(bool, f64, &Endpoint)> = self
+            .endpoints
+            .iter()
+            .map(|endpoint| (endpoint.is_cooling_down(), endpoint.score(), endpoint))
+            .collect();
+        ranked.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)));
+        ranked.into_iter().map(|(_, _, endpoint)| endpoint).collect()
+    }
+
+    /// Sends a raw JSON-RPC request and decodes `result` into `R`.
+    pub async fn request<P, R>(&self, method: &str, params: P) -> Result<R, RpcError>
+    where
+        P: Serialize,
+        R: DeserializeOwned,
+    {
+        let params = serde_json::to_value(params)?;
+
+        loop {
+            let mut last_error = None;
+            let mut rate_limited_for: Option<Duration> = None;
+
+            for endpoint in self.ranked_endpoints() {
+                if let Err(wait) = endpoint.try_acquire() {
+                    rate_limited_for = Some(rate_limited_for.map_or(wait, |current| current.min(wait)));
+                    continue;
+                }
+                match self.send(endpoint, method, &params).await {
+                    Ok(result) => return Ok(serde_json::from_value(result)?),
+                    Err(e) if e.is_endpoint_failure() => {
+                        println!("RPC {} failed on {}: {}", method, endpoint.config.url, e);
+                        last_error = Some(e);
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+
+            match (last_error, rate_limited_for) {
+                // Every usable endpoint failed
+                (Some(e), _) => return Err(e),
+                // Nothing was tried because every endpoint is at its rate limit
+                (None, Some(wait)) => tokio::time::sleep(wait).await,
+                (None, None) => return Err(RpcError::Missing("RPC endpoints".to_string())),
+            }
+        }
+    }
+
+    /// Sends the request to every endpoint not in cooldown and returns the
+    /// result once `quorum` of them returned the same value.
+    pub async fn request_quorum<P, R>(&self, method: &str, params: P) -> Result<R, RpcError>
+    where
+        P: Serialize,
+        R: DeserializeOwned,
+    {
+        if self.quorum <= 1 || self.endpoints.len() <= 1 {
+            return self.request(method, params).await;
+        }
+
+        let params = serde_json::to_value(params)?;
+        let candidates: Vec<&Endpoint> = self
+            .endpoints
+            .iter()
+            .filter(|endpoint| !endpoint.is_cooling_down() && endpoint.try_acquire().is_ok())
+            .collect();
+        let results = futures::future::join_all(
+            candidates
+                .iter()
+                .map(|endpoint| self.send(endpoint, method, &params)),
+        )
+        .await;
+
+        let mut groups: Vec<(serde_json::Value, usize)> = Vec::new();
+        let mut responses = 0;
+        for result in results.into_iter().flatten() {
+            responses += 1;
+            match groups.iter_mut().find(|(value, _)| *value == result) {
+                Some((_, count)) => *count += 1,
+                None => groups.push((result, 1)),
+            }
+        }
+
+        match groups.into_iter().max_by_key(|(_, count)| *count) {
+            Some((value, agreeing)) if agreeing >= self.quorum => Ok(serde_json::from_value(value)?),
+            best => Err(RpcError::NoQuorum {
+                required: self.quorum,
+                agreeing: best.map_or(0, |(_, count)| count),
+                responses,
+            }),
+        }
+    }
+
+    /// One request to one endpoint, recording its latency or failure.
+    async fn send(
+        &self,
+        endpoint: &Endpoint,
+        method: &str,
+        params: &serde_json::Value,
+    ) -> Result<serde_json::Value, RpcError> {
+        let started = Instant::now();
+        let result = self.send_once(&endpoint.config.url, method, params).await;
+        match &result {
+            Err(e) if e.is_endpoint_failure() => endpoint.record_failure(),
+            _ => endpoint.record_success(started.elapsed()),
+        }
+        result
+    }
+
+    async fn send_once(&self, url: &str, method: &str, params: &serde_json::Value) -> Result<serde_json::Value, RpcError> {
+        let body = JsonRpcRequest {
+            jsonrpc: "2.0",
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+            method,
+            params,
+        };
+
+        let response = self
+            .http
+            .post(url)
+            .header(CONTENT_TYPE, HeaderValue::from_static("application/json"))
+            .json(&body)
+            .send()
+            .await?;
+
+        let status = response.status();
+        let text = response.text().await?;
+        if !status.is_success() {
+            return Err(RpcError::Http {
+                status: status.as_u16(),
+                body: text,
+            });
+        }
+
+        let response: JsonRpcResponse = serde_json::from_str(&text)?;
+        if let Some(error) = response.error {
+            return Err(RpcError::Rpc {
+                code: error.code,
+                message: error.message,
+                data: error.data,
+            });
+        }
+
+        Ok(response.result.unwrap_or(serde_json::Value::Null))
+    }
+
+    pub async fn chain_id(&self) -> Result<u64, RpcError> {
+        let id: U64 = self.request("eth_chainId", NO_PARAMS).await?;
+        Ok(id.to())
+    }
+
+    /// `eth_chainId` from every endpoint, so a URL pointing at the wrong
+    /// network is caught even while a healthier endpoint answers requests.
+    pub async fn endpoint_chain_ids(&self) -> Vec<(String, Result<u64, RpcError>)> {
+        let params = serde_json::to_value(NO_PARAMS).unwrap_or_default();
+        let results = futures::future::join_all(
+            self.endpoints
+                .iter()
+                .map(|endpoint| self.send(endpoint, "eth_chainId", &params)),
+        )
+        .await;
+        self.endpoints
+            .iter()
+            .zip(results)
+            .map(|(endpoint, result)| {
+                let id = result.and_then(|value| Ok(serde_json::from_value::<U64>(value)?.to::<u64>()));
+                (endpoint.config.url.clone(), id)
+            })
+            .collect()
+    }
+
+    pub async fn block_number(&self) -> Result<u64, RpcError> {
+        let number: U64 = self.request("eth_blockNumber", NO_PARAMS).await?;
+        Ok(number.to())
+    }
+
+    /// Fetches a block header; `None` if the node does not know the block yet.
+    pub async fn get_block(&self, block: BlockTag) -> Result<Option<Block>, RpcError> {
+        self.request("eth_getBlockByNumber", (block, false)).await
+    }
+
+    /// Fetches a block header by hash; `None` if the node does not know it (e.g. pruned fork).
+    pub async fn get_block_by_hash(&self, hash: B256) -> Result<Option<Block>, RpcError> {
+        self.request("eth_getBlockByHash", (hash, false)).await
+    }
+
+    pub async fn get_latest_block(&self) -> Result<Block, RpcError> {
+        self.get_block(BlockTag::Latest)
+            .await?
+            .ok_or_else(|| RpcError::Missing("latest block".to_string()))
+    }
+
+    pub async fn get_balance(&self, address: Address, block: BlockTag) -> Result<U256, RpcError> {
+        self.request("eth_getBalance", (address, block)).await
+    }
+
+    /// `get_balance` confirmed by the configured quorum of endpoints.
+    pub async fn get_balance_quorum(&self, address: Address, block: BlockTag) -> Result<U256, RpcError> {
+        self.request_quorum("eth_getBalance", (address, block)).await
+    }
+
+    /// Deployed bytecode at `address`; empty for EOAs and undeployed contracts.
+    pub async fn get_code(&self, address: Address, block: BlockTag) -> Result<Bytes, RpcError> {
+        self.request("eth_getCode", (address, block)).await
+    }
+
+    pub async fn get_transaction_count(&self, address: Address, block: BlockTag) -> Result<u64, RpcError> {
+        let count: U64 = self.request("eth_getTransactionCount", (address, block)).await?;
+        Ok(count.to())
+    }
+
+    pub async fn gas_price(&self) -> Result<U256, RpcError> {
+        self.request("eth_gasPrice", NO_PARAMS).await
+    }
+
+    pub async fn max_priority_fee_per_gas(&self) -> Result<U256, RpcError> {
+        self.request("eth_maxPriorityFeePerGas", NO_PARAMS).await
+    }
+
+    /// `eth_feeHistory` over `block_count` blocks ending at `newest`,
+    /// with priority fees sampled at `reward_percentiles` (0-100, ascending).
+    pub async fn fee_history(
+        &self,
+        block_count: u64,
+        newest: BlockTag,
+        reward_percentiles: &[f64],
+    ) -> Result<FeeHistory, RpcError> {
+        self.request(
+            "eth_feeHistory",
+            (format!("{:#x}", block_count), newest, reward_percentiles),
+        )
+        .await
+    }
+
+    pub async fn call(&self, call: &CallRequest, block: BlockTag) -> Result<Bytes, RpcError> {
+        self.request("eth_call", (call, block)).await
+    }
+
+    /// `call` confirmed by the configured quorum of endpoints.
+    pub async fn call_quorum(&self, call: &CallRequest, block: BlockTag) -> Result<Bytes, RpcError> {
+        self.request_quorum("eth_call", (call, block)).await
+    }
+
+    pub async fn estimate_gas(&self, call: &CallRequest, block: BlockTag) -> Result<u64, RpcError> {
+        let gas: U64 = self.request("eth_estimateGas", (call, block)).await?;
+        Ok(gas.to())
+    }
+
+    /// `eth_simulateV1`; not every node implements it yet.
+    pub async fn simulate(&self, payload: &SimulatePayload, block: BlockTag) -> Result<Vec<SimulatedBlock>, RpcError> {
+        self.request("eth_simulateV1", (payload, block)).await
+    }
+
+    /// Logs matching `filter`. Most providers cap the block range per request.
+    pub async fn get_logs(&self, filter: &LogFilter) -> Result<Vec<Log>, RpcError> {
+        self.request("eth_getLogs", (filter,)).await
+    }
+
+    /// `None` if the node has neither mined the transaction nor holds it in its mempool.
+    pub async fn get_transaction_by_hash(&self, hash: B256) -> Result<Option<Transaction>, RpcError> {
+        self.request("eth_getTransactionByHash", (hash,)).await
+    }
+
+    /// `None` while the transaction is pending or unknown to the node.
+    pub async fn get_transaction_receipt(&self, hash: B256) -> Result<Option<TransactionReceipt>, RpcError> {
+        self.request("eth_getTransactionReceipt", (hash,)).await
+    }
+
+    /// Submits a signed, RLP-encoded transaction and returns its hash.
+    pub async fn send_raw_transaction(&self, raw: &[u8]) -> Result<B256, RpcError> {
+        self.request("eth_sendRawTransaction", (Bytes::copy_from_slice(raw),))
+            .await
+    }
+}
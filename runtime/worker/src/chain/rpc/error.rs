@@ -0,0 +1,124 @@
+use std::error::Error;
+use std::fmt;
+
+/// Errors surfaced by [`RpcClient`](super::RpcClient).
+#[derive(Debug)]
+pub enum RpcError {
+    /// The HTTP request could not be sent or the body could not be read.
+    Transport(reqwest::Error),
+    /// The endpoint answered with a non-2xx status.
+    Http { status: u16, body: String },
+    /// The node returned a JSON-RPC error object.
+    Rpc {
+        code: i64,
+        message: String,
+        data: Option<serde_json::Value>,
+    },
+    /// The response body or `result` did not match the expected shape.
+    Decode(serde_json::Error),
+    /// The node returned `null` where a value was required.
+    Missing(String),
+    /// Fewer than `required` endpoints returned the same result.
+    NoQuorum {
+        required: usize,
+        agreeing: usize,
+        responses: usize,
+    },
+}
+
+impl RpcError {
+    /// Errors that say something about the endpoint rather than the request,
+    /// so the same request may succeed on another endpoint. Of the HTTP errors
+    /// only timeouts, rate limits and server errors qualify; other 4xx mean the
+    /// request itself was refused.
+    pub fn is_endpoint_failure(&self) -> bool {
+        match self {
+            RpcError::Transport(_) | RpcError::Decode(_) => true,
+            RpcError::Http { status, .. } => *status == 408 || *status == 429 || *status >= 500,
+            // -32005: request limit exceeded (EIP-1474)
+            RpcError::Rpc { code, message, .. } => {
+                *code == -32005 || message.contains("rate limit") || message.contains("Too Many Requests")
+            }
+            RpcError::Missing(_) | RpcError::NoQuorum { .. } => false,
+        }
+    }
+
+    /// Whether `eth_sendRawTransaction` was rejected because the node already
+    /// has this exact transaction (wording differs between clients).
+    pub fn is_known_transaction(&self) -> bool {
+        match self {
+            RpcError::Rpc { message, .. } => {
+                let message = message.to_lowercase();
+                ["already known", "alreadyknown", "known transaction", "already imported", "already exists"]
+                    .iter()
+                    .any(|known| message.contains(known))
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether `eth_sendRawTransaction` was rejected because the nonce is already mined.
+    pub fn is_nonce_too_low(&self) -> bool {
+        match self {
+            RpcError::Rpc { message, .. } => {
+                let message = message.to_lowercase();
+                message.contains("nonce too low") || message.contains("oldnonce")
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether an `eth_call` / `eth_estimateGas` failed because the call reverted
+    /// (as opposed to a node or transport problem).
+    pub fn is_revert(&self) -> bool {
+        match self {
+            RpcError::Rpc { message, data, .. } => {
+                data.as_ref().is_some_and(|value| value.is_string()) || message.contains("revert")
+            }
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for RpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RpcError::Transport(e) => write!(f, "RPC transport error: {}", e),
+            RpcError::Http { status, body } => write!(f, "RPC HTTP error {}: {}", status, body),
+            RpcError::Rpc { code, message, .. } => write!(f, "RPC error {}: {}", code, message),
+            RpcError::Decode(e) => write!(f, "RPC decode error: {}", e),
+            RpcError::Missing(what) => write!(f, "RPC returned no {}", what),
+            RpcError::NoQuorum {
+                required,
+                agreeing,
+                responses,
+            } => write!(
+                f,
+                "RPC quorum not reached: {} of {} responses agree, {} required",
+                agreeing, responses, required
+            ),
+        }
+    }
+}
+
+impl Error for RpcError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RpcError::Transport(e) => Some(e),
+            RpcError::Decode(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for RpcError {
+    fn from(e: reqwest::Error) -> Self {
+        RpcError::Transport(e)
+    }
+}
+
+impl From<serde_json::Error> for RpcError {
+    fn from(e: serde_json::Error) -> Self {
+        RpcError::Decode(e)
+    }
+}
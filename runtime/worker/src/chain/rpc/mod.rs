@@ -0,0 +1,13 @@
+// JSON-RPC client for EVM nodes
+pub mod client;
+pub mod error;
+pub mod pool;
+pub mod types;
+
+pub use client::RpcClient;
+pub use error::RpcError;
+pub use pool::{EndpointConfig, EndpointHealth};
+pub use types::{
+    AccountOverride, Block, BlockTag, CallRequest, FeeHistory, Log, LogFilter, SimulateBlock, SimulatePayload,
+    SimulatedBlock, SimulatedCall, Transaction, TransactionReceipt,
+};
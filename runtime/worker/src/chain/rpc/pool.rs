@@ -0,0 +1,143 @@
+// Endpoint health and rate limiting for the RPC client
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Weight of the newest sample in the latency / error moving averages.
+const EWMA_ALPHA: f64 = 0.3;
+/// Cooldown after the first failure; doubles per consecutive failure.
+const BASE_COOLDOWN: Duration = Duration::from_secs(1);
+const MAX_COOLDOWN: Duration = Duration::from_secs(60);
+
+/// One RPC endpoint as configured for a chain.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EndpointConfig {
+    pub url: String,
+    /// Maximum requests per second sent to this endpoint; `None` is unlimited.
+    pub rate_limit: Option<u32>,
+}
+
+impl EndpointConfig {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            rate_limit: None,
+        }
+    }
+}
+
+/// Snapshot of an endpoint's health, for logging and diagnostics.
+#[derive(Debug, Clone)]
+pub struct EndpointHealth {
+    pub url: String,
+    pub latency_ms: f64,
+    /// Moving average of failed requests in `0.0..=1.0`.
+    pub error_rate: f64,
+    pub requests: u64,
+    pub cooling_down: bool,
+    pub score: f64,
+}
+
+#[derive(Debug)]
+struct EndpointState {
+    latency_ms: f64,
+    error_rate: f64,
+    requests: u64,
+    consecutive_failures: u32,
+    cooldown_until: Option<Instant>,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+#[derive(Debug)]
+pub(crate) struct Endpoint {
+    pub(crate) config: EndpointConfig,
+    state: Mutex<EndpointState>,
+}
+
+impl Endpoint {
+    pub(crate) fn new(config: EndpointConfig) -> Self {
+        let tokens = config.rate_limit.unwrap_or(0) as f64;
+        Self {
+            config,
+            state: Mutex::new(EndpointState {
+                // Untried endpoints start with a neutral latency so they get traffic
+                latency_ms: 100.0,
+                error_rate: 0.0,
+                requests: 0,
+                consecutive_failures: 0,
+                cooldown_until: None,
+                tokens,
+                last_refill: Instant::now(),
+            }),
+        }
+    }
+
+    /// Lower is better: average latency inflated by the recent error rate.
+    pub(crate) fn score(&self) -> f64 {
+        let state = self.state.lock().unwrap();
+        state.latency_ms * (1.0 + 4.0 * state.error_rate)
+    }
+
+    pub(crate) fn is_cooling_down(&self) -> bool {
+        let state = self.state.lock().unwrap();
+        state.cooldown_until.is_some_and(|until| Instant::now() < until)
+    }
+
+    /// Takes one token from the rate limiter; `Err` holds the wait until the next token.
+    pub(crate) fn try_acquire(&self) -> Result<(), Duration> {
+        let limit = match self.config.rate_limit {
+            Some(limit) if limit > 0 => limit as f64,
+            _ => return Ok(()),
+        };
+
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+        let refill = now.duration_since(state.last_refill).as_secs_f64() * limit;
+        state.tokens = (state.tokens + refill).min(limit);
+        state.last_refill = now;
+
+        if state.tokens >= 1.0 {
+            state.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - state.tokens) / limit))
+        }
+    }
+
+    pub(crate) fn record_success(&self, latency: Duration) {
+        let mut state = self.state.lock().unwrap();
+        let latency_ms = latency.as_secs_f64() * 1000.0;
+        state.latency_ms = if state.requests == 0 {
+            latency_ms
+        } else {
+            EWMA_ALPHA * latency_ms + (1.0 - EWMA_ALPHA) * state.latency_ms
+        };
+        state.error_rate *= 1.0 - EWMA_ALPHA;
+        state.requests += 1;
+        state.consecutive_failures = 0;
+        state.cooldown_until = None;
+    }
+
+    pub(crate) fn record_failure(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.error_rate = EWMA_ALPHA + (1.0 - EWMA_ALPHA) * state.error_rate;
+        state.requests += 1;
+        state.consecutive_failures += 1;
+        let backoff = BASE_COOLDOWN * 2u32.pow(state.consecutive_failures.min(6) - 1);
+        state.cooldown_until = Some(Instant::now() + backoff.min(MAX_COOLDOWN));
+    }
+
+    pub(crate) fn health(&self) -> EndpointHealth {
+        let score = self.score();
+        let cooling_down = self.is_cooling_down();
+        let state = self.state.lock().unwrap();
+        EndpointHealth {
+            url: self.config.url.clone(),
+            latency_ms: state.latency_ms,
+            error_rate: state.error_rate,
+            requests: state.requests,
+            cooling_down,
+            score,
+        }
+    }
+}
@@ -0,0 +1,222 @@
+use std::collections::HashMap;
+
+use alloy_primitives::{Address, Bytes, B256, U256, U64};
+use serde::{Deserialize, Serialize, Serializer};
+
+/// Block selector accepted by the `eth_*` state queries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockTag {
+    Latest,
+    Pending,
+    Safe,
+    Finalized,
+    Earliest,
+    Number(u64),
+}
+
+impl Serialize for BlockTag {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            BlockTag::Latest => serializer.serialize_str("latest"),
+            BlockTag::Pending => serializer.serialize_str("pending"),
+            BlockTag::Safe => serializer.serialize_str("safe"),
+            BlockTag::Finalized => serializer.serialize_str("finalized"),
+            BlockTag::Earliest => serializer.serialize_str("earliest"),
+            BlockTag::Number(number) => serializer.serialize_str(&format!("{:#x}", number)),
+        }
+    }
+}
+
+/// Block header fields returned by `eth_getBlockByNumber` (without transaction bodies).
+#[derive(Deserialize, Debug, Clone)]
+pub struct Block {
+    pub number: U64,
+    /// `None` for the pending block.
+    pub hash: Option<B256>,
+    #[serde(rename = "parentHash")]
+    pub parent_hash: B256,
+    pub timestamp: U64,
+    #[serde(rename = "gasLimit")]
+    pub gas_limit: U64,
+    #[serde(rename = "gasUsed")]
+    pub gas_used: U64,
+    /// Absent on chains (or blocks) before London.
+    #[serde(rename = "baseFeePerGas", default)]
+    pub base_fee_per_gas: Option<U256>,
+}
+
+/// Result of `eth_feeHistory`.
+#[derive(Deserialize, Debug, Clone)]
+pub struct FeeHistory {
+    #[serde(rename = "oldestBlock")]
+    pub oldest_block: U64,
+    /// One entry per block in the range plus the next block's base fee.
+    #[serde(rename = "baseFeePerGas")]
+    pub base_fee_per_gas: Vec<U256>,
+    #[serde(rename = "gasUsedRatio")]
+    pub gas_used_ratio: Vec<f64>,
+    /// Priority fee at each requested percentile, per block.
+    #[serde(default)]
+    pub reward: Option<Vec<Vec<U256>>>,
+}
+
+/// Call object shared by `eth_call` and `eth_estimateGas`.
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct CallRequest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<Address>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<Address>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas: Option<U64>,
+    #[serde(rename = "gasPrice", skip_serializing_if = "Option::is_none")]
+    pub gas_price: Option<U256>,
+    #[serde(rename = "maxFeePerGas", skip_serializing_if = "Option::is_none")]
+    pub max_fee_per_gas: Option<U256>,
+    #[serde(rename = "maxPriorityFeePerGas", skip_serializing_if = "Option::is_none")]
+    pub max_priority_fee_per_gas: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Bytes>,
+}
+
+/// Parameters of `eth_simulateV1`: calls executed in order on top of `block`.
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct SimulatePayload {
+    #[serde(rename = "blockStateCalls")]
+    pub block_state_calls: Vec<SimulateBlock>,
+    /// Adds synthetic ERC-20 style `Transfer` logs for native value transfers.
+    #[serde(rename = "traceTransfers")]
+    pub trace_transfers: bool,
+    /// Enforce nonce, balance and fee checks like a real block would.
+    pub validation: bool,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct SimulateBlock {
+    #[serde(rename = "stateOverrides", skip_serializing_if = "HashMap::is_empty")]
+    pub state_overrides: HashMap<Address, AccountOverride>,
+    pub calls: Vec<CallRequest>,
+}
+
+/// Replacement account state for a simulation.
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct AccountOverride {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub balance: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<Bytes>,
+}
+
+/// One block of an `eth_simulateV1` result.
+#[derive(Deserialize, Debug, Clone)]
+pub struct SimulatedBlock {
+    pub calls: Vec<SimulatedCall>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct SimulatedCall {
+    #[serde(rename = "returnData")]
+    pub return_data: Bytes,
+    #[serde(default)]
+    pub logs: Vec<Log>,
+    #[serde(rename = "gasUsed")]
+    pub gas_used: U64,
+    /// `1` on success, `0` on revert.
+    pub status: U64,
+    #[serde(default)]
+    pub error: Option<SimulatedCallError>,
+}
+
+impl SimulatedCall {
+    pub fn is_success(&self) -> bool {
+        self.status == U64::from(1)
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct SimulatedCallError {
+    pub code: i64,
+    pub message: String,
+    #[serde(default)]
+    pub data: Option<Bytes>,
+}
+
+/// Filter for `eth_getLogs` and `logs` subscriptions.
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct LogFilter {
+    /// Emitting contracts; empty matches any address.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub address: Vec<Address>,
+    /// Per-position topic alternatives; `None` matches any topic in that position.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub topics: Vec<Option<Vec<B256>>>,
+    /// Block range for `eth_getLogs`; ignored by subscriptions.
+    #[serde(rename = "fromBlock", skip_serializing_if = "Option::is_none")]
+    pub from_block: Option<BlockTag>,
+    #[serde(rename = "toBlock", skip_serializing_if = "Option::is_none")]
+    pub to_block: Option<BlockTag>,
+}
+
+/// Log entry emitted by a transaction.
+#[derive(Deserialize, Debug, Clone)]
+pub struct Log {
+    pub address: Address,
+    pub topics: Vec<B256>,
+    pub data: Bytes,
+    #[serde(rename = "blockNumber", default)]
+    pub block_number: Option<U64>,
+    #[serde(rename = "blockHash", default)]
+    pub block_hash: Option<B256>,
+    #[serde(rename = "transactionHash", default)]
+    pub transaction_hash: Option<B256>,
+    #[serde(rename = "logIndex", default)]
+    pub log_index: Option<U64>,
+    #[serde(default)]
+    pub removed: bool,
+}
+
+/// Result of `eth_getTransactionByHash` (only the fields needed to track a transaction).
+#[derive(Deserialize, Debug, Clone)]
+pub struct Transaction {
+    pub hash: B256,
+    pub nonce: U64,
+    pub from: Address,
+    pub to: Option<Address>,
+    pub value: U256,
+    /// `None` while the transaction is in the mempool.
+    #[serde(rename = "blockNumber", default)]
+    pub block_number: Option<U64>,
+}
+
+/// Result of `eth_getTransactionReceipt`.
+#[derive(Deserialize, Debug, Clone)]
+pub struct TransactionReceipt {
+    #[serde(rename = "transactionHash")]
+    pub transaction_hash: B256,
+    #[serde(rename = "blockNumber")]
+    pub block_number: Option<U64>,
+    #[serde(rename = "blockHash")]
+    pub block_hash: Option<B256>,
+    pub from: Address,
+    pub to: Option<Address>,
+    /// `1` on success, `0` on revert (post-Byzantium).
+    #[serde(default)]
+    pub status: Option<U64>,
+    #[serde(rename = "gasUsed")]
+    pub gas_used: U64,
+    #[serde(rename = "effectiveGasPrice", default)]
+    pub effective_gas_price: Option<U256>,
+    #[serde(default)]
+    pub logs: Vec<Log>,
+}
+
+impl TransactionReceipt {
+    pub fn is_success(&self) -> bool {
+        self.status.map(|status| status == U64::from(1)).unwrap_or(true)
+    }
+}
@@ -0,0 +1,74 @@
+// All chains the worker is connected to, keyed by chain id
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::sync::Arc;
+
+use crate::chain::Chain;
+
+/// Chains enabled for this process. One of them is the primary chain that
+/// single-chain strategies operate on.
+#[derive(Clone)]
+pub struct ChainSet {
+    chains: BTreeMap<u64, Arc<dyn Chain>>,
+    primary: u64,
+}
+
+impl ChainSet {
+    /// Creates a set containing only `primary`.
+    pub fn new(primary: Arc<dyn Chain>) -> Self {
+        let primary_id = primary.config().chain_id;
+        let mut chains = BTreeMap::new();
+        chains.insert(primary_id, primary);
+        Self {
+            chains,
+            primary: primary_id,
+        }
+    }
+
+    /// Adds `chain`, replacing any chain with the same id except the primary one.
+    pub fn insert(&mut self, chain: Arc<dyn Chain>) {
+        let chain_id = chain.config().chain_id;
+        if chain_id != self.primary {
+            self.chains.insert(chain_id, chain);
+        }
+    }
+
+    pub fn primary(&self) -> Arc<dyn Chain> {
+        self.chains[&self.primary].clone()
+    }
+
+    pub fn get(&self, chain_id: u64) -> Option<Arc<dyn Chain>> {
+        self.chains.get(&chain_id).cloned()
+    }
+
+    /// Like `get`, but with an error naming the missing chain.
+    pub fn require(&self, chain_id: u64) -> Result<Arc<dyn Chain>, Box<dyn Error>> {
+        self.get(chain_id)
+            .ok_or_else(|| format!("Chain {} is not enabled (add it to enabled_chains in config.toml)", chain_id).into())
+    }
+
+    /// Looks a chain up by its `[chains.<key>]` name.
+    pub fn by_key(&self, key: &str) -> Option<Arc<dyn Chain>> {
+        self.chains.values().find(|chain| chain.config().key == key).cloned()
+    }
+
+    pub fn contains(&self, chain_id: u64) -> bool {
+        self.chains.contains_key(&chain_id)
+    }
+
+    pub fn chain_ids(&self) -> Vec<u64> {
+        self.chains.keys().copied().collect()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Arc<dyn Chain>> {
+        self.chains.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.chains.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chains.is_empty()
+    }
+}
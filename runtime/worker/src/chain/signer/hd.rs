@@ -0,0 +1,185 @@
+// BIP-39 mnemonics and BIP-32 / BIP-44 key derivation
+
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use hmac::{Hmac, Mac};
+use k256::elliptic_curve::sec1::ToEncodedPoint;
+use k256::elliptic_curve::PrimeField;
+use k256::ecdsa::SigningKey;
+use k256::{FieldBytes, Scalar};
+use sha2::Sha512;
+
+use super::LocalSigner;
+
+type HmacSha512 = Hmac<Sha512>;
+
+/// First account of the standard Ethereum BIP-44 path.
+pub const DEFAULT_DERIVATION_PATH: &str = "m/44'/60'/0'/0/0";
+
+const HARDENED_BIT: u32 = 1 << 31;
+
+#[derive(Debug)]
+pub enum HdError {
+    Mnemonic(bip39::Error),
+    InvalidPath(String),
+    /// The derived key is out of range for secp256k1 (probability < 2^-127 per level).
+    InvalidChildKey,
+}
+
+impl fmt::Display for HdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HdError::Mnemonic(e) => write!(f, "Invalid mnemonic: {}", e),
+            HdError::InvalidPath(path) => write!(f, "Invalid derivation path: {}", path),
+            HdError::InvalidChildKey => write!(f, "Derived child key is invalid"),
+        }
+    }
+}
+
+impl Error for HdError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            HdError::Mnemonic(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<bip39::Error> for HdError {
+    fn from(e: bip39::Error) -> Self {
+        HdError::Mnemonic(e)
+    }
+}
+
+/// A BIP-32 path such as `m/44'/60'/0'/0/0` (`'` or `h` marks hardened indices).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DerivationPath(Vec<u32>);
+
+impl FromStr for DerivationPath {
+    type Err = HdError;
+
+    fn from_str(path: &str) -> Result<Self, Self::Err> {
+        let invalid = || HdError::InvalidPath(path.to_string());
+        let mut parts = path.trim().split('/');
+        if parts.next() != Some("m") {
+            return Err(invalid());
+        }
+
+        let mut indices = Vec::new();
+        for part in parts {
+            let (number, hardened) = match part.strip_suffix('\'').or_else(|| part.strip_suffix('h')) {
+                Some(number) => (number, true),
+                None => (part, false),
+            };
+            let index: u32 = number.parse().map_err(|_| invalid())?;
+            if index >= HARDENED_BIT {
+                return Err(invalid());
+            }
+            indices.push(if hardened { index | HARDENED_BIT } else { index });
+        }
+        Ok(Self(indices))
+    }
+}
+
+/// Expands a path template with one optional `{start..end}` (exclusive) or
+/// `{start..=end}` (inclusive) range, e.g. `m/44'/60'/0'/0/{0..20}`.
+pub fn expand_path_template(template: &str) -> Result<Vec<DerivationPath>, HdError> {
+    let invalid = || HdError::InvalidPath(template.to_string());
+
+    let (open, close) = match (template.find('{'), template.find('}')) {
+        (None, None) => return Ok(vec![template.parse()?]),
+        (Some(open), Some(close)) if open < close => (open, close),
+        _ => return Err(invalid()),
+    };
+
+    let range = &template[open + 1..close];
+    let (start, end) = match range.split_once("..=") {
+        Some((start, end)) => {
+            let end: u32 = end.trim().parse().map_err(|_| invalid())?;
+            (start, end.checked_add(1).ok_or_else(invalid)?)
+        }
+        None => {
+            let (start, end) = range.split_once("..").ok_or_else(invalid)?;
+            (start, end.trim().parse().map_err(|_| invalid())?)
+        }
+    };
+    let start: u32 = start.trim().parse().map_err(|_| invalid())?;
+    if start >= end {
+        return Err(invalid());
+    }
+
+    (start..end)
+        .map(|index| {
+            format!("{}{}{}", &template[..open], index, &template[close + 1..]).parse()
+        })
+        .collect()
+}
+
+/// Derives the private key at `path` from a BIP-39 seed.
+fn derive_signing_key(seed: &[u8], path: &DerivationPath) -> Result<SigningKey, HdError> {
+    let (mut key, mut chain_code) = split_hmac(b"Bitcoin seed", seed)?;
+    if bool::from(key.is_zero()) {
+        return Err(HdError::InvalidChildKey);
+    }
+
+    for &index in &path.0 {
+        let mut data = Vec::with_capacity(37);
+        if index & HARDENED_BIT != 0 {
+            data.push(0);
+            data.extend_from_slice(&key.to_bytes());
+        } else {
+            let public_key = k256::PublicKey::from_secret_scalar(&key_to_nonzero(&key)?);
+            data.extend_from_slice(public_key.to_encoded_point(true).as_bytes());
+        }
+        data.extend_from_slice(&index.to_be_bytes());
+
+        let (tweak, next_chain_code) = split_hmac(&chain_code, &data)?;
+        key += tweak;
+        if bool::from(key.is_zero()) {
+            return Err(HdError::InvalidChildKey);
+        }
+        chain_code = next_chain_code;
+    }
+
+    SigningKey::from_bytes(&key.to_bytes()).map_err(|_| HdError::InvalidChildKey)
+}
+
+/// HMAC-SHA512 split into `(IL as scalar, IR)`.
+fn split_hmac(key: &[u8], data: &[u8]) -> Result<(Scalar, [u8; 32]), HdError> {
+    let mut mac = HmacSha512::new_from_slice(key).map_err(|_| HdError::InvalidChildKey)?;
+    mac.update(data);
+    let output = mac.finalize().into_bytes();
+
+    let mut il = FieldBytes::default();
+    il.copy_from_slice(&output[..32]);
+    let scalar = Option::<Scalar>::from(Scalar::from_repr(il)).ok_or(HdError::InvalidChildKey)?;
+    let mut chain_code = [0u8; 32];
+    chain_code.copy_from_slice(&output[32..]);
+    Ok((scalar, chain_code))
+}
+
+fn key_to_nonzero(key: &Scalar) -> Result<k256::NonZeroScalar, HdError> {
+    Option::from(k256::NonZeroScalar::new(*key)).ok_or(HdError::InvalidChildKey)
+}
+
+/// Signer for the key at `path` under a raw BIP-32 seed.
+pub fn signer_from_seed(seed: &[u8], path: &DerivationPath) -> Result<LocalSigner, HdError> {
+    derive_signing_key(seed, path).map(LocalSigner::new)
+}
+
+/// Derives one signer per path in `path_template` from a BIP-39 mnemonic.
+pub fn signers_from_mnemonic(
+    phrase: &str,
+    passphrase: &str,
+    path_template: &str,
+) -> Result<Vec<LocalSigner>, HdError> {
+    let mnemonic = bip39::Mnemonic::parse(phrase.trim())?;
+    let seed = mnemonic.to_seed(passphrase);
+
+    expand_path_template(path_template)?
+        .iter()
+        .map(|path| signer_from_seed(&seed, path))
+        .collect()
+}
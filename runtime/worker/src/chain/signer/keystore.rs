@@ -0,0 +1,393 @@
+// Web3 Secret Storage (v3) keystore files
+//
+// https://ethereum.org/en/developers/docs/data-structures-and-encoding/web3-secret-storage/
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use aes::cipher::{KeyIvInit, StreamCipher};
+use alloy_primitives::{keccak256, Address};
+use k256::ecdsa::SigningKey;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+use super::LocalSigner;
+
+type Aes128Ctr = ctr::Ctr128BE<aes::Aes128>;
+
+const CIPHER: &str = "aes-128-ctr";
+const DKLEN: usize = 32;
+
+/// scrypt cost used for newly written keystores (same as geth's "standard" setting).
+const SCRYPT_LOG_N: u8 = 18;
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
+
+/// Largest scrypt cost accepted when loading: keeps a hostile keystore from
+/// pinning the CPU or exhausting memory (geth's "standard" setting is n = 2^18, p = 1).
+const MAX_SCRYPT_N: u64 = 1 << 20;
+const MAX_SCRYPT_P: u32 = 16;
+/// Upper bound for the 128 * r * n bytes scrypt allocates.
+const MAX_SCRYPT_MEMORY: u64 = 1 << 30;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct KeystoreFile {
+    pub version: u32,
+    pub id: String,
+    /// Lowercase hex without 0x; optional in the spec, so never trusted on load.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    #[serde(alias = "Crypto")]
+    pub crypto: CryptoSection,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CryptoSection {
+    pub cipher: String,
+    pub cipherparams: CipherParams,
+    pub ciphertext: String,
+    pub kdf: String,
+    pub kdfparams: KdfParams,
+    pub mac: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CipherParams {
+    pub iv: String,
+}
+
+/// Parameters of either supported KDF; which fields are present depends on `kdf`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct KdfParams {
+    pub dklen: usize,
+    pub salt: String,
+    // scrypt
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub n: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub r: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub p: Option<u32>,
+    // pbkdf2
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub c: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prf: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum KeystoreError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    Hex(hex::FromHexError),
+    /// KDF, cipher or parameters not covered by the v3 spec.
+    Unsupported(String),
+    /// MAC check failed: wrong passphrase or corrupted file.
+    InvalidPassword,
+    InvalidKey(String),
+}
+
+impl fmt::Display for KeystoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeystoreError::Io(e) => write!(f, "Keystore I/O error: {}", e),
+            KeystoreError::Json(e) => write!(f, "Invalid keystore JSON: {}", e),
+            KeystoreError::Hex(e) => write!(f, "Invalid hex in keystore: {}", e),
+            KeystoreError::Unsupported(what) => write!(f, "Unsupported keystore: {}", what),
+            KeystoreError::InvalidPassword => write!(f, "Keystore MAC mismatch (wrong passphrase?)"),
+            KeystoreError::InvalidKey(reason) => write!(f, "Invalid private key: {}", reason),
+        }
+    }
+}
+
+impl Error for KeystoreError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            KeystoreError::Io(e) => Some(e),
+            KeystoreError::Json(e) => Some(e),
+            KeystoreError::Hex(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for KeystoreError {
+    fn from(e: std::io::Error) -> Self {
+        KeystoreError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for KeystoreError {
+    fn from(e: serde_json::Error) -> Self {
+        KeystoreError::Json(e)
+    }
+}
+
+impl From<hex::FromHexError> for KeystoreError {
+    fn from(e: hex::FromHexError) -> Self {
+        KeystoreError::Hex(e)
+    }
+}
+
+impl KeystoreFile {
+    /// Decrypts the keystore into a signer.
+    pub fn decrypt(&self, password: &str) -> Result<LocalSigner, KeystoreError> {
+        let crypto = &self.crypto;
+        if crypto.cipher != CIPHER {
+            return Err(KeystoreError::Unsupported(format!("cipher {}", crypto.cipher)));
+        }
+
+        let derived = derive_key(password, &crypto.kdf, &crypto.kdfparams)?;
+        let ciphertext = hex::decode(&crypto.ciphertext)?;
+
+        let mut mac_preimage = derived[16..32].to_vec();
+        mac_preimage.extend_from_slice(&ciphertext);
+        if keccak256(&mac_preimage).as_slice() != hex::decode(&crypto.mac)?.as_slice() {
+            return Err(KeystoreError::InvalidPassword);
+        }
+
+        let iv = hex::decode(&crypto.cipherparams.iv)?;
+        let mut key_bytes = ciphertext;
+        apply_cipher(&derived[..16], &iv, &mut key_bytes)?;
+
+        let key = SigningKey::from_slice(&key_bytes)
+            .map_err(|e| KeystoreError::InvalidKey(e.to_string()))?;
+        Ok(LocalSigner::new(key))
+    }
+
+    /// Encrypts a key with scrypt and a random salt/IV.
+    fn encrypt(key: &SigningKey, password: &str) -> Result<Self, KeystoreError> {
+        let mut rng = rand::thread_rng();
+        let mut salt = [0u8; 32];
+        let mut iv = [0u8; 16];
+        let mut id = [0u8; 16];
+        rng.fill_bytes(&mut salt);
+        rng.fill_bytes(&mut iv);
+        rng.fill_bytes(&mut id);
+
+        let kdfparams = KdfParams {
+            dklen: DKLEN,
+            salt: hex::encode(salt),
+            n: Some(1u64 << SCRYPT_LOG_N),
+            r: Some(SCRYPT_R),
+            p: Some(SCRYPT_P),
+            c: None,
+            prf: None,
+        };
+        let derived = derive_key(password, "scrypt", &kdfparams)?;
+
+        let mut ciphertext = key.to_bytes().to_vec();
+        apply_cipher(&derived[..16], &iv, &mut ciphertext)?;
+
+        let mut mac_preimage = derived[16..32].to_vec();
+        mac_preimage.extend_from_slice(&ciphertext);
+
+        let address = crate::chain::signing_key_to_address_evm(key);
+        Ok(Self {
+            version: 3,
+            id: uuid::Builder::from_random_bytes(id).into_uuid().to_string(),
+            address: Some(hex::encode(address)),
+            crypto: CryptoSection {
+                cipher: CIPHER.to_string(),
+                cipherparams: CipherParams { iv: hex::encode(iv) },
+                ciphertext: hex::encode(ciphertext),
+                kdf: "scrypt".to_string(),
+                kdfparams,
+                mac: hex::encode(keccak256(&mac_preimage)),
+            },
+        })
+    }
+}
+
+fn derive_key(password: &str, kdf: &str, params: &KdfParams) -> Result<Vec<u8>, KeystoreError> {
+    if params.dklen < 32 {
+        return Err(KeystoreError::Unsupported(format!("dklen {}", params.dklen)));
+    }
+    let salt = hex::decode(&params.salt)?;
+    let mut derived = vec![0u8; params.dklen];
+
+    match kdf {
+        "scrypt" => {
+            let (n, r, p) = match (params.n, params.r, params.p) {
+                (Some(n), Some(r), Some(p))
+                    if n.is_power_of_two()
+                        && (2..=MAX_SCRYPT_N).contains(&n)
+                        && (1..=MAX_SCRYPT_P).contains(&p)
+                        && r >= 1
+                        && (r as u64).saturating_mul(128).saturating_mul(n) <= MAX_SCRYPT_MEMORY =>
+                {
+                    (n, r, p)
+                }
+                (n, r, p) => {
+                    return Err(KeystoreError::Unsupported(format!(
+                        "scrypt parameters n={:?}, r={:?}, p={:?}",
+                        n, r, p
+                    )))
+                }
+            };
+            match scrypt::Params::new(n.trailing_zeros() as u8, r, p, params.dklen) {
+                Ok(scrypt_params) => scrypt::scrypt(password.as_bytes(), &salt, &scrypt_params, &mut derived)
+                    .map_err(|e| KeystoreError::Unsupported(format!("scrypt output: {}", e)))?,
+                // The scrypt crate also enforces RFC 7914's N < 2^(16r), which geth does not:
+                // the spec's own example (n = 2^18, r = 1) needs the local implementation
+                Err(_) => scrypt_unchecked(password.as_bytes(), &salt, n as usize, r as usize, p as usize, &mut derived),
+            }
+        }
+        "pbkdf2" => {
+            let c = params
+                .c
+                .ok_or_else(|| KeystoreError::Unsupported("pbkdf2 without c".to_string()))?;
+            match params.prf.as_deref() {
+                Some("hmac-sha256") | None => {}
+                Some(prf) => return Err(KeystoreError::Unsupported(format!("prf {}", prf))),
+            }
+            pbkdf2::pbkdf2_hmac::<sha2::Sha256>(password.as_bytes(), &salt, c, &mut derived);
+        }
+        other => return Err(KeystoreError::Unsupported(format!("kdf {}", other))),
+    }
+
+    Ok(derived)
+}
+
+/// scrypt with only the bounds checked in `derive_key`, matching geth's
+/// golang.org/x/crypto/scrypt.
+fn scrypt_unchecked(password: &[u8], salt: &[u8], n: usize, r: usize, p: usize, output: &mut [u8]) {
+    let block_len = 128 * r;
+    let mut blocks = vec![0u8; block_len * p];
+    pbkdf2::pbkdf2_hmac::<sha2::Sha256>(password, salt, 1, &mut blocks);
+
+    let mut x = vec![0u32; 32 * r];
+    let mut scratch = vec![0u32; 32 * r];
+    let mut v = vec![0u32; 32 * r * n];
+    for block in blocks.chunks_mut(block_len) {
+        for (word, bytes) in x.iter_mut().zip(block.chunks_exact(4)) {
+            *word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        }
+        // ROMix
+        for i in 0..n {
+            v[i * 32 * r..(i + 1) * 32 * r].copy_from_slice(&x);
+            block_mix(&mut x, &mut scratch, r);
+        }
+        for _ in 0..n {
+            let j = x[(2 * r - 1) * 16] as usize & (n - 1);
+            for (word, v_word) in x.iter_mut().zip(&v[j * 32 * r..(j + 1) * 32 * r]) {
+                *word ^= v_word;
+            }
+            block_mix(&mut x, &mut scratch, r);
+        }
+        for (bytes, word) in block.chunks_exact_mut(4).zip(&x) {
+            bytes.copy_from_slice(&word.to_le_bytes());
+        }
+    }
+
+    pbkdf2::pbkdf2_hmac::<sha2::Sha256>(password, &blocks, 1, output);
+}
+
+/// scrypt BlockMix over 2r 64-byte blocks, with even outputs first and odd ones after.
+fn block_mix(b: &mut [u32], scratch: &mut [u32], r: usize) {
+    let mut x = [0u32; 16];
+    x.copy_from_slice(&b[(2 * r - 1) * 16..]);
+    for i in 0..2 * r {
+        for (word, b_word) in x.iter_mut().zip(&b[i * 16..(i + 1) * 16]) {
+            *word ^= b_word;
+        }
+        salsa20_8(&mut x);
+        let out = (i / 2 + (i % 2) * r) * 16;
+        scratch[out..out + 16].copy_from_slice(&x);
+    }
+    b.copy_from_slice(scratch);
+}
+
+fn salsa20_8(b: &mut [u32; 16]) {
+    fn quarter_round(x: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+        x[b] ^= x[a].wrapping_add(x[d]).rotate_left(7);
+        x[c] ^= x[b].wrapping_add(x[a]).rotate_left(9);
+        x[d] ^= x[c].wrapping_add(x[b]).rotate_left(13);
+        x[a] ^= x[d].wrapping_add(x[c]).rotate_left(18);
+    }
+
+    let mut x = *b;
+    for _ in 0..4 {
+        quarter_round(&mut x, 0, 4, 8, 12);
+        quarter_round(&mut x, 5, 9, 13, 1);
+        quarter_round(&mut x, 10, 14, 2, 6);
+        quarter_round(&mut x, 15, 3, 7, 11);
+        quarter_round(&mut x, 0, 1, 2, 3);
+        quarter_round(&mut x, 5, 6, 7, 4);
+        quarter_round(&mut x, 10, 11, 8, 9);
+        quarter_round(&mut x, 15, 12, 13, 14);
+    }
+    for (word, x_word) in b.iter_mut().zip(x) {
+        *word = word.wrapping_add(x_word);
+    }
+}
+
+fn apply_cipher(key: &[u8], iv: &[u8], data: &mut [u8]) -> Result<(), KeystoreError> {
+    let mut cipher = Aes128Ctr::new_from_slices(key, iv)
+        .map_err(|_| KeystoreError::Unsupported("aes-128-ctr key/iv length".to_string()))?;
+    cipher.apply_keystream(data);
+    Ok(())
+}
+
+/// Parses a keystore file without decrypting it.
+pub fn read_keystore_file(path: &Path) -> Result<KeystoreFile, KeystoreError> {
+    Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+}
+
+/// Reads and decrypts a single keystore file.
+pub fn decrypt_keystore_file(path: &Path, password: &str) -> Result<LocalSigner, KeystoreError> {
+    read_keystore_file(path)?.decrypt(password)
+}
+
+/// Decrypts every `*.json` keystore in `dir` with the same passphrase.
+pub fn load_keystore_dir(dir: &Path, password: &str) -> Result<Vec<LocalSigner>, KeystoreError> {
+    let mut signers = Vec::new();
+    for path in keystore_paths(dir)? {
+        signers.push(decrypt_keystore_file(&path, password)?);
+    }
+    Ok(signers)
+}
+
+/// Keystore files in `dir`, sorted by file name.
+pub fn keystore_paths(dir: &Path) -> Result<Vec<PathBuf>, KeystoreError> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+/// Generates a new random key and writes it as an encrypted keystore into `dir`.
+pub fn create_keystore(dir: &Path, password: &str) -> Result<(Address, PathBuf), KeystoreError> {
+    let key = SigningKey::random(&mut rand::thread_rng());
+    write_keystore(dir, &key, password)
+}
+
+/// Encrypts an existing hex private key into a keystore in `dir`.
+pub fn import_private_key(dir: &Path, private_key: &str, password: &str) -> Result<(Address, PathBuf), KeystoreError> {
+    let key = crate::chain::parse_private_key_evm(private_key)
+        .map_err(|e| KeystoreError::InvalidKey(e.to_string()))?;
+    write_keystore(dir, &key, password)
+}
+
+fn write_keystore(dir: &Path, key: &SigningKey, password: &str) -> Result<(Address, PathBuf), KeystoreError> {
+    let keystore = KeystoreFile::encrypt(key, password)?;
+    let address = crate::chain::signing_key_to_address_evm(key);
+
+    fs::create_dir_all(dir)?;
+    let path = dir.join(format!("{}.json", hex::encode(address)));
+
+    // Owner-only permissions; fails if a keystore for this address already exists
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(&path)?;
+    file.write_all(serde_json::to_string_pretty(&keystore)?.as_bytes())?;
+
+    Ok((address, path))
+}
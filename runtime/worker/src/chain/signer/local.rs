@@ -0,0 +1,47 @@
+use std::error::Error;
+use std::fmt;
+
+use alloy_primitives::{Address, B256, U256};
+use k256::ecdsa::SigningKey;
+
+use super::{Signature, Signer, SignerError};
+
+/// In-memory secp256k1 signer. The key never leaves this struct.
+pub struct LocalSigner {
+    key: SigningKey,
+    address: Address,
+}
+
+impl LocalSigner {
+    pub fn new(key: SigningKey) -> Self {
+        let address = crate::chain::signing_key_to_address_evm(&key);
+        Self { key, address }
+    }
+
+    /// Parses a hex private key (with or without 0x prefix).
+    pub fn from_hex(private_key: &str) -> Result<Self, Box<dyn Error>> {
+        Ok(Self::new(crate::chain::parse_private_key_evm(private_key)?))
+    }
+}
+
+impl Signer for LocalSigner {
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    fn sign_hash(&self, hash: &B256) -> Result<Signature, SignerError> {
+        let (signature, recovery_id) = self.key.sign_prehash_recoverable(hash.as_slice())?;
+        Ok(Signature {
+            r: U256::from_be_slice(&signature.r().to_bytes()),
+            s: U256::from_be_slice(&signature.s().to_bytes()),
+            y_parity: recovery_id.is_y_odd(),
+        })
+    }
+}
+
+// Never print key material
+impl fmt::Debug for LocalSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LocalSigner").field("address", &self.address).finish()
+    }
+}
@@ -0,0 +1,171 @@
+// Signing abstraction for EVM accounts
+pub mod hd;
+pub mod keystore;
+pub mod local;
+
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+
+use alloy_primitives::{keccak256, Address, B256, U256};
+
+use crate::chain::tx::{SignedTransaction, TypedTransaction};
+
+pub use local::LocalSigner;
+
+/// secp256k1 signature in Ethereum's `(r, s, y_parity)` form.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Signature {
+    pub r: U256,
+    pub s: U256,
+    pub y_parity: bool,
+}
+
+impl Signature {
+    /// 65-byte `r || s || v` encoding with `v` in `{27, 28}`.
+    pub fn to_rsv_bytes(&self) -> [u8; 65] {
+        let mut out = [0u8; 65];
+        out[..32].copy_from_slice(&self.r.to_be_bytes::<32>());
+        out[32..64].copy_from_slice(&self.s.to_be_bytes::<32>());
+        out[64] = 27 + self.y_parity as u8;
+        out
+    }
+}
+
+#[derive(Debug)]
+pub enum SignerError {
+    Signing(k256::ecdsa::Error),
+}
+
+impl fmt::Display for SignerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignerError::Signing(e) => write!(f, "Signing failed: {}", e),
+        }
+    }
+}
+
+impl Error for SignerError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SignerError::Signing(e) => Some(e),
+        }
+    }
+}
+
+impl From<k256::ecdsa::Error> for SignerError {
+    fn from(e: k256::ecdsa::Error) -> Self {
+        SignerError::Signing(e)
+    }
+}
+
+/// EIP-712 domain. Only the fields that are `Some` take part in the separator.
+#[derive(Debug, Clone, Default)]
+pub struct Eip712Domain {
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub chain_id: Option<u64>,
+    pub verifying_contract: Option<Address>,
+    pub salt: Option<B256>,
+}
+
+impl Eip712Domain {
+    pub fn separator(&self) -> B256 {
+        let mut fields = Vec::new();
+        let mut encoded = Vec::new();
+
+        if let Some(name) = &self.name {
+            fields.push("string name");
+            encoded.extend_from_slice(keccak256(name.as_bytes()).as_slice());
+        }
+        if let Some(version) = &self.version {
+            fields.push("string version");
+            encoded.extend_from_slice(keccak256(version.as_bytes()).as_slice());
+        }
+        if let Some(chain_id) = self.chain_id {
+            fields.push("uint256 chainId");
+            encoded.extend_from_slice(&U256::from(chain_id).to_be_bytes::<32>());
+        }
+        if let Some(contract) = self.verifying_contract {
+            fields.push("address verifyingContract");
+            encoded.extend_from_slice(contract.into_word().as_slice());
+        }
+        if let Some(salt) = self.salt {
+            fields.push("bytes32 salt");
+            encoded.extend_from_slice(salt.as_slice());
+        }
+
+        let type_hash = keccak256(format!("EIP712Domain({})", fields.join(",")));
+        let mut preimage = type_hash.to_vec();
+        preimage.extend(encoded);
+        keccak256(preimage)
+    }
+
+    /// `keccak256(0x19 0x01 || domainSeparator || structHash)`
+    pub fn signing_hash(&self, struct_hash: &B256) -> B256 {
+        let mut preimage = vec![0x19, 0x01];
+        preimage.extend_from_slice(self.separator().as_slice());
+        preimage.extend_from_slice(struct_hash.as_slice());
+        keccak256(preimage)
+    }
+}
+
+/// Something that can sign on behalf of an EVM address.
+pub trait Signer: Send + Sync {
+    fn address(&self) -> Address;
+
+    /// Signs a 32-byte prehash, returning a low-s signature.
+    fn sign_hash(&self, hash: &B256) -> Result<Signature, SignerError>;
+
+    fn sign_transaction(&self, tx: &TypedTransaction) -> Result<SignedTransaction, SignerError> {
+        let signature = self.sign_hash(&tx.signature_hash())?;
+        let raw = tx.encode_signed(&signature);
+        Ok(SignedTransaction {
+            hash: keccak256(&raw),
+            raw: raw.into(),
+        })
+    }
+
+    /// Signs EIP-712 typed data given its domain and the `hashStruct` of the message.
+    fn sign_typed_data(&self, domain: &Eip712Domain, struct_hash: &B256) -> Result<Signature, SignerError> {
+        self.sign_hash(&domain.signing_hash(struct_hash))
+    }
+}
+
+/// A wallet entry configured for the worker.
+#[derive(Clone)]
+pub enum WalletAccount {
+    /// Account whose key is available for signing.
+    Signer(Arc<dyn Signer>),
+    /// Address-only entry: balances and history can be read, nothing can be signed.
+    WatchOnly(Address),
+}
+
+impl WalletAccount {
+    pub fn address(&self) -> Address {
+        match self {
+            WalletAccount::Signer(signer) => signer.address(),
+            WalletAccount::WatchOnly(address) => *address,
+        }
+    }
+
+    pub fn signer(&self) -> Option<Arc<dyn Signer>> {
+        match self {
+            WalletAccount::Signer(signer) => Some(signer.clone()),
+            WalletAccount::WatchOnly(_) => None,
+        }
+    }
+
+    pub fn is_watch_only(&self) -> bool {
+        matches!(self, WalletAccount::WatchOnly(_))
+    }
+}
+
+impl fmt::Debug for WalletAccount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WalletAccount::Signer(signer) => write!(f, "Signer({:#x})", signer.address()),
+            WalletAccount::WatchOnly(address) => write!(f, "WatchOnly({:#x})", address),
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use std::error::Error;
+use std::fmt;
+
+use alloy_primitives::{Address, Bytes, B256, U256};
+
+use crate::chain::rpc::{RpcError, TransactionReceipt};
+use crate::chain::signer::SignerError;
+
+/// Errors from signing, broadcasting or confirming a transaction.
+#[derive(Debug)]
+pub enum TxError {
+    /// The transaction request could not be turned into a signable transaction.
+    InvalidRequest(String),
+    Signer(SignerError),
+    Rpc(RpcError),
+    /// The transaction was mined but reverted.
+    Reverted {
+        hash: B256,
+        receipt: Box<TransactionReceipt>,
+    },
+    /// No receipt with enough confirmations before the deadline.
+    Timeout { hash: B256 },
+    /// Pre-flight simulation reverted; `data` is the raw revert payload.
+    SimulationReverted { reason: String, data: Bytes },
+    /// Pre-flight simulation paid out less than the required minimum.
+    OutputBelowMinimum { min_amount: U256, simulated: U256 },
+    /// The node cannot simulate the output check (no `eth_simulateV1`) and unchecked
+    /// output is not allowed for the chain.
+    OutputUnchecked(String),
+    /// A chain id in the request (`what`) is not the chain the transaction would be sent on.
+    ChainMismatch { what: String, expected: u64, actual: u64 },
+    /// The request's sender is not the signing account.
+    SenderMismatch { expected: Address, actual: Address },
+}
+
+impl fmt::Display for TxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TxError::InvalidRequest(reason) => write!(f, "Invalid transaction request: {}", reason),
+            TxError::Signer(e) => write!(f, "Failed to sign transaction: {}", e),
+            TxError::Rpc(e) => write!(f, "{}", e),
+            TxError::Reverted { hash, .. } => write!(f, "Transaction {:#x} reverted", hash),
+            TxError::Timeout { hash } => write!(f, "Timed out waiting for transaction {:#x}", hash),
+            TxError::SimulationReverted { reason, .. } => write!(f, "Simulation reverted: {}", reason),
+            TxError::OutputBelowMinimum { min_amount, simulated } => write!(
+                f,
+                "Simulated output {} is below the minimum {}",
+                simulated, min_amount
+            ),
+            TxError::OutputUnchecked(reason) => write!(f, "Output could not be verified: {}", reason),
+            TxError::ChainMismatch { what, expected, actual } => {
+                write!(f, "{} is chain {} but the transaction is for chain {}", what, actual, expected)
+            }
+            TxError::SenderMismatch { expected, actual } => {
+                write!(f, "Request is from {:#x} but the signer is {:#x}", actual, expected)
+            }
+        }
+    }
+}
+
+impl Error for TxError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TxError::Signer(e) => Some(e),
+            TxError::Rpc(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<RpcError> for TxError {
+    fn from(e: RpcError) -> Self {
+        TxError::Rpc(e)
+    }
+}
+
+impl From<SignerError> for TxError {
+    fn from(e: SignerError) -> Self {
+        TxError::Signer(e)
+    }
+}
@@ -0,0 +1,170 @@
+// Transaction encoding, signing and broadcast
+pub mod error;
+pub mod sender;
+pub mod simulate;
+
+use alloy_primitives::{keccak256, Address, Bytes, B256, U256, U64};
+use alloy_rlp::{Encodable, Header, EMPTY_LIST_CODE, EMPTY_STRING_CODE};
+
+use crate::chain::rpc::CallRequest;
+use crate::chain::signer::Signature;
+
+pub use error::TxError;
+pub use sender::{recheck_receipt, send_transaction, wait_for_receipt, SendOptions};
+pub use simulate::{decode_revert_reason, simulate, OutputCheck, Simulation};
+
+/// EIP-2718 type byte of a dynamic-fee transaction.
+const EIP1559_TX_TYPE: u8 = 0x02;
+
+/// Fee fields of a transaction: either a single legacy gas price or EIP-1559 caps.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TxFees {
+    Legacy {
+        gas_price: U256,
+    },
+    Eip1559 {
+        max_fee_per_gas: U256,
+        max_priority_fee_per_gas: U256,
+    },
+}
+
+/// An unsigned transaction ready to be signed for a specific chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypedTransaction {
+    pub chain_id: u64,
+    pub nonce: u64,
+    pub gas_limit: u64,
+    pub fees: TxFees,
+    /// `None` for contract creation.
+    pub to: Option<Address>,
+    pub value: U256,
+    pub data: Bytes,
+}
+
+/// A signed transaction: the raw EIP-2718 envelope and its hash.
+#[derive(Debug, Clone)]
+pub struct SignedTransaction {
+    pub hash: B256,
+    pub raw: Bytes,
+}
+
+impl TypedTransaction {
+    /// `eth_call` / `eth_estimateGas` object for this transaction sent by `from`.
+    pub fn to_call_request(&self, from: Address) -> CallRequest {
+        let mut call = CallRequest {
+            from: Some(from),
+            to: self.to,
+            gas: Some(U64::from(self.gas_limit)),
+            value: Some(self.value),
+            data: Some(self.data.clone()),
+            ..Default::default()
+        };
+        match &self.fees {
+            TxFees::Legacy { gas_price } => call.gas_price = Some(*gas_price),
+            TxFees::Eip1559 {
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+            } => {
+                call.max_fee_per_gas = Some(*max_fee_per_gas);
+                call.max_priority_fee_per_gas = Some(*max_priority_fee_per_gas);
+            }
+        }
+        call
+    }
+
+    /// Hash that has to be signed (EIP-155 for legacy, EIP-1559 otherwise).
+    pub fn signature_hash(&self) -> B256 {
+        match &self.fees {
+            TxFees::Legacy { .. } => {
+                let mut payload = Vec::new();
+                self.encode_legacy_fields(&mut payload);
+                self.chain_id.encode(&mut payload);
+                0u8.encode(&mut payload);
+                0u8.encode(&mut payload);
+                keccak256(wrap_list(payload))
+            }
+            TxFees::Eip1559 { .. } => {
+                let mut payload = Vec::new();
+                self.encode_eip1559_fields(&mut payload);
+                let mut out = vec![EIP1559_TX_TYPE];
+                out.extend(wrap_list(payload));
+                keccak256(out)
+            }
+        }
+    }
+
+    /// RLP-encodes the transaction together with `signature` into a raw envelope.
+    pub fn encode_signed(&self, signature: &Signature) -> Vec<u8> {
+        match &self.fees {
+            TxFees::Legacy { .. } => {
+                let mut payload = Vec::new();
+                self.encode_legacy_fields(&mut payload);
+                let v = self.chain_id * 2 + 35 + signature.y_parity as u64;
+                v.encode(&mut payload);
+                signature.r.encode(&mut payload);
+                signature.s.encode(&mut payload);
+                wrap_list(payload)
+            }
+            TxFees::Eip1559 { .. } => {
+                let mut payload = Vec::new();
+                self.encode_eip1559_fields(&mut payload);
+                signature.y_parity.encode(&mut payload);
+                signature.r.encode(&mut payload);
+                signature.s.encode(&mut payload);
+                let mut out = vec![EIP1559_TX_TYPE];
+                out.extend(wrap_list(payload));
+                out
+            }
+        }
+    }
+
+    fn encode_legacy_fields(&self, out: &mut Vec<u8>) {
+        let gas_price = match &self.fees {
+            TxFees::Legacy { gas_price } => *gas_price,
+            TxFees::Eip1559 { max_fee_per_gas, .. } => *max_fee_per_gas,
+        };
+        self.nonce.encode(out);
+        gas_price.encode(out);
+        self.gas_limit.encode(out);
+        self.encode_to(out);
+        self.value.encode(out);
+        self.data.encode(out);
+    }
+
+    fn encode_eip1559_fields(&self, out: &mut Vec<u8>) {
+        let (max_fee_per_gas, max_priority_fee_per_gas) = match &self.fees {
+            TxFees::Eip1559 { max_fee_per_gas, max_priority_fee_per_gas } => {
+                (*max_fee_per_gas, *max_priority_fee_per_gas)
+            }
+            TxFees::Legacy { gas_price } => (*gas_price, *gas_price),
+        };
+        self.chain_id.encode(out);
+        self.nonce.encode(out);
+        max_priority_fee_per_gas.encode(out);
+        max_fee_per_gas.encode(out);
+        self.gas_limit.encode(out);
+        self.encode_to(out);
+        self.value.encode(out);
+        self.data.encode(out);
+        // Empty access list
+        out.push(EMPTY_LIST_CODE);
+    }
+
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        match &self.to {
+            Some(to) => to.encode(out),
+            None => out.push(EMPTY_STRING_CODE),
+        }
+    }
+}
+
+fn wrap_list(payload: Vec<u8>) -> Vec<u8> {
+    let mut out = Vec::with_capacity(payload.len() + 9);
+    Header {
+        list: true,
+        payload_length: payload.len(),
+    }
+    .encode(&mut out);
+    out.extend(payload);
+    out
+}
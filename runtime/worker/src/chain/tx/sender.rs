@@ -0,0 +1,300 @@
+use std::time::{Duration, Instant};
+
+use alloy_primitives::B256;
+
+use crate::chain::rpc::{RpcError, TransactionReceipt};
+use crate::chain::signer::Signer;
+use crate::chain::Chain;
+
+use super::{TxError, TypedTransaction};
+
+/// How long to wait for a broadcast transaction and how deep it must be buried.
+#[derive(Debug, Clone)]
+pub struct SendOptions {
+    /// Blocks (including the inclusion block) required before returning; `0` is treated as `1`.
+    /// `None` uses the chain's configured confirmation depth.
+    pub confirmations: Option<u64>,
+    pub poll_interval: Duration,
+    pub timeout: Duration,
+    /// Rebroadcast with bumped fees when the transaction is still pending after this long.
+    /// `None` waits for the original transaction only.
+    pub replace_after: Option<Duration>,
+    /// Fee increase per replacement; values below 10% are raised to 10%.
+    pub fee_bump_percent: u64,
+}
+
+impl Default for SendOptions {
+    fn default() -> Self {
+        Self {
+            confirmations: None,
+            poll_interval: Duration::from_secs(2),
+            timeout: Duration::from_secs(120),
+            replace_after: None,
+            fee_bump_percent: 12,
+        }
+    }
+}
+
+/// Signs `tx`, broadcasts it through the chain's RPC client and waits for the receipt.
+///
+/// `tx.nonce` is expected to come from `chain.nonces().reserve()`; the nonce is
+/// released if the transaction never reaches the node and confirmed once it is mined.
+/// With `options.replace_after` set, a pending transaction is replaced by one with
+/// fees bumped through `chain.fee_oracle()` until one of them is mined or the
+/// timeout passes. On HyperEVM the signer is first switched to the block type
+/// that fits `tx.gas_limit`, and big block sends wait accordingly longer.
+pub async fn send_transaction(
+    chain: &dyn Chain,
+    signer: &dyn Signer,
+    tx: &TypedTransaction,
+    options: &SendOptions,
+) -> Result<TransactionReceipt, TxError> {
+    let nonces = chain.nonces();
+    let address = signer.address();
+
+    if let Err(e) = check_target(chain, signer, tx) {
+        nonces.release(address, tx.nonce);
+        return Err(e);
+    }
+
+    let (tx, options) = match chain.dual_blocks() {
+        Some(dual_blocks) => {
+            let routed = async {
+                let block_type = dual_blocks.route(chain, signer, tx.gas_limit).await?;
+                let fees = dual_blocks.fit_fees(chain.rpc(), block_type, &tx.fees).await?;
+                Ok::<_, TxError>((TypedTransaction { fees, ..tx.clone() }, dual_blocks.send_options(block_type, options)))
+            }
+            .await;
+            match routed {
+                Ok(routed) => routed,
+                Err(e) => {
+                    nonces.release(address, tx.nonce);
+                    return Err(e);
+                }
+            }
+        }
+        None => (tx.clone(), options.clone()),
+    };
+    let (tx, options) = (&tx, &options);
+
+    let signed = match signer.sign_transaction(tx) {
+        Ok(signed) => signed,
+        Err(e) => {
+            nonces.release(address, tx.nonce);
+            return Err(e.into());
+        }
+    };
+    let hash = match chain.rpc().send_raw_transaction(&signed.raw).await {
+        Ok(hash) => hash,
+        // The raw send fails over like any request, so an endpoint that accepted the
+        // transaction and then timed out leaves the next one answering "already known"
+        // or "nonce too low" for the very same transaction
+        Err(e) if already_submitted(chain, &e, signed.hash).await => {
+            println!("Transaction {:#x} was already submitted ({})", signed.hash, e);
+            signed.hash
+        }
+        Err(e) => {
+            nonces.release(address, tx.nonce);
+            if e.is_nonce_too_low() {
+                // Used by a transaction sent elsewhere: move past it instead of reusing it
+                if let Err(resync_error) = nonces.resync(chain, address).await {
+                    println!("Nonce resync for {:#x} failed: {}", address, resync_error);
+                }
+            }
+            return Err(e.into());
+        }
+    };
+    nonces.mark_broadcast(address, tx.nonce, hash);
+    println!("Transaction broadcast: {:#x}", hash);
+
+    let result = match options.replace_after {
+        Some(replace_after) => replace_until_mined(chain, signer, tx, hash, replace_after, options).await,
+        None => wait_for_receipt(chain, hash, options).await,
+    };
+    // A reverted transaction still consumes its nonce; a timed out one stays in flight
+    if matches!(result, Ok(_) | Err(TxError::Reverted { .. })) {
+        nonces.confirm(address, tx.nonce);
+    }
+    // The node may have dropped it: cancel it then, so that later nonces can be mined
+    if matches!(result, Err(TxError::Timeout { .. })) {
+        if let Err(e) = nonces.repair(chain, signer, options.timeout).await {
+            println!("Nonce repair for {:#x} failed: {}", address, e);
+        }
+    }
+    result
+}
+
+/// Whether a rejected broadcast means the node already has the transaction `hash`.
+/// "nonce too low" alone could also be another transaction with the same nonce.
+async fn already_submitted(chain: &dyn Chain, error: &RpcError, hash: B256) -> bool {
+    if error.is_known_transaction() {
+        return true;
+    }
+    error.is_nonce_too_low() && matches!(chain.rpc().get_transaction_by_hash(hash).await, Ok(Some(_)))
+}
+
+/// Refuses to sign for another chain or with a key the chain does not hold.
+fn check_target(chain: &dyn Chain, signer: &dyn Signer, tx: &TypedTransaction) -> Result<(), TxError> {
+    let chain_id = chain.config().chain_id;
+    if tx.chain_id != chain_id {
+        return Err(TxError::ChainMismatch {
+            what: format!("{} (the sending chain)", chain.config().name),
+            expected: tx.chain_id,
+            actual: chain_id,
+        });
+    }
+    if chain.signer(signer.address()).is_none() {
+        return Err(TxError::InvalidRequest(format!(
+            "{:#x} is not a signing account of {}",
+            signer.address(),
+            chain.config().name
+        )));
+    }
+    Ok(())
+}
+
+/// Replace-by-fee loop: waits `replace_after` for any broadcast version of the
+/// transaction, then rebroadcasts the same nonce with bumped fees.
+async fn replace_until_mined(
+    chain: &dyn Chain,
+    signer: &dyn Signer,
+    tx: &TypedTransaction,
+    first_hash: B256,
+    replace_after: Duration,
+    options: &SendOptions,
+) -> Result<TransactionReceipt, TxError> {
+    let rpc = chain.rpc();
+    let deadline = Instant::now() + options.timeout;
+    let mut current = tx.clone();
+    let mut hashes = vec![first_hash];
+
+    loop {
+        let wait_until = deadline.min(Instant::now() + replace_after);
+        if let Some(receipt) = poll_receipts(chain, &hashes, options, wait_until).await {
+            return check_status(receipt);
+        }
+        let last_hash = *hashes.last().unwrap_or(&first_hash);
+        if Instant::now() >= deadline {
+            return Err(TxError::Timeout { hash: last_hash });
+        }
+
+        let fees = match chain.fee_oracle().bump(&current.fees, options.fee_bump_percent) {
+            Some(fees) => fees,
+            None => {
+                println!("Fee cap reached, waiting for {:#x} without replacing", last_hash);
+                continue;
+            }
+        };
+        let replacement = TypedTransaction {
+            fees,
+            ..current.clone()
+        };
+        let signed = signer.sign_transaction(&replacement)?;
+        match rpc.send_raw_transaction(&signed.raw).await {
+            Ok(hash) => {
+                println!("Replaced {:#x} with {:#x} ({:?})", last_hash, hash, replacement.fees);
+                chain.nonces().mark_broadcast(signer.address(), replacement.nonce, hash);
+                hashes.push(hash);
+                current = replacement;
+            }
+            // e.g. "nonce too low" when an earlier version was just mined; keep polling
+            Err(e) => println!("Replacement of {:#x} rejected: {}", last_hash, e),
+        }
+    }
+}
+
+/// Polls for the receipt of `hash` until it has the required confirmations on
+/// the canonical chain. Transient RPC failures are retried until the deadline.
+pub async fn wait_for_receipt(
+    chain: &dyn Chain,
+    hash: B256,
+    options: &SendOptions,
+) -> Result<TransactionReceipt, TxError> {
+    let deadline = Instant::now() + options.timeout;
+    match poll_receipts(chain, &[hash], options, deadline).await {
+        Some(receipt) => check_status(receipt),
+        None => Err(TxError::Timeout { hash }),
+    }
+}
+
+fn check_status(receipt: TransactionReceipt) -> Result<TransactionReceipt, TxError> {
+    if receipt.is_success() {
+        Ok(receipt)
+    } else {
+        Err(TxError::Reverted {
+            hash: receipt.transaction_hash,
+            receipt: Box::new(receipt),
+        })
+    }
+}
+
+/// Re-checks a receipt returned earlier after a `BlockEvent::Reorg` orphaned
+/// its block: waits for the transaction to be mined again on the new fork.
+/// Fails with `Timeout` if it was dropped and never re-included.
+pub async fn recheck_receipt(
+    chain: &dyn Chain,
+    receipt: &TransactionReceipt,
+    options: &SendOptions,
+) -> Result<TransactionReceipt, TxError> {
+    println!(
+        "[{}] Block of {:#x} was orphaned, waiting for re-inclusion",
+        chain.config().name,
+        receipt.transaction_hash
+    );
+    wait_for_receipt(chain, receipt.transaction_hash, options).await
+}
+
+/// Polls until one of `hashes` is confirmed or `until` passes.
+async fn poll_receipts(
+    chain: &dyn Chain,
+    hashes: &[B256],
+    options: &SendOptions,
+    until: Instant,
+) -> Option<TransactionReceipt> {
+    let confirmations = options.confirmations.unwrap_or(chain.blocks().confirmations()).max(1);
+
+    loop {
+        for &hash in hashes {
+            match confirmed_receipt(chain, hash, confirmations).await {
+                Ok(Some(receipt)) => return Some(receipt),
+                Ok(None) => {}
+                Err(e) => println!("Receipt poll for {:#x} failed: {}", hash, e),
+            }
+        }
+
+        if Instant::now() >= until {
+            return None;
+        }
+        tokio::time::sleep(options.poll_interval).await;
+    }
+}
+
+/// The receipt of `hash` if its block is canonical and `confirmations` deep.
+async fn confirmed_receipt(
+    chain: &dyn Chain,
+    hash: B256,
+    confirmations: u64,
+) -> Result<Option<TransactionReceipt>, TxError> {
+    let rpc = chain.rpc();
+    let receipt = match rpc.get_transaction_receipt(hash).await? {
+        Some(receipt) => receipt,
+        None => return Ok(None),
+    };
+    let (mined_at, block_hash) = match (receipt.block_number, receipt.block_hash) {
+        (Some(number), Some(block_hash)) => (number.to::<u64>(), block_hash),
+        _ => return Ok(None),
+    };
+
+    let blocks = chain.blocks();
+    blocks.poll(rpc).await?;
+    // A lagging endpoint can still serve a receipt from an orphaned block
+    if blocks.is_canonical(mined_at, block_hash) == Some(false) {
+        println!("Receipt of {:#x} is on orphaned block {}, waiting for re-inclusion", hash, mined_at);
+        return Ok(None);
+    }
+    if blocks.depth(mined_at) >= confirmations {
+        Ok(Some(receipt))
+    } else {
+        Ok(None)
+    }
+}
@@ -0,0 +1,266 @@
+// Pre-flight simulation of transactions before they are signed
+use std::collections::HashMap;
+
+use alloy_primitives::{address, Address, Bytes, U256};
+
+use crate::chain::rpc::{
+    AccountOverride, BlockTag, CallRequest, RpcClient, RpcError, SimulateBlock, SimulatePayload, SimulatedCall,
+};
+
+use super::TxError;
+
+/// `Error(string)`
+const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+/// `Panic(uint256)`
+const PANIC_SELECTOR: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
+/// `balanceOf(address)`
+const BALANCE_OF_SELECTOR: [u8; 4] = [0x70, 0xa0, 0x82, 0x31];
+
+/// Placeholder address used by Li.Fi and most aggregators for the native token.
+const NATIVE_TOKEN_PLACEHOLDER: Address = address!("eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee");
+
+/// Address where the native balance reader is injected through a state override.
+const BALANCE_READER: Address = address!("000000000000000000000000000000000000ba1a");
+/// `PUSH1 0 CALLDATALOAD BALANCE PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN`:
+/// returns the native balance of the address passed as the first argument.
+const BALANCE_READER_CODE: [u8; 12] = [
+    0x60, 0x00, 0x35, 0x31, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3,
+];
+
+/// Minimum amount of `token` that `recipient` must receive from the transaction.
+#[derive(Debug, Clone)]
+pub struct OutputCheck {
+    /// ERC-20 address; the zero address or `0xeeee…` means the native token.
+    pub token: Address,
+    pub recipient: Address,
+    pub min_amount: U256,
+    /// Skip the check instead of failing when the node does not support `eth_simulateV1`.
+    pub allow_unchecked: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Simulation {
+    pub gas_estimate: u64,
+    pub return_data: Bytes,
+    /// Amount received by the output check's recipient; `None` when no check was
+    /// requested or it was skipped under `OutputCheck::allow_unchecked`.
+    pub output_delta: Option<U256>,
+}
+
+/// Runs `eth_estimateGas` and `eth_call` for `call` on top of `block` and, with
+/// `output` set, measures the recipient's balance change with `eth_simulateV1`.
+/// Fails if the call reverts, needs more gas than `call.gas`, pays out less
+/// than `output.min_amount`, or the output cannot be measured.
+pub async fn simulate(
+    rpc: &RpcClient,
+    call: &CallRequest,
+    block: BlockTag,
+    output: Option<&OutputCheck>,
+) -> Result<Simulation, TxError> {
+    // Estimated without the gas limit: nodes cap the search at `gas` and would
+    // report a too-low limit as a failure instead of returning the real need
+    let estimate_call = CallRequest {
+        gas: None,
+        ..call.clone()
+    };
+    let gas_estimate = rpc.estimate_gas(&estimate_call, block).await.map_err(revert_error)?;
+    if let Some(gas_limit) = call.gas {
+        if gas_estimate > gas_limit.to::<u64>() {
+            return Err(TxError::InvalidRequest(format!(
+                "gas limit {} is below the estimate {}",
+                gas_limit, gas_estimate
+            )));
+        }
+    }
+
+    let return_data = rpc.call(call, block).await.map_err(revert_error)?;
+
+    let output_delta = match output {
+        Some(check) => {
+            let delta = simulate_output_delta(rpc, call, block, check).await?;
+            if let Some(delta) = delta {
+                if delta < check.min_amount {
+                    return Err(TxError::OutputBelowMinimum {
+                        min_amount: check.min_amount,
+                        simulated: delta,
+                    });
+                }
+            }
+            delta
+        }
+        None => None,
+    };
+
+    Ok(Simulation {
+        gas_estimate,
+        return_data,
+        output_delta,
+    })
+}
+
+/// Executes balance read, `call`, balance read in one simulated block.
+async fn simulate_output_delta(
+    rpc: &RpcClient,
+    call: &CallRequest,
+    block: BlockTag,
+    check: &OutputCheck,
+) -> Result<Option<U256>, TxError> {
+    let is_native = check.token == Address::ZERO || check.token == NATIVE_TOKEN_PLACEHOLDER;
+    let balance_call = if is_native {
+        CallRequest {
+            to: Some(BALANCE_READER),
+            data: Some(check.recipient.into_word().to_vec().into()),
+            ..Default::default()
+        }
+    } else {
+        let mut data = BALANCE_OF_SELECTOR.to_vec();
+        data.extend_from_slice(check.recipient.into_word().as_slice());
+        CallRequest {
+            to: Some(check.token),
+            data: Some(data.into()),
+            ..Default::default()
+        }
+    };
+
+    let mut state_overrides = HashMap::new();
+    if is_native {
+        state_overrides.insert(
+            BALANCE_READER,
+            AccountOverride {
+                code: Some(Bytes::from_static(&BALANCE_READER_CODE)),
+                ..Default::default()
+            },
+        );
+    }
+
+    // Fee fields are dropped so that the sender's balance only moves by `value`
+    let tx_call = CallRequest {
+        gas_price: None,
+        max_fee_per_gas: None,
+        max_priority_fee_per_gas: None,
+        ..call.clone()
+    };
+    let payload = SimulatePayload {
+        block_state_calls: vec![SimulateBlock {
+            state_overrides,
+            calls: vec![balance_call.clone(), tx_call, balance_call],
+        }],
+        trace_transfers: false,
+        validation: false,
+    };
+
+    let blocks = match rpc.simulate(&payload, block).await {
+        Ok(blocks) => blocks,
+        // -32601: method not found. eth_call does not keep state, so there is no
+        // other way to measure the balance change before sending
+        Err(RpcError::Rpc { code: -32601, .. }) if check.allow_unchecked => {
+            println!("eth_simulateV1 is not supported by this node; skipping output check");
+            return Ok(None);
+        }
+        Err(RpcError::Rpc { code: -32601, .. }) => {
+            return Err(TxError::OutputUnchecked(
+                "eth_simulateV1 is not supported by this node (set allow_unchecked_output to send anyway)".to_string(),
+            ));
+        }
+        Err(e) => return Err(e.into()),
+    };
+    let calls = blocks
+        .into_iter()
+        .next()
+        .map(|block| block.calls)
+        .unwrap_or_default();
+    let [before, tx, after] = calls.as_slice() else {
+        return Err(RpcError::Missing("eth_simulateV1 call results".to_string()).into());
+    };
+
+    if !tx.is_success() {
+        let data = tx
+            .error
+            .as_ref()
+            .and_then(|error| error.data.clone())
+            .unwrap_or_else(|| tx.return_data.clone());
+        return Err(TxError::SimulationReverted {
+            reason: decode_revert_reason(&data),
+            data,
+        });
+    }
+    let before = read_balance(before)?;
+    let after = read_balance(after)?;
+    Ok(Some(after.saturating_sub(before)))
+}
+
+fn read_balance(call: &SimulatedCall) -> Result<U256, TxError> {
+    if !call.is_success() || call.return_data.len() < 32 {
+        return Err(RpcError::Missing("balance in simulation result".to_string()).into());
+    }
+    Ok(U256::from_be_slice(&call.return_data[..32]))
+}
+
+/// Maps a reverted `eth_call` / `eth_estimateGas` to `SimulationReverted`;
+/// other RPC failures are passed through.
+fn revert_error(e: RpcError) -> TxError {
+    if !e.is_revert() {
+        return TxError::Rpc(e);
+    }
+    match e {
+        RpcError::Rpc { message, data, .. } => {
+            let data = data
+                .as_ref()
+                .and_then(|value| value.as_str())
+                .and_then(|hex| hex.parse::<Bytes>().ok())
+                .unwrap_or_default();
+            let reason = if data.is_empty() {
+                message
+            } else {
+                decode_revert_reason(&data)
+            };
+            TxError::SimulationReverted { reason, data }
+        }
+        other => TxError::Rpc(other),
+    }
+}
+
+/// Human-readable revert reason: `Error(string)`, `Panic(uint256)` or the raw custom error.
+pub fn decode_revert_reason(data: &[u8]) -> String {
+    if data.is_empty() {
+        return "reverted without reason".to_string();
+    }
+    if data.len() < 4 {
+        return format!("malformed revert data 0x{}", hex::encode(data));
+    }
+
+    let (selector, args) = data.split_at(4);
+    if selector == ERROR_SELECTOR {
+        if let Some(message) = decode_abi_string(args) {
+            return message;
+        }
+    }
+    if selector == PANIC_SELECTOR && args.len() >= 32 {
+        let code = U256::from_be_slice(&args[..32]);
+        return format!("panic 0x{:x} ({})", code, panic_description(code));
+    }
+    format!("custom error 0x{}", hex::encode(data))
+}
+
+fn decode_abi_string(args: &[u8]) -> Option<String> {
+    let offset: usize = U256::from_be_slice(args.get(..32)?).try_into().ok()?;
+    let length: usize = U256::from_be_slice(args.get(offset..offset + 32)?).try_into().ok()?;
+    let bytes = args.get(offset + 32..offset + 32 + length)?;
+    Some(String::from_utf8_lossy(bytes).into_owned())
+}
+
+/// Solidity panic codes (https://docs.soliditylang.org/en/latest/control-structures.html#panic-via-assert-and-error-via-require)
+fn panic_description(code: U256) -> &'static str {
+    match code.try_into().unwrap_or(u64::MAX) {
+        0x01 => "assertion failed",
+        0x11 => "arithmetic overflow or underflow",
+        0x12 => "division by zero",
+        0x21 => "invalid enum value",
+        0x22 => "invalid storage byte array",
+        0x31 => "pop on empty array",
+        0x32 => "array index out of bounds",
+        0x41 => "out of memory",
+        0x51 => "call to zero-initialized function",
+        _ => "unknown panic",
+    }
+}
@@ -0,0 +1,230 @@
+// eth_subscribe over WebSocket, exposed as async streams
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use alloy_primitives::B256;
+use futures::{SinkExt, Stream, StreamExt};
+use serde::de::DeserializeOwned;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::chain::rpc::{Block, Log, LogFilter};
+
+/// Buffered notifications per subscription before the reader waits for the consumer.
+const CHANNEL_CAPACITY: usize = 256;
+const MIN_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+/// A ping is sent after this much silence; the connection is dropped after twice as long.
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Opens `eth_subscribe` subscriptions against a chain's WebSocket endpoints.
+///
+/// Every subscription runs on its own connection in a background task that
+/// reconnects (rotating through the configured URLs) and resubscribes after a
+/// disconnect. Notifications missed while disconnected are not replayed.
+/// Must be used from within a tokio runtime.
+#[derive(Debug, Clone)]
+pub struct SubscriptionClient {
+    urls: Vec<String>,
+}
+
+impl SubscriptionClient {
+    pub fn new(urls: Vec<String>) -> Self {
+        Self { urls }
+    }
+
+    pub fn urls(&self) -> &[String] {
+        &self.urls
+    }
+
+    /// Headers of new blocks as they are imported (reorged blocks included).
+    pub fn new_heads(&self) -> Subscription<Block> {
+        self.subscribe(serde_json::json!(["newHeads"]))
+    }
+
+    /// Logs matching `filter` from newly imported blocks.
+    pub fn logs(&self, filter: LogFilter) -> Subscription<Log> {
+        self.subscribe(serde_json::json!(["logs", filter]))
+    }
+
+    /// Hashes of transactions entering the node's mempool. Many public
+    /// endpoints do not offer this; the stream then ends right away.
+    pub fn pending_transactions(&self) -> Subscription<B256> {
+        self.subscribe(serde_json::json!(["newPendingTransactions"]))
+    }
+
+    fn subscribe<T>(&self, params: serde_json::Value) -> Subscription<T>
+    where
+        T: DeserializeOwned + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel(CHANNEL_CAPACITY);
+        let task = tokio::spawn(run_subscription(self.urls.clone(), params, sender));
+        Subscription { receiver, task }
+    }
+}
+
+/// Stream of subscription notifications. Dropping it closes the connection.
+pub struct Subscription<T> {
+    receiver: mpsc::Receiver<T>,
+    task: JoinHandle<()>,
+}
+
+impl<T> Stream for Subscription<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+impl<T> Drop for Subscription<T> {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+enum SessionEnd {
+    /// The `Subscription` was dropped.
+    ReceiverDropped,
+    /// The node answered `eth_subscribe` with an error; retrying will not help.
+    Rejected(String),
+    /// Connection failed or was lost; `subscribed` tells whether it got as far as a subscription id.
+    Lost { subscribed: bool, reason: String },
+}
+
+async fn run_subscription<T>(urls: Vec<String>, params: serde_json::Value, sender: mpsc::Sender<T>)
+where
+    T: DeserializeOwned + Send + 'static,
+{
+    if urls.is_empty() {
+        return;
+    }
+
+    let mut attempt = 0usize;
+    let mut backoff = MIN_BACKOFF;
+    loop {
+        let url = &urls[attempt % urls.len()];
+        match run_session(url, &params, &sender).await {
+            SessionEnd::ReceiverDropped => return,
+            SessionEnd::Rejected(message) => {
+                println!("Subscription {} rejected by {}: {}", params, url, message);
+                return;
+            }
+            SessionEnd::Lost { subscribed, reason } => {
+                println!("Subscription {} on {} lost: {}", params, url, reason);
+                if subscribed {
+                    backoff = MIN_BACKOFF;
+                }
+            }
+        }
+        if sender.is_closed() {
+            return;
+        }
+
+        attempt += 1;
+        tokio::time::sleep(backoff).await;
+        backoff = (backoff * 2).min(MAX_BACKOFF);
+    }
+}
+
+/// One connection: subscribe, then forward notifications until something breaks.
+async fn run_session<T>(url: &str, params: &serde_json::Value, sender: &mpsc::Sender<T>) -> SessionEnd
+where
+    T: DeserializeOwned,
+{
+    let lost = |subscribed: bool, reason: String| SessionEnd::Lost { subscribed, reason };
+
+    let (socket, _) = match tokio_tungstenite::connect_async(url).await {
+        Ok(connection) => connection,
+        Err(e) => return lost(false, e.to_string()),
+    };
+    let (mut write, mut read) = socket.split();
+
+    let request = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "eth_subscribe",
+        "params": params,
+    });
+    if let Err(e) = write.send(Message::Text(request.to_string())).await {
+        return lost(false, e.to_string());
+    }
+
+    let mut subscription_id: Option<String> = None;
+    let mut last_message = Instant::now();
+    let mut keepalive = tokio::time::interval(KEEPALIVE_INTERVAL);
+    keepalive.tick().await;
+
+    loop {
+        let message = tokio::select! {
+            message = read.next() => message,
+            _ = keepalive.tick() => {
+                if last_message.elapsed() >= KEEPALIVE_INTERVAL * 2 {
+                    return lost(subscription_id.is_some(), "no messages or pongs".to_string());
+                }
+                if last_message.elapsed() >= KEEPALIVE_INTERVAL {
+                    if let Err(e) = write.send(Message::Ping(Vec::new())).await {
+                        return lost(subscription_id.is_some(), e.to_string());
+                    }
+                }
+                continue;
+            }
+            _ = sender.closed() => return SessionEnd::ReceiverDropped,
+        };
+        last_message = Instant::now();
+
+        let text = match message {
+            Some(Ok(Message::Text(text))) => text,
+            Some(Ok(Message::Ping(payload))) => {
+                let _ = write.send(Message::Pong(payload)).await;
+                continue;
+            }
+            Some(Ok(Message::Close(frame))) => {
+                return lost(subscription_id.is_some(), format!("closed by server ({:?})", frame));
+            }
+            Some(Ok(_)) => continue,
+            Some(Err(e)) => return lost(subscription_id.is_some(), e.to_string()),
+            None => return lost(subscription_id.is_some(), "connection closed".to_string()),
+        };
+
+        let value: serde_json::Value = match serde_json::from_str(&text) {
+            Ok(value) => value,
+            Err(e) => {
+                println!("Ignoring malformed WebSocket message: {}", e);
+                continue;
+            }
+        };
+
+        // Reply to eth_subscribe
+        if value.get("id").and_then(|id| id.as_u64()) == Some(1) {
+            if let Some(error) = value.get("error") {
+                let message = error.get("message").and_then(|m| m.as_str()).unwrap_or("unknown error");
+                return SessionEnd::Rejected(message.to_string());
+            }
+            subscription_id = value.get("result").and_then(|id| id.as_str()).map(|id| id.to_string());
+            continue;
+        }
+
+        // eth_subscription notification
+        let notification = match value.get("params") {
+            Some(params) if value.get("method").and_then(|m| m.as_str()) == Some("eth_subscription") => params,
+            _ => continue,
+        };
+        if notification.get("subscription").and_then(|id| id.as_str()) != subscription_id.as_deref() {
+            continue;
+        }
+        let item = match notification.get("result").map(|result| T::deserialize(result)) {
+            Some(Ok(item)) => item,
+            Some(Err(e)) => {
+                println!("Ignoring undecodable subscription item: {}", e);
+                continue;
+            }
+            None => continue,
+        };
+        if sender.send(item).await.is_err() {
+            return SessionEnd::ReceiverDropped;
+        }
+    }
+}
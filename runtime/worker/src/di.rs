@@ -1,18 +1,32 @@
 // DI container definition
 
+use std::env;
 use std::fs;
 use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
+use alloy_primitives::U256;
 use toml::Value;
 use worker::strategy::{AtomicArbStrategy, LiquidatorStrategy, Strategy};
-use worker::chain::{Chain, EthereumChain, HyperEVMChain};
+use worker::chain::evm::NativeToken;
+use worker::chain::gas::{FeeOracleConfig, FeeStrategy};
+use worker::chain::hyperevm::DualBlockConfig;
+use worker::chain::multicall::MulticallConfig;
+use worker::chain::rpc::EndpointConfig;
+use worker::chain::{self, ethereum, hyperevm, Chain, ChainConfig, ChainSet, EvmChain};
 use worker::env::{local::LocalEnvironment, vps::VPSEnvironment, Environment};
+use worker::module::lifi::{LifiClient, LifiConfig};
 
 
 pub struct AppContext {
     pub config: Arc<Config>,
     pub strategy: Arc<dyn Strategy>,
+    /// Primary chain (`chain_type`).
     pub chain: Arc<dyn Chain>,
+    /// Every chain in `enabled_chains`, keyed by chain id.
+    pub chains: Arc<ChainSet>,
+    /// Li.Fi API client shared by every strategy.
+    pub lifi: Arc<LifiClient>,
     pub environment: Arc<dyn Environment>,
 }
 
@@ -20,12 +34,58 @@ impl AppContext {
     pub fn new() -> Self {
         let config = Config::from_file("config.toml");
 
-        let chain: Arc<dyn Chain> = match config.chain_type.as_str() {
-            "ethereum" => Arc::new(EthereumChain::new()),
-            "hyperevm" => Arc::new(HyperEVMChain::new()),
-            _ => Arc::new(EthereumChain::new()), // fallback
+        // ウォレットはチェーンに渡し、署名鍵はchainモジュールの外に出さない
+        let accounts = chain::load_wallet_accounts_evm().unwrap_or_else(|e| {
+            println!("Failed to load wallets: {}", e);
+            Vec::new()
+        });
+
+        // chain_typeに対応する[chains.<key>]セクションからEVMチェーンを構築
+        let chain_config = config.chain(&config.chain_type).cloned().unwrap_or_else(|| {
+            println!("Unknown chain_type {}, falling back to ethereum", config.chain_type);
+            ethereum::default_config()
+        });
+        let chain: Arc<dyn Chain> = match EvmChain::new(chain_config, accounts.clone()) {
+            Ok(chain) => Arc::new(chain),
+            Err(e) => {
+                println!("Invalid chain config: {}, falling back to ethereum", e);
+                Arc::new(EvmChain::new(ethereum::default_config(), accounts.clone()).expect("ethereum defaults are valid"))
+            }
         };
 
+        // クロスチェーンルート用に有効化された他のチェーンも同じウォレットで保持
+        let mut chain_set = ChainSet::new(chain.clone());
+        for key in &config.enabled_chains {
+            if chain_set.by_key(key).is_some() {
+                continue;
+            }
+            let chain_config = match config.chain(key) {
+                Some(chain_config) => chain_config.clone(),
+                None => {
+                    println!("Unknown chain {} in enabled_chains, skipping", key);
+                    continue;
+                }
+            };
+            match EvmChain::new(chain_config, accounts.clone()) {
+                Ok(extra) => chain_set.insert(Arc::new(extra)),
+                Err(e) => println!("Invalid chain config: {}, skipping", e),
+            }
+        }
+        let chains = Arc::new(chain_set);
+
+        // APIキーは.envから読み、config.tomlには書かない
+        let lifi_config = LifiConfig {
+            api_keys: LifiConfig::parse_api_keys(&env::var("LIFI_API_KEYS").unwrap_or_default()),
+            ..config.lifi.clone()
+        };
+        if lifi_config.api_keys.is_empty() {
+            println!("LIFI_API_KEYS is not set, calling Li.Fi without an API key");
+        }
+        let lifi = Arc::new(LifiClient::new(lifi_config).unwrap_or_else(|e| {
+            println!("Invalid Li.Fi config: {}, falling back to defaults", e);
+            LifiClient::new(LifiConfig::default()).expect("Li.Fi defaults are valid")
+        }));
+
         let environment: Arc<dyn Environment> = match config.environment_type.as_str() {
             "local" => Arc::new(LocalEnvironment::new()),
             "vps" => Arc::new(VPSEnvironment::new()),
@@ -33,15 +93,17 @@ impl AppContext {
         };
 
         let strategy: Arc<dyn Strategy> = match config.strategy_type.as_str() {
-            "atomic_arb" => Arc::new(AtomicArbStrategy::new(chain.clone(), environment.clone())),
-            "liquidator" => Arc::new(LiquidatorStrategy::new(chain.clone(), environment.clone())),
-            _ => Arc::new(AtomicArbStrategy::new(chain.clone(), environment.clone())), // fallback
+            "atomic_arb" => Arc::new(AtomicArbStrategy::new(chains.clone(), lifi.clone(), environment.clone())),
+            "liquidator" => Arc::new(LiquidatorStrategy::new(chains.clone(), environment.clone())),
+            _ => Arc::new(AtomicArbStrategy::new(chains.clone(), lifi.clone(), environment.clone())), // fallback
         };
 
         AppContext {
             config: Arc::new(config),
             strategy,
             chain,
+            chains,
+            lifi,
             environment,
         }
     }
@@ -53,6 +115,12 @@ pub struct Config {
     pub strategy_type: String,
     pub chain_type: String,
     pub environment_type: String,
+    /// Every `[chains.<key>]` section, plus built-in ethereum/hyperevm defaults.
+    pub chains: Vec<ChainConfig>,
+    /// `[chains.<key>]` names to connect to alongside `chain_type`.
+    pub enabled_chains: Vec<String>,
+    /// `[lifi]` section; the API key is taken from the environment instead.
+    pub lifi: LifiConfig,
 }
 
 impl Config {
@@ -60,11 +128,255 @@ impl Config {
         let config_content = fs::read_to_string(Path::new(config_path)).unwrap_or_default();
         let config: Value = config_content.parse::<Value>().unwrap_or(Value::Table(Default::default()));
 
+        let chain_type = config["chain_type"].as_str().unwrap_or("ethereum").to_string();
+
+        let enabled_chains = config
+            .get("enabled_chains")
+            .and_then(|keys| keys.as_array())
+            .map(|keys| keys.iter().filter_map(|key| key.as_str().map(|key| key.to_string())).collect())
+            .unwrap_or_default();
+
+        let mut chains = vec![ethereum::default_config(), hyperevm::default_config()];
+        if let Some(sections) = config.get("chains").and_then(|chains| chains.as_table()) {
+            for (key, section) in sections {
+                let base = chains.iter().position(|chain| &chain.key == key);
+                let defaults = base.map(|index| chains[index].clone());
+                match chain_config(key, section, defaults) {
+                    Ok(chain) => match base {
+                        Some(index) => chains[index] = chain,
+                        None => chains.push(chain),
+                    },
+                    Err(e) => println!("Skipping [chains.{}]: {}", key, e),
+                }
+            }
+        }
+
         Config {
             log_level: config["log_level"].as_str().unwrap_or("info").to_string(),
             strategy_type: config["strategy_type"].as_str().unwrap_or("atomic_arb").to_string(),
-            chain_type: config["chain_type"].as_str().unwrap_or("ethereum").to_string(),
+            chain_type,
             environment_type: config["environment_type"].as_str().unwrap_or("local").to_string(),
+            chains,
+            enabled_chains,
+            lifi: lifi_config(config.get("lifi")),
+        }
+    }
+
+    pub fn chain(&self, key: &str) -> Option<&ChainConfig> {
+        self.chains.iter().find(|chain| chain.key == key)
+    }
+}
+
+/// Builds a chain from its config section; keys missing from the section
+/// keep the built-in `defaults` (required for chains other than ethereum/hyperevm).
+fn chain_config(key: &str, section: &Value, defaults: Option<ChainConfig>) -> Result<ChainConfig, String> {
+    let str_of = |name: &str| section.get(name).and_then(|v| v.as_str()).map(|v| v.to_string());
+
+    let chain_id = match int_of(section, "chain_id")? {
+        Some(id) => id,
+        None => defaults.as_ref().map(|d| d.chain_id).ok_or("chain_id is required")?,
+    };
+
+    // rpc_urls: 文字列または { url, rate_limit } の配列（従来のrpc_url単体も可）
+    let rpc_endpoints: Vec<EndpointConfig> = match (section.get("rpc_urls").and_then(|v| v.as_array()), str_of("rpc_url")) {
+        (Some(urls), _) => urls
+            .iter()
+            .filter_map(|url| endpoint_config(url).transpose())
+            .collect::<Result<_, _>>()?,
+        (None, Some(url)) => vec![EndpointConfig::new(&url)],
+        (None, None) => defaults.as_ref().map(|d| d.rpc_endpoints.clone()).unwrap_or_default(),
+    };
+    if rpc_endpoints.is_empty() {
+        return Err("rpc_urls is required".to_string());
+    }
+    let ws_urls: Vec<String> = match section.get("ws_urls").and_then(|v| v.as_array()) {
+        Some(urls) => urls.iter().filter_map(|url| url.as_str().map(|url| url.to_string())).collect(),
+        None => defaults.as_ref().map(|d| d.ws_urls.clone()).unwrap_or_default(),
+    };
+    let rpc_quorum = match section.get("rpc_quorum").and_then(|v| v.as_integer()) {
+        Some(quorum) => quorum.max(1) as usize,
+        None => defaults.as_ref().map(|d| d.rpc_quorum).unwrap_or(1),
+    };
+
+    let native_token = match section.get("native_token") {
+        Some(token) => NativeToken {
+            symbol: token.get("symbol").and_then(|v| v.as_str()).unwrap_or("ETH").to_string(),
+            decimals: int_of(token, "decimals")
+                .map_err(|e| format!("native_token: {}", e))?
+                .unwrap_or(18),
+        },
+        None => defaults.as_ref().map(|d| d.native_token.clone()).unwrap_or(NativeToken {
+            symbol: "ETH".to_string(),
+            decimals: 18,
+        }),
+    };
+
+    let block_time = match int_of(section, "block_time_ms")? {
+        Some(ms) => Duration::from_millis(ms),
+        None => defaults.as_ref().map(|d| d.block_time).unwrap_or(Duration::from_secs(2)),
+    };
+
+    let confirmations = match section.get("confirmations").and_then(|v| v.as_integer()) {
+        Some(confirmations) => confirmations.max(1) as u64,
+        None => defaults.as_ref().map(|d| d.confirmations).unwrap_or(1),
+    };
+
+    let fee = fee_oracle_config(section, defaults.as_ref().map(|d| d.fee.clone()).unwrap_or_default())?;
+    let multicall = multicall_config(section, defaults.as_ref().map(|d| d.multicall.clone()).unwrap_or_default())?;
+
+    Ok(ChainConfig {
+        key: key.to_string(),
+        chain_id,
+        name: str_of("name")
+            .or_else(|| defaults.as_ref().map(|d| d.name.clone()))
+            .unwrap_or_else(|| key.to_string()),
+        rpc_endpoints,
+        ws_urls,
+        rpc_quorum,
+        native_token,
+        block_time,
+        confirmations,
+        explorer_url: str_of("explorer_url").or_else(|| defaults.as_ref().and_then(|d| d.explorer_url.clone())),
+        eip1559: section
+            .get("eip1559")
+            .and_then(|v| v.as_bool())
+            .or(defaults.as_ref().map(|d| d.eip1559))
+            .unwrap_or(true),
+        lifi_key: str_of("lifi_key").or_else(|| defaults.as_ref().and_then(|d| d.lifi_key.clone())),
+        fee,
+        multicall,
+        allow_unchecked_output: section
+            .get("allow_unchecked_output")
+            .and_then(|v| v.as_bool())
+            .or(defaults.as_ref().map(|d| d.allow_unchecked_output))
+            .unwrap_or(false),
+        // 空文字でHyperCore連携を無効化
+        hypercore_api_url: match str_of("hypercore_api_url") {
+            Some(url) if url.trim().is_empty() => None,
+            Some(url) => Some(url),
+            None => defaults.as_ref().and_then(|d| d.hypercore_api_url.clone()),
+        },
+        dual_blocks: dual_block_config(section, defaults.as_ref().and_then(|d| d.dual_blocks.clone())),
+    })
+}
+
+/// One `rpc_urls` entry; entries that are neither a string nor a table with a `url` are ignored
+fn endpoint_config(value: &Value) -> Result<Option<EndpointConfig>, String> {
+    match value {
+        Value::String(url) => Ok(Some(EndpointConfig::new(url))),
+        Value::Table(table) => match table.get("url").and_then(|v| v.as_str()) {
+            Some(url) => Ok(Some(EndpointConfig {
+                url: url.to_string(),
+                rate_limit: int_of(value, "rate_limit")?,
+            })),
+            None => Ok(None),
+        },
+        _ => Ok(None),
+    }
+}
+
+/// `fee_strategy`, `max_fee_per_gas_gwei` and `max_priority_fee_per_gas_gwei` of a chain section
+fn fee_oracle_config(section: &Value, mut fee_config: FeeOracleConfig) -> Result<FeeOracleConfig, String> {
+    if let Some(strategy) = section.get("fee_strategy") {
+        let parsed = match strategy {
+            Value::String(name) => FeeStrategy::parse(name),
+            other => other.as_float().or(other.as_integer().map(|p| p as f64)).map(FeeStrategy::Percentile),
+        };
+        match parsed {
+            Some(strategy) => fee_config.strategy = strategy,
+            None => println!("Unknown fee_strategy {}, using standard", strategy),
         }
     }
+    if let Some(cap) = gwei_to_wei(section, "max_fee_per_gas_gwei")? {
+        fee_config.max_fee_cap = Some(cap);
+    }
+    if let Some(cap) = gwei_to_wei(section, "max_priority_fee_per_gas_gwei")? {
+        fee_config.max_priority_fee_cap = Some(cap);
+    }
+    Ok(fee_config)
+}
+
+/// `multicall_address` (an empty string disables batching) and `multicall_max_batch_gas` of a chain section
+fn multicall_config(section: &Value, mut multicall: MulticallConfig) -> Result<MulticallConfig, String> {
+    if let Some(address) = section.get("multicall_address").and_then(|v| v.as_str()) {
+        multicall.address = match address.trim() {
+            "" => None,
+            address => Some(address.parse().map_err(|e| format!("invalid multicall_address {}: {}", address, e))?),
+        };
+    }
+    if let Some(gas) = section.get("multicall_max_batch_gas").and_then(|v| v.as_integer()) {
+        multicall.max_batch_gas = gas.max(1) as u64;
+    }
+    Ok(multicall)
+}
+
+/// `small_block_gas_limit`, `big_block_gas_limit` and `big_block_time_ms` of a chain section;
+/// setting any of them enables small/big block routing on chains without a preset
+fn dual_block_config(section: &Value, dual_blocks: Option<DualBlockConfig>) -> Option<DualBlockConfig> {
+    let int_of = |name: &str| section.get(name).and_then(|v| v.as_integer()).map(|v| v.max(1) as u64);
+    let (small, big, time) = (
+        int_of("small_block_gas_limit"),
+        int_of("big_block_gas_limit"),
+        int_of("big_block_time_ms"),
+    );
+    if dual_blocks.is_none() && small.is_none() && big.is_none() && time.is_none() {
+        return None;
+    }
+    let mut config = dual_blocks.unwrap_or_default();
+    if let Some(small) = small {
+        config.small_block_gas_limit = small;
+    }
+    if let Some(big) = big {
+        config.big_block_gas_limit = big;
+    }
+    if let Some(time) = time {
+        config.big_block_time = Duration::from_millis(time);
+    }
+    Some(config)
+}
+
+/// `base_url`, `timeout_ms`, `max_retries`, `status_poll_interval_ms` and `max_key_wait_ms` of the `[lifi]` section
+fn lifi_config(section: Option<&Value>) -> LifiConfig {
+    let mut lifi = LifiConfig::default();
+    let Some(section) = section else {
+        return lifi;
+    };
+    if let Some(base_url) = section.get("base_url").and_then(|v| v.as_str()) {
+        lifi.base_url = base_url.to_string();
+    }
+    if let Some(timeout) = section.get("timeout_ms").and_then(|v| v.as_integer()) {
+        lifi.timeout = Duration::from_millis(timeout.max(1) as u64);
+    }
+    if let Some(retries) = section.get("max_retries").and_then(|v| v.as_integer()) {
+        lifi.max_retries = retries.max(0) as u32;
+    }
+    if let Some(interval) = section.get("status_poll_interval_ms").and_then(|v| v.as_integer()) {
+        lifi.status_poll_interval = Duration::from_millis(interval.max(1) as u64);
+    }
+    if let Some(wait) = section.get("max_key_wait_ms").and_then(|v| v.as_integer()) {
+        lifi.max_key_wait = Duration::from_millis(wait.max(0) as u64);
+    }
+    lifi
+}
+
+/// Integer key `name` of `section`, converted to `T`; out-of-range values are an error naming the key
+fn int_of<T: TryFrom<i64>>(section: &Value, name: &str) -> Result<Option<T>, String> {
+    match section.get(name).and_then(|v| v.as_integer()) {
+        Some(value) => T::try_from(value)
+            .map(Some)
+            .map_err(|_| format!("invalid {} {}", name, value)),
+        None => Ok(None),
+    }
+}
+
+/// Gwei amount in key `name` of `section`, in wei; negative, infinite or NaN amounts are an error
+fn gwei_to_wei(section: &Value, name: &str) -> Result<Option<U256>, String> {
+    let Some(gwei) = section.get(name).and_then(|v| v.as_float().or(v.as_integer().map(|i| i as f64))) else {
+        return Ok(None);
+    };
+    let wei = gwei * 1e9;
+    if !wei.is_finite() || wei < 0.0 || wei >= u128::MAX as f64 {
+        return Err(format!("invalid {} {}", name, gwei));
+    }
+    Ok(Some(U256::from(wei as u128)))
 }
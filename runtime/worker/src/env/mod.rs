@@ -4,6 +4,6 @@ pub mod vps;
 pub use local::LocalEnvironment;
 pub use vps::VPSEnvironment;
 
-pub trait Environment {
+pub trait Environment: Send + Sync {
     fn setup(&self);
 }
\ No newline at end of file
@@ -1,7 +1,10 @@
 // Entry point for Rust worker bot
 
+mod approvals;
 mod di;
-mod chain;
+
+use std::env;
+use worker::chain::{self, WalletAccount};
 
 fn main() {
     // Initialize dotenv to load .env file
@@ -9,27 +12,81 @@ fn main() {
     
     println!("Initializing worker bot...");
 
-    // Test wallet address conversion
-    println!("Testing wallet address conversion...");
-    match chain::hyperevm::get_primary_wallet_address() {
-        Ok(address) => {
-            println!("✅ Primary wallet address: {:#x}", address);
-        }
-        Err(e) => {
-            println!("❌ Failed to get primary wallet address: {}", e);
+    let context = di::AppContext::new();
+    println!(
+        "Config: strategy={}, chain={}, environment={}, log_level={}",
+        context.config.strategy_type,
+        context.config.chain_type,
+        context.config.environment_type,
+        context.config.log_level
+    );
+    println!("Li.Fi API: {}", context.lifi.config().base_url);
+
+    // `approvals` subcommand: inventory and revoke open ERC-20 allowances
+    let args: Vec<String> = env::args().collect();
+    if args.get(1).map(String::as_str) == Some("approvals") {
+        if let Err(e) = approvals::run(&context, &args[2..]) {
+            println!("❌ approvals failed: {}", e);
         }
+        return;
     }
 
-    let context = di::AppContext::new();
+    // List configured wallets
+    for account in context.chain.accounts() {
+        match account {
+            WalletAccount::Signer(signer) => println!("✅ Signing wallet: {:#x}", signer.address()),
+            WalletAccount::WatchOnly(address) => println!("👀 Watch-only wallet: {:#x}", address),
+        }
+    }
+    match context.chain.primary_signer() {
+        Ok(signer) => println!("✅ Primary wallet address: {:#x}", signer.address()),
+        Err(e) => println!("❌ Failed to get primary wallet address: {}", e),
+    }
 
     // Setup environment
     context.environment.setup();
 
-    // Connect to chain
-    context.chain.connect();
+    // Connect to every enabled chain
+    for chain in context.chains.iter() {
+        chain.connect();
+    }
+
+    // Refuse to run against an RPC endpoint that serves another chain
+    let runtime = tokio::runtime::Runtime::new().expect("Failed to create tokio runtime");
+    let verified = runtime.block_on(async {
+        let mut verified = true;
+        for chain in context.chains.iter() {
+            match chain::verify_chain_id(chain.as_ref()).await {
+                Ok(()) => println!("✅ [{}] Chain id {} verified", chain.config().name, chain.config().chain_id),
+                Err(e) => {
+                    println!("❌ {}", e);
+                    verified = false;
+                }
+            }
+        }
+        verified
+    });
+    if !verified {
+        std::process::exit(1);
+    }
+
+    // Follow every chain's head in the background: newHeads when WebSocket
+    // endpoints are configured, polling once per block time otherwise
+    for chain in context.chains.iter() {
+        let source = if chain.subscriptions().is_some() { "newHeads" } else { "polling" };
+        println!("[{}] Following blocks via {}", chain.config().name, source);
+        let chain = chain.clone();
+        runtime.spawn(async move { chain.blocks().run(chain.as_ref()).await });
+    }
+
+    // Fill nonce gaps left by failed sends before they block later transactions
+    for chain in context.chains.iter() {
+        let chain = chain.clone();
+        runtime.spawn(async move { chain.nonces().run(chain.as_ref()).await });
+    }
 
-    // Execute strategy
-    context.strategy.execute();
+    // Execute strategy on the same runtime
+    context.strategy.execute(runtime.handle());
 
     println!("Worker bot execution completed.");
 }
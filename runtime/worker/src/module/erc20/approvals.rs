@@ -0,0 +1,263 @@
+// 残っている allowance の棚卸しと revoke
+use std::collections::BTreeSet;
+use std::env;
+use std::error::Error;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use alloy_primitives::{b256, Address, B256, U256};
+use serde::{Deserialize, Serialize};
+
+use crate::chain::rpc::{LogFilter, TransactionReceipt};
+use crate::chain::{BlockTag, CallRequest, Chain};
+
+use super::{allowance_call, approve, approve_calldata};
+
+/// `Approval(address,address,uint256)`
+const APPROVAL_TOPIC: B256 = b256!("8c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925");
+/// 1回の eth_getLogs で問い合わせるブロック数（公開RPCの上限に収まる大きさ）
+const LOG_CHUNK_BLOCKS: u64 = 5_000;
+/// `APPROVAL_LEDGER_PATH` 未設定時の記録先
+const DEFAULT_LEDGER_PATH: &str = "approvals.jsonl";
+
+/// ワーカーが送信した approve の記録（1行1件のJSON）
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LedgerEntry {
+    pub chain_id: u64,
+    pub token: Address,
+    pub owner: Address,
+    pub spender: Address,
+    pub amount: U256,
+    pub tx_hash: B256,
+    /// UNIX秒
+    pub timestamp: u64,
+}
+
+/// approve した spender を追記していくファイル。棚卸しの候補になる
+#[derive(Debug, Clone)]
+pub struct ApprovalLedger {
+    path: PathBuf,
+}
+
+impl ApprovalLedger {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// `APPROVAL_LEDGER_PATH`、未設定または空なら ./approvals.jsonl
+    pub fn from_env() -> Self {
+        let path = env::var("APPROVAL_LEDGER_PATH")
+            .ok()
+            .filter(|path| !path.is_empty())
+            .unwrap_or_else(|| DEFAULT_LEDGER_PATH.to_string());
+        Self::new(path)
+    }
+
+    pub fn record(
+        &self,
+        chain_id: u64,
+        receipt: &TransactionReceipt,
+        token: Address,
+        spender: Address,
+        amount: U256,
+    ) -> Result<(), Box<dyn Error>> {
+        let entry = LedgerEntry {
+            chain_id,
+            token,
+            owner: receipt.from,
+            spender,
+            amount,
+            tx_hash: receipt.transaction_hash,
+            timestamp: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+        };
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+        Ok(())
+    }
+
+    /// 記録済みの approve（ファイルがなければ空、壊れた行は読み飛ばす）
+    pub fn entries(&self) -> Result<Vec<LedgerEntry>, Box<dyn Error>> {
+        let content = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+}
+
+/// (token, owner, spender) の組
+pub type ApprovalKey = (Address, Address, Address);
+
+/// 0 でない allowance
+#[derive(Debug, Clone)]
+pub struct OpenAllowance {
+    pub token: Address,
+    pub owner: Address,
+    pub spender: Address,
+    pub amount: U256,
+}
+
+/// 棚卸し対象の組: 記録済みの approve と、直近 `lookback_blocks` の Approval ログ。
+/// さらにそこに出てきたトークンごとに、既知の spender（Li.Fi Diamond など）への
+/// allowance も候補にする（記録を始める前の approve やログの範囲外の approve を拾うため）
+pub async fn approval_candidates(
+    chain: &dyn Chain,
+    owners: &[Address],
+    ledger: &[LedgerEntry],
+    known_spenders: &[Address],
+    lookback_blocks: u64,
+) -> Result<BTreeSet<ApprovalKey>, Box<dyn Error>> {
+    let mut candidates: BTreeSet<ApprovalKey> = ledger
+        .iter()
+        .filter(|entry| entry.chain_id == chain.config().chain_id && owners.contains(&entry.owner))
+        .map(|entry| (entry.token, entry.owner, entry.spender))
+        .collect();
+    if lookback_blocks > 0 && !owners.is_empty() {
+        candidates.extend(logged_approvals(chain, owners, lookback_blocks).await?);
+    }
+
+    let tokens: BTreeSet<Address> = candidates.iter().map(|(token, _, _)| *token).collect();
+    for token in tokens {
+        for owner in owners {
+            for spender in known_spenders {
+                candidates.insert((token, *owner, *spender));
+            }
+        }
+    }
+    Ok(candidates)
+}
+
+/// 直近 `lookback_blocks` の `owners` の ERC-20 Approval ログ
+async fn logged_approvals(
+    chain: &dyn Chain,
+    owners: &[Address],
+    lookback_blocks: u64,
+) -> Result<BTreeSet<ApprovalKey>, Box<dyn Error>> {
+    let mut candidates = BTreeSet::new();
+
+    let head = chain.rpc().block_number().await?;
+    let mut from = head.saturating_sub(lookback_blocks);
+    while from <= head {
+        let to = (from + LOG_CHUNK_BLOCKS - 1).min(head);
+        let filter = LogFilter {
+            topics: vec![
+                Some(vec![APPROVAL_TOPIC]),
+                Some(owners.iter().map(|owner| owner.into_word()).collect()),
+            ],
+            from_block: Some(BlockTag::Number(from)),
+            to_block: Some(BlockTag::Number(to)),
+            ..Default::default()
+        };
+        match chain.rpc().get_logs(&filter).await {
+            Ok(logs) => {
+                for log in logs {
+                    // ERC-721 の Approval は topic が4つなので除外
+                    if let [_, owner, spender] = log.topics.as_slice() {
+                        candidates.insert((
+                            log.address,
+                            Address::from_word(*owner),
+                            Address::from_word(*spender),
+                        ));
+                    }
+                }
+            }
+            Err(e) => println!(
+                "[{}] eth_getLogs for blocks {}..={} failed: {}",
+                chain.config().name,
+                from,
+                to,
+                e
+            ),
+        }
+        from = to + 1;
+    }
+    Ok(candidates)
+}
+
+/// 候補の現在の allowance をマルチコールでまとめて読み、0 でないものを返す
+pub async fn scan_allowances(
+    chain: &dyn Chain,
+    candidates: &BTreeSet<ApprovalKey>,
+) -> Result<Vec<OpenAllowance>, Box<dyn Error>> {
+    let keys: Vec<ApprovalKey> = candidates.iter().copied().collect();
+    let calls: Vec<_> = keys
+        .iter()
+        .map(|(token, owner, spender)| allowance_call(*token, *owner, *spender))
+        .collect();
+    let results = chain.multicall().call_typed(chain.rpc(), &calls, BlockTag::Latest).await?;
+
+    let mut open = Vec::new();
+    for ((token, owner, spender), result) in keys.into_iter().zip(results) {
+        match result {
+            Ok(amount) if !amount.is_zero() => open.push(OpenAllowance {
+                token,
+                owner,
+                spender,
+                amount,
+            }),
+            Ok(_) => {}
+            Err(e) => println!(
+                "[{}] allowance({:#x}, {:#x}) on {:#x} failed: {}",
+                chain.config().name,
+                owner,
+                spender,
+                token,
+                e
+            ),
+        }
+    }
+    Ok(open)
+}
+
+/// allowance を 0 に戻す。`dry_run` なら estimateGas で通ることだけ確認し、送信しない。
+/// 結果は入力と同じ順で、送信した場合はレシート、dry run なら `None`。
+pub async fn revoke_allowances(
+    chain: &dyn Chain,
+    allowances: &[OpenAllowance],
+    dry_run: bool,
+) -> Vec<Result<Option<TransactionReceipt>, Box<dyn Error>>> {
+    let mut results = Vec::with_capacity(allowances.len());
+    for allowance in allowances {
+        results.push(revoke(chain, allowance, dry_run).await);
+    }
+    results
+}
+
+async fn revoke(
+    chain: &dyn Chain,
+    allowance: &OpenAllowance,
+    dry_run: bool,
+) -> Result<Option<TransactionReceipt>, Box<dyn Error>> {
+    let signer = chain
+        .signer(allowance.owner)
+        .ok_or_else(|| format!("{:#x} is watch-only or unknown", allowance.owner))?;
+
+    if dry_run {
+        let call = CallRequest {
+            from: Some(allowance.owner),
+            to: Some(allowance.token),
+            data: Some(approve_calldata(allowance.spender, U256::ZERO)),
+            ..Default::default()
+        };
+        let gas = chain.rpc().estimate_gas(&call, BlockTag::Latest).await?;
+        println!(
+            "[{}] Would revoke {:#x} on {:#x} for {:#x} (gas estimate {})",
+            chain.config().name,
+            allowance.spender,
+            allowance.token,
+            allowance.owner,
+            gas
+        );
+        return Ok(None);
+    }
+
+    let receipt = approve(chain, signer.as_ref(), allowance.token, allowance.spender, U256::ZERO).await?;
+    Ok(Some(receipt))
+}
@@ -0,0 +1,307 @@
+// ERC-20 モジュール: 残高・allowance・メタデータの読み取りと approve フロー
+pub mod approvals;
+
+use std::error::Error;
+
+use alloy_primitives::{address, Address, Bytes, U256};
+
+use crate::chain::multicall::{Call, TypedCall};
+use crate::chain::rpc::TransactionReceipt;
+use crate::chain::tx::{send_transaction, SendOptions, TypedTransaction};
+use crate::chain::{BlockTag, CallRequest, Chain, Signer};
+
+use approvals::ApprovalLedger;
+
+/// Li.Fiや多くのアグリゲーターがネイティブトークンに使うプレースホルダーアドレス
+pub const NATIVE_TOKEN_PLACEHOLDER: Address = address!("eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee");
+
+/// `allowance(address,address)`
+const ALLOWANCE_SELECTOR: [u8; 4] = [0xdd, 0x62, 0xed, 0x3e];
+/// `approve(address,uint256)`
+const APPROVE_SELECTOR: [u8; 4] = [0x09, 0x5e, 0xa7, 0xb3];
+/// `decimals()`
+const DECIMALS_SELECTOR: [u8; 4] = [0x31, 0x3c, 0xe5, 0x67];
+/// `symbol()`
+const SYMBOL_SELECTOR: [u8; 4] = [0x95, 0xd8, 0x9b, 0x41];
+
+/// estimateGasの結果に上乗せする割合（%）
+const APPROVE_GAS_MARGIN_PERCENT: u64 = 20;
+
+/// トークンの表示用メタデータ
+#[derive(Debug, Clone)]
+pub struct TokenMetadata {
+    pub address: Address,
+    pub symbol: String,
+    pub decimals: u8,
+}
+
+/// approveする数量の決め方
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ApprovalMode {
+    /// 今回の取引に必要な数量だけ許可する
+    Exact,
+    /// `U256::MAX` を許可し、以降の approve を省略する
+    Infinite,
+}
+
+impl ApprovalMode {
+    /// `RouteAction.infinite_approval` から決める（未指定は Exact）
+    pub fn from_infinite_approval(infinite_approval: Option<bool>) -> Self {
+        if infinite_approval.unwrap_or(false) {
+            ApprovalMode::Infinite
+        } else {
+            ApprovalMode::Exact
+        }
+    }
+
+    fn amount(&self, required: U256) -> U256 {
+        match self {
+            ApprovalMode::Exact => required,
+            ApprovalMode::Infinite => U256::MAX,
+        }
+    }
+}
+
+/// 0x0 と 0xeeee… はネイティブトークン（allowance 不要）
+pub fn is_native(token: Address) -> bool {
+    token == Address::ZERO || token == NATIVE_TOKEN_PLACEHOLDER
+}
+
+fn with_args(selector: [u8; 4], args: &[&[u8]]) -> Bytes {
+    let mut data = selector.to_vec();
+    for arg in args {
+        data.extend_from_slice(arg);
+    }
+    data.into()
+}
+
+/// `balanceOf(owner)` のマルチコール用呼び出し
+pub fn balance_of_call(token: Address, owner: Address) -> TypedCall<U256> {
+    TypedCall::balance_of(token, owner)
+}
+
+/// `allowance(owner, spender)` のマルチコール用呼び出し
+pub fn allowance_call(token: Address, owner: Address, spender: Address) -> TypedCall<U256> {
+    let data = with_args(
+        ALLOWANCE_SELECTOR,
+        &[owner.into_word().as_slice(), spender.into_word().as_slice()],
+    );
+    let mut call = Call::new(token, data);
+    call.gas_hint = 30_000;
+    TypedCall::uint(call)
+}
+
+/// `approve(spender, amount)` のcalldata
+pub fn approve_calldata(spender: Address, amount: U256) -> Bytes {
+    with_args(
+        APPROVE_SELECTOR,
+        &[spender.into_word().as_slice(), &amount.to_be_bytes::<32>()],
+    )
+}
+
+async fn call_u256(chain: &dyn Chain, token: Address, data: Bytes, quorum: bool) -> Result<U256, Box<dyn Error>> {
+    let call = CallRequest {
+        to: Some(token),
+        data: Some(data),
+        ..Default::default()
+    };
+    let output = if quorum {
+        chain.rpc().call_quorum(&call, BlockTag::Latest).await?
+    } else {
+        chain.rpc().call(&call, BlockTag::Latest).await?
+    };
+    if output.len() < 32 {
+        return Err(format!("{:#x} returned {} bytes, expected a uint256", token, output.len()).into());
+    }
+    Ok(U256::from_be_slice(&output[..32]))
+}
+
+/// ERC-20の残高
+pub async fn balance_of(chain: &dyn Chain, token: Address, owner: Address) -> Result<U256, Box<dyn Error>> {
+    call_u256(chain, token, balance_of_call(token, owner).call.data, false).await
+}
+
+/// ネイティブトークンまたはERC-20の残高（取引サイズの判断に使うのでquorum読み取り）
+pub async fn token_balance_quorum(chain: &dyn Chain, token: Address, owner: Address) -> Result<U256, Box<dyn Error>> {
+    if is_native(token) {
+        return Ok(chain.rpc().get_balance_quorum(owner, BlockTag::Latest).await?);
+    }
+    call_u256(chain, token, balance_of_call(token, owner).call.data, true).await
+}
+
+pub async fn allowance(
+    chain: &dyn Chain,
+    token: Address,
+    owner: Address,
+    spender: Address,
+) -> Result<U256, Box<dyn Error>> {
+    call_u256(chain, token, allowance_call(token, owner, spender).call.data, false).await
+}
+
+/// 複数トークンの残高をマルチコールでまとめて読む（読めなかったトークンは `Err`）
+pub async fn balances(
+    chain: &dyn Chain,
+    tokens: &[Address],
+    owner: Address,
+) -> Result<Vec<Result<U256, Box<dyn Error>>>, Box<dyn Error>> {
+    let calls: Vec<TypedCall<U256>> = tokens.iter().map(|token| balance_of_call(*token, owner)).collect();
+    let results = chain.multicall().call_typed(chain.rpc(), &calls, BlockTag::Latest).await?;
+    Ok(results.into_iter().map(|result| result.map_err(|e| e.into())).collect())
+}
+
+pub async fn decimals(chain: &dyn Chain, token: Address) -> Result<u8, Box<dyn Error>> {
+    Ok(metadata(chain, token).await?.decimals)
+}
+
+pub async fn symbol(chain: &dyn Chain, token: Address) -> Result<String, Box<dyn Error>> {
+    Ok(metadata(chain, token).await?.symbol)
+}
+
+/// `symbol()` と `decimals()` を1回のマルチコールで読む
+pub async fn metadata(chain: &dyn Chain, token: Address) -> Result<TokenMetadata, Box<dyn Error>> {
+    if is_native(token) {
+        let native = &chain.config().native_token;
+        return Ok(TokenMetadata {
+            address: token,
+            symbol: native.symbol.clone(),
+            decimals: native.decimals,
+        });
+    }
+
+    let calls = [
+        Call::new(token, with_args(SYMBOL_SELECTOR, &[])),
+        Call::new(token, with_args(DECIMALS_SELECTOR, &[])),
+    ];
+    let mut results = chain
+        .multicall()
+        .aggregate(chain.rpc(), &calls, BlockTag::Latest)
+        .await?
+        .into_iter();
+    let symbol = results.next().ok_or("missing symbol() result")??;
+    let decimals = results.next().ok_or("missing decimals() result")??;
+
+    let decimals = decimals
+        .get(..32)
+        .map(U256::from_be_slice)
+        .and_then(|value| u8::try_from(value).ok())
+        .ok_or_else(|| format!("{:#x} returned invalid decimals", token))?;
+    Ok(TokenMetadata {
+        address: token,
+        symbol: decode_symbol(&symbol).ok_or_else(|| format!("{:#x} returned invalid symbol", token))?,
+        decimals,
+    })
+}
+
+/// ABIの `string`、または古いトークン（MKRなど）の `bytes32`
+fn decode_symbol(data: &[u8]) -> Option<String> {
+    if data.len() == 32 {
+        let end = data.iter().position(|byte| *byte == 0).unwrap_or(32);
+        return Some(String::from_utf8_lossy(&data[..end]).into_owned());
+    }
+    let offset: usize = U256::from_be_slice(data.get(..32)?).try_into().ok()?;
+    let length: usize = U256::from_be_slice(data.get(offset..offset + 32)?).try_into().ok()?;
+    let bytes = data.get(offset + 32..offset + 32 + length)?;
+    Some(String::from_utf8_lossy(bytes).into_owned())
+}
+
+/// `spender` が `owner` の `token` を `required` 以上使えるようにする。
+/// 足りなければ approve を送信して確定まで待つ。送信した approve のレシートを返す
+/// （ネイティブトークンや allowance が十分な場合は空）。
+pub async fn ensure_allowance(
+    chain: &dyn Chain,
+    signer: &dyn Signer,
+    token: Address,
+    spender: Address,
+    required: U256,
+    mode: ApprovalMode,
+) -> Result<Vec<TransactionReceipt>, Box<dyn Error>> {
+    if is_native(token) || spender == Address::ZERO || required.is_zero() {
+        return Ok(Vec::new());
+    }
+    let owner = signer.address();
+    let current = allowance(chain, token, owner, spender).await?;
+    if current >= required {
+        return Ok(Vec::new());
+    }
+
+    let mut receipts = Vec::new();
+    // USDTなど、0以外のallowanceを直接変更できないトークンがあるので先に0へ戻す
+    if !current.is_zero() {
+        println!(
+            "[{}] Resetting allowance of {:#x} for {:#x} ({} < {})",
+            chain.config().name,
+            token,
+            spender,
+            current,
+            required
+        );
+        receipts.push(approve(chain, signer, token, spender, U256::ZERO).await?);
+    }
+
+    let amount = mode.amount(required);
+    println!(
+        "[{}] Approving {:#x} to spend {} of {:#x} ({:?})",
+        chain.config().name,
+        spender,
+        amount,
+        token,
+        mode
+    );
+    let receipt = approve(chain, signer, token, spender, amount).await?;
+    // 後で棚卸し・revokeできるように spender を記録しておく
+    if let Err(e) = ApprovalLedger::from_env().record(chain.config().chain_id, &receipt, token, spender, amount) {
+        println!("Failed to record approval in ledger: {}", e);
+    }
+    receipts.push(receipt);
+    Ok(receipts)
+}
+
+/// `approve(spender, amount)` を送信して確定まで待つ
+pub async fn approve(
+    chain: &dyn Chain,
+    signer: &dyn Signer,
+    token: Address,
+    spender: Address,
+    amount: U256,
+) -> Result<TransactionReceipt, Box<dyn Error>> {
+    let owner = signer.address();
+    let data = approve_calldata(spender, amount);
+    let call = CallRequest {
+        from: Some(owner),
+        to: Some(token),
+        data: Some(data.clone()),
+        ..Default::default()
+    };
+
+    let nonce = chain.nonces().reserve(chain, owner).await?;
+    let prepared = async {
+        let gas = chain.rpc().estimate_gas(&call, BlockTag::Pending).await?;
+        let fees = chain.fee_oracle().estimate(chain.rpc()).await?;
+        Ok::<_, Box<dyn Error>>(TypedTransaction {
+            chain_id: chain.config().chain_id,
+            nonce,
+            gas_limit: gas + gas * APPROVE_GAS_MARGIN_PERCENT / 100,
+            fees,
+            to: Some(token),
+            value: U256::ZERO,
+            data,
+        })
+    }
+    .await;
+    let tx = match prepared {
+        Ok(tx) => tx,
+        Err(e) => {
+            chain.nonces().release(owner, nonce);
+            return Err(e);
+        }
+    };
+
+    let receipt = send_transaction(chain, signer, &tx, &SendOptions::default()).await?;
+    println!(
+        "[{}] Approval {:#x} confirmed in block {}",
+        chain.config().name,
+        receipt.transaction_hash,
+        receipt.block_number.unwrap_or_default()
+    );
+    Ok(receipt)
+}
@@ -1,12 +1,13 @@
-use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
 use serde::{Deserialize, Serialize};
-use std::env;
-use std::error::Error;
+use std::str::FromStr;
 use std::sync::Arc;
-use dotenv::dotenv;
+use alloy_primitives::{Address, Bytes, U256};
 
-use crate::module::lifi::find_routes::LifiRouteResponse;
+use crate::module::lifi::client::LifiClient;
+use crate::module::lifi::error::LifiError;
+use crate::module::lifi::find_routes::{LifiRouteResponse, RouteStep};
 use crate::chain::Chain;
+use crate::chain::tx::{TxError, TxFees, TypedTransaction};
 
 /// トランザクション応答の構造体（実際のAPIレスポンスに合わせて修正）
 #[derive(Deserialize, Serialize, Debug)]
@@ -45,95 +46,159 @@ pub struct TransactionRequest {
     pub max_priority_fee_per_gas: Option<String>,
 }
 
-/// ルートIDを指定してトランザクションを構築する関数（DI対応）
-pub async fn build_transaction(
-    response: &LifiRouteResponse, 
-    route_id: Option<&str>,
-    chain: Arc<dyn Chain>
-) -> Result<TransactionResponse, Box<dyn Error>> {
-    // .envファイルを読み込む
-    dotenv().ok();
-    
-    // 環境変数からAPIキーを取得
-    let api_key = env::var("LIFI_API_KEYS").expect("LIFI_API_KEYS must be set in .env file");
-    
-    // 使用するルートを決定
-    let route = if let Some(id) = route_id {
-        // 指定されたIDのルートを検索
-        response.routes.iter()
-            .find(|r| r.id == id)
-            .ok_or_else(|| format!("Route with ID {} not found", id))?
-    } else {
-        // 最初のルートを使用
-        response.routes.first()
-            .ok_or_else(|| "No routes available")?
-    };
-    
-    // ルートの最初のステップを使用
-    if route.steps.is_empty() {
-        return Err("Selected route has no steps".into());
+impl TransactionResponse {
+    /// 送信前に、Li.Fiの応答が送金元チェーン・署名者と食い違っていないか確認する
+    /// （chainId・from・トークンのチェーンIDのどれかが違えば送信しない）
+    pub fn verify_for(&self, chain: &dyn Chain, from: Address) -> Result<(), TxError> {
+        let chain_id = chain.config().chain_id;
+        let action = &self.action;
+        let chain_ids = [
+            ("transactionRequest.chainId", self.transaction_request.chain_id, chain_id),
+            ("action.fromChainId", action.from_chain_id, chain_id),
+            ("action.fromToken.chainId", action.from_token.chain_id, chain_id),
+            ("action.toToken.chainId", action.to_token.chain_id, action.to_chain_id),
+        ];
+        for (what, actual, expected) in chain_ids {
+            if actual != expected {
+                return Err(TxError::ChainMismatch {
+                    what: what.to_string(),
+                    expected,
+                    actual,
+                });
+            }
+        }
+
+        let request_from = Address::from_str(&self.transaction_request.from)
+            .map_err(|e| TxError::InvalidRequest(format!("from: {}", e)))?;
+        if request_from != from {
+            return Err(TxError::SenderMismatch {
+                expected: from,
+                actual: request_from,
+            });
+        }
+        Ok(())
     }
-    
-    // トランザクションに使用するステップをクローン
-    let mut step = route.steps[0].clone();
-    
-    // fromAddressとtoAddressを設定
-    // DIから注入されたChainを使用してEVMアドレスを取得
-    let wallet_address = chain.get_primary_wallet_address()?;
-    let wallet_address_str = format!("{:#x}", wallet_address);
-    
-    if step.action.from_address.is_none() {
-        step.action.from_address = Some(wallet_address_str.clone());
+}
+
+impl TransactionRequest {
+    /// Li.Fiのトランザクションリクエストを署名可能なトランザクションに変換する
+    /// （`maxFeePerGas`があればEIP-1559、なければ`gasPrice`のレガシー形式）
+    pub fn to_typed_transaction(&self, nonce: u64) -> Result<TypedTransaction, TxError> {
+        let fees = match (&self.max_fee_per_gas, &self.gas_price) {
+            (Some(max_fee), _) => {
+                let max_fee_per_gas = parse_u256("maxFeePerGas", max_fee)?;
+                let max_priority_fee_per_gas = match &self.max_priority_fee_per_gas {
+                    Some(priority) => parse_u256("maxPriorityFeePerGas", priority)?,
+                    None => max_fee_per_gas,
+                };
+                TxFees::Eip1559 {
+                    max_fee_per_gas,
+                    max_priority_fee_per_gas,
+                }
+            }
+            (None, Some(gas_price)) => TxFees::Legacy {
+                gas_price: parse_u256("gasPrice", gas_price)?,
+            },
+            (None, None) => {
+                return Err(TxError::InvalidRequest(
+                    "neither gasPrice nor maxFeePerGas is set".to_string(),
+                ))
+            }
+        };
+
+        let gas_limit = parse_u256("gasLimit", &self.gas_limit)?;
+        let to = Address::from_str(&self.to)
+            .map_err(|e| TxError::InvalidRequest(format!("to: {}", e)))?;
+        let data = Bytes::from_str(&self.data)
+            .map_err(|e| TxError::InvalidRequest(format!("data: {}", e)))?;
+
+        Ok(TypedTransaction {
+            chain_id: self.chain_id,
+            nonce,
+            gas_limit: gas_limit
+                .try_into()
+                .map_err(|_| TxError::InvalidRequest("gasLimit exceeds u64".to_string()))?,
+            fees,
+            to: Some(to),
+            value: parse_u256("value", &self.value)?,
+            data,
+        })
     }
-    
-    if step.action.to_address.is_none() {
-        // トークンの送信先（通常は同じアドレス）
-        step.action.to_address = Some(wallet_address_str);
+}
+
+/// 16進数（0x付き）または10進数の数量文字列をパースする
+fn parse_u256(field: &str, value: &str) -> Result<U256, TxError> {
+    U256::from_str(value).map_err(|e| TxError::InvalidRequest(format!("{}: {}", field, e)))
+}
+
+impl LifiClient {
+    /// ルートIDを指定してトランザクションを構築する（`/advanced/stepTransaction`）
+    ///
+    /// ルートの最初のステップだけを構築する。全ステップの実行は[`RouteExecutor`](crate::module::lifi::RouteExecutor)を使う
+    pub async fn build_transaction(
+        &self,
+        response: &LifiRouteResponse,
+        route_id: Option<&str>,
+        chain: Arc<dyn Chain>,
+    ) -> Result<TransactionResponse, LifiError> {
+        // 使用するルートを決定
+        let route = if let Some(id) = route_id {
+            // 指定されたIDのルートを検索
+            response.routes.iter()
+                .find(|r| r.id == id)
+                .ok_or_else(|| LifiError::InvalidRequest(format!("Route with ID {} not found", id)))?
+        } else {
+            // 最初のルートを使用
+            response.routes.first()
+                .ok_or_else(|| LifiError::InvalidRequest("No routes available".to_string()))?
+        };
+
+        // ルートの最初のステップを使用
+        let step = route.steps.first()
+            .ok_or_else(|| LifiError::InvalidRequest("Selected route has no steps".to_string()))?;
+
+        // DIから注入されたChainを使用してEVMアドレスを取得
+        let wallet_address = chain
+            .primary_signer()
+            .map_err(|e| LifiError::InvalidRequest(e.to_string()))?
+            .address();
+
+        self.step_transaction(step, wallet_address).await
     }
-    
-    // ヘッダーを設定
-    let mut headers = HeaderMap::new();
-    headers.insert("x-lifi-api-key", HeaderValue::from_str(&api_key)?);
-    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
-    
-    // APIエンドポイントにPOSTリクエストを送信する前に、ステップJSONをログ出力
-    let step_json = serde_json::to_string_pretty(&step).unwrap_or_else(|_| format!("{:?}", step));
-    println!("Sending step to API: {}", step_json);
-    
-    let client = reqwest::Client::new();
-    let response = client
-        .post("https://li.quest/v1/advanced/stepTransaction?skipSimulation=true")
-        .headers(headers)
-        .json(&step)
-        .send()
-        .await?;
-    
-    // レスポンスを処理
-    if response.status().is_success() {
-        // まずJSONテキストを取得
-        let json_text = response.text().await?;
-        println!("Transaction API Response: {}", json_text);
-        
-        // JSONテキストをパース
-        match serde_json::from_str::<TransactionResponse>(&json_text) {
-            Ok(tx_response) => Ok(tx_response),
-            Err(e) => {
-                println!("Transaction JSON parse error: {}", e);
-                Err(format!("Failed to parse transaction API response: {}", e).into())
-            }
+
+    /// 1ステップ分のトランザクションを構築する（`/advanced/stepTransaction`）
+    ///
+    /// fromAddress・toAddressが未指定なら`from`を使う。数量は`step.action.fromAmount`で
+    /// 見積もり直されるので、前のステップの実際の受取量を入れて呼び出せる
+    pub async fn step_transaction(&self, step: &RouteStep, from: Address) -> Result<TransactionResponse, LifiError> {
+        // トランザクションに使用するステップをクローン
+        let mut step = step.clone();
+
+        // fromAddressとtoAddressを設定
+        let wallet_address_str = format!("{:#x}", from);
+
+        if step.action.from_address.is_none() {
+            step.action.from_address = Some(wallet_address_str.clone());
         }
-    } else {
-        let status = response.status();
-        let error_text = response.text().await?;
-        println!("Transaction API error (status {}): {}", status, error_text);
-        Err(format!("API request failed with status {}: {}", status, error_text).into())
+
+        if step.action.to_address.is_none() {
+            // トークンの送信先（通常は同じアドレス）
+            step.action.to_address = Some(wallet_address_str);
+        }
+
+        // APIエンドポイントにPOSTリクエストを送信する前に、ステップJSONをログ出力
+        let step_json = serde_json::to_string_pretty(&step).unwrap_or_else(|_| format!("{:?}", step));
+        println!("Sending step to API: {}", step_json);
+
+        self.post("advanced/stepTransaction", &step).await
     }
-}
 
-/// 最適なルートのトランザクションを構築する関数（最初のルートを使用）（DI対応）
-pub async fn build_best_route_transaction(
-    response: &LifiRouteResponse,
-    chain: Arc<dyn Chain>
-) -> Result<TransactionResponse, Box<dyn Error>> {
-    build_transaction(response, None, chain).await
+    /// 最適なルートのトランザクションを構築する（最初のルートを使用）
+    pub async fn build_best_route_transaction(
+        &self,
+        response: &LifiRouteResponse,
+        chain: Arc<dyn Chain>,
+    ) -> Result<TransactionResponse, LifiError> {
+        self.build_transaction(response, None, chain).await
+    }
 }
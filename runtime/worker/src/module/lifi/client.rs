@@ -0,0 +1,175 @@
+use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
+use reqwest::{RequestBuilder, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::time::Duration;
+
+use crate::module::lifi::error::LifiError;
+use crate::module::lifi::keys::{ApiKeyPool, KeyUsage};
+
+/// 公開APIのベースURL（Cloudflareのproxyやローカルのモックに差し替え可能）
+pub const DEFAULT_BASE_URL: &str = "https://li.quest/v1";
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+const DEFAULT_MAX_RETRIES: u32 = 3;
+const DEFAULT_STATUS_POLL_INTERVAL: Duration = Duration::from_secs(10);
+const DEFAULT_MAX_KEY_WAIT: Duration = Duration::from_secs(120);
+
+/// Li.Fiクライアントの設定（config.tomlの[lifi]セクションとLIFI_API_KEYS）
+#[derive(Debug, Clone)]
+pub struct LifiConfig {
+    pub base_url: String,
+    /// `x-lifi-api-key`ヘッダーに付けるキー（空ならキーなしの低いレート制限で呼び出す）
+    pub api_keys: Vec<String>,
+    pub timeout: Duration,
+    /// 429を受けたときに別のキー・待機後に再送する回数
+    pub max_retries: u32,
+    /// クロスチェーン送金の`/status`を問い合わせる間隔
+    pub status_poll_interval: Duration,
+    /// 全てのキーがレート制限中のとき、1リクエストで空きを待つ合計時間の上限
+    pub max_key_wait: Duration,
+}
+
+impl LifiConfig {
+    /// カンマ区切りの`LIFI_API_KEYS`をキー集合に分割する
+    pub fn parse_api_keys(value: &str) -> Vec<String> {
+        value
+            .split(',')
+            .map(str::trim)
+            .filter(|key| !key.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+impl Default for LifiConfig {
+    fn default() -> Self {
+        Self {
+            base_url: DEFAULT_BASE_URL.to_string(),
+            api_keys: Vec::new(),
+            timeout: DEFAULT_TIMEOUT,
+            max_retries: DEFAULT_MAX_RETRIES,
+            status_poll_interval: DEFAULT_STATUS_POLL_INTERVAL,
+            max_key_wait: DEFAULT_MAX_KEY_WAIT,
+        }
+    }
+}
+
+/// Li.Fi APIクライアント
+///
+/// HTTPクライアント（コネクションプール）とヘッダーを一度だけ構築し、
+/// DIコンテナ経由で戦略間で共有する。APIキーはリクエストごとにローテーションする
+pub struct LifiClient {
+    config: LifiConfig,
+    http: reqwest::Client,
+    keys: ApiKeyPool,
+}
+
+impl LifiClient {
+    pub fn new(config: LifiConfig) -> Result<Self, LifiError> {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        let keys = ApiKeyPool::new(&config.api_keys)?;
+
+        let http = reqwest::Client::builder()
+            .default_headers(headers)
+            .timeout(config.timeout)
+            .build()?;
+
+        Ok(Self {
+            config: LifiConfig {
+                base_url: config.base_url.trim_end_matches('/').to_string(),
+                ..config
+            },
+            http,
+            keys,
+        })
+    }
+
+    pub fn config(&self) -> &LifiConfig {
+        &self.config
+    }
+
+    /// APIキーごとのリクエスト数・429回数・残量
+    pub fn key_usage(&self) -> Vec<KeyUsage> {
+        self.keys.usage()
+    }
+
+    /// `<base_url>/<path>`にJSONをPOSTし、成功時のレスポンスを`R`にデコードする
+    pub(crate) async fn post<B, R>(&self, path: &str, body: &B) -> Result<R, LifiError>
+    where
+        B: Serialize,
+        R: DeserializeOwned,
+    {
+        self.send(path, |url| self.http.post(url).json(body)).await
+    }
+
+    /// `<base_url>/<path>`にクエリ付きでGETし、成功時のレスポンスを`R`にデコードする
+    pub(crate) async fn get<Q, R>(&self, path: &str, query: &Q) -> Result<R, LifiError>
+    where
+        Q: Serialize,
+        R: DeserializeOwned,
+    {
+        self.send(path, |url| self.http.get(url).query(query)).await
+    }
+
+    /// `request`で組み立てたリクエストをAPIキーを付けて送る
+    ///
+    /// 429はそのキーをクールダウンさせて次のキーで`max_retries`回まで再送し、
+    /// 全てのキーが使えないときは最初に使えるようになるまで待つ
+    async fn send<F, R>(&self, path: &str, request: F) -> Result<R, LifiError>
+    where
+        F: Fn(String) -> RequestBuilder,
+        R: DeserializeOwned,
+    {
+        let mut rate_limited = 0;
+        let mut waited = Duration::ZERO;
+        let (status, json_text) = loop {
+            let key = match self.keys.acquire() {
+                Ok(key) => key,
+                Err(wait) if waited + wait > self.config.max_key_wait => {
+                    return Err(LifiError::RateLimited {
+                        message: format!("every API key is rate limited for another {:?}", wait),
+                    });
+                }
+                Err(wait) => {
+                    println!("Li.Fi API keys are rate limited, waiting {:?}", wait);
+                    tokio::time::sleep(wait).await;
+                    waited += wait;
+                    continue;
+                }
+            };
+
+            let mut builder = request(format!("{}/{}", self.config.base_url, path));
+            if let Some(header) = &key.header {
+                builder = builder.header("x-lifi-api-key", header.clone());
+            }
+            let response = builder.send().await?;
+
+            let status = response.status();
+            if status == StatusCode::TOO_MANY_REQUESTS {
+                let cooldown = key.record_rate_limited(response.headers());
+                rate_limited += 1;
+                println!("Li.Fi {} rate limited (attempt {}), key cooling down for {:?}", path, rate_limited, cooldown);
+                if rate_limited > self.config.max_retries {
+                    let json_text = response.text().await.unwrap_or_default();
+                    break (status, json_text);
+                }
+                continue;
+            }
+            key.record_response(response.headers());
+            break (status, response.text().await?);
+        };
+
+        if !status.is_success() {
+            println!("Li.Fi {} error (status {}): {}", path, status, json_text);
+            return Err(LifiError::from_response(status.as_u16(), &json_text));
+        }
+        println!("Li.Fi {} response: {} ({} bytes)", path, status, json_text.len());
+
+        serde_json::from_str::<R>(&json_text).map_err(|e| {
+            println!("Li.Fi {} JSON parse error: {}", path, e);
+            LifiError::Decode(e)
+        })
+    }
+}
@@ -0,0 +1,258 @@
+use serde::Deserialize;
+use serde_json::Value;
+use std::error::Error;
+use std::fmt;
+use std::time::Duration;
+
+/// ツール側の問題とみなす`unavailableRoutes.failed`のコード（流動性や金額の問題ではない）
+const TOOL_FAULT_CODES: [&str; 4] = ["TOOL_TIMEOUT", "TOOL_SPECIFIC_ERROR", "UNKNOWN_ERROR", "RPC_ERROR"];
+
+/// `unavailableRoutes.failed`（またはエラー応答の`errors.failed`）の1件
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolFailure {
+    pub tool: String,
+    /// `INSUFFICIENT_LIQUIDITY`、`NO_POSSIBLE_ROUTE`、`TOOL_TIMEOUT`など
+    pub code: String,
+    pub message: String,
+}
+
+impl ToolFailure {
+    /// ペアではなくツール自体の不具合による失敗か（ブラックリスト候補）
+    pub fn is_tool_fault(&self) -> bool {
+        TOOL_FAULT_CODES.contains(&self.code.as_str())
+    }
+}
+
+/// Li.Fiモジュールのエラー
+///
+/// 戦略側はリトライ（[`is_retryable`](Self::is_retryable)）・ペアのスキップ
+/// （[`skips_pair`](Self::skips_pair)）・ツールの除外（[`failed_tools`](Self::failed_tools)）を
+/// 判断でき、[`kind`](Self::kind)で原因別に集計できる
+#[derive(Debug)]
+pub enum LifiError {
+    /// HTTPリクエストを送れなかった、または応答を読めなかった
+    Transport(reqwest::Error),
+    /// 応答が想定した形ではなかった
+    Decode(serde_json::Error),
+    /// 429（またはコード1005）で、再送しても解消しなかった
+    RateLimited { message: String },
+    /// ルートが見つからない（コード1002、または`routes`が空）
+    NoRoute { message: String, failures: Vec<ToolFailure> },
+    /// ルートはあるが流動性が足りない
+    InsufficientLiquidity { message: String, failures: Vec<ToolFailure> },
+    /// 存在しない・サポートされていないトークン
+    InvalidToken { message: String },
+    /// スリッページ（価格変動）でクォートが無効になった（コード1007）
+    Slippage { message: String },
+    /// ブリッジ・DEXなど外部ツールのエラー（コード1001/1008）
+    Tool { tool: Option<String>, message: String },
+    /// その他のAPIエラー
+    Api { status: u16, code: Option<u32>, message: String },
+    /// 呼び出し側の指定が不正（存在しないルートIDなど）
+    InvalidRequest(String),
+    /// クロスチェーン送金が待ち時間内にDONE/FAILEDにならなかった（結果は不明）
+    TransferTimeout {
+        tx_hash: String,
+        bridge: String,
+        waited: Duration,
+        last_status: Option<String>,
+    },
+}
+
+/// Li.Fiのエラー応答（`{"message": ..., "code": 1002, "errors": {...}}`）
+#[derive(Deserialize, Default)]
+struct ErrorBody {
+    #[serde(default)]
+    message: String,
+    code: Option<u32>,
+    #[serde(default)]
+    errors: Option<Value>,
+    #[serde(default)]
+    tool: Option<String>,
+}
+
+impl LifiError {
+    /// 非2xx応答のステータスと本文から分類する
+    pub fn from_response(status: u16, body: &str) -> Self {
+        let parsed: ErrorBody = serde_json::from_str(body).unwrap_or_default();
+        let message = if parsed.message.is_empty() {
+            body.to_string()
+        } else {
+            parsed.message
+        };
+        let failures = parsed
+            .errors
+            .as_ref()
+            .and_then(|errors| errors.get("failed"))
+            .and_then(|failed| failed.as_array())
+            .map(|failed| tool_failures(failed))
+            .unwrap_or_default();
+
+        match parsed.code {
+            _ if status == 429 => LifiError::RateLimited { message },
+            Some(1005) => LifiError::RateLimited { message },
+            Some(1002) => Self::no_route(message, failures),
+            Some(1007) => LifiError::Slippage { message },
+            Some(1001) | Some(1008) => LifiError::Tool {
+                tool: parsed.tool.or_else(|| failures.first().map(|failure| failure.tool.clone())),
+                message,
+            },
+            Some(1003) | Some(1011) if message.to_lowercase().contains("token") => LifiError::InvalidToken { message },
+            code => LifiError::Api { status, code, message },
+        }
+    }
+
+    /// ルート検索の結果が空のとき、`unavailableRoutes.failed`から原因を分類する
+    pub fn from_unavailable(failed: &[Value]) -> Self {
+        Self::no_route("No routes found".to_string(), tool_failures(failed))
+    }
+
+    fn no_route(message: String, failures: Vec<ToolFailure>) -> Self {
+        if failures.iter().any(|failure| failure.code == "INSUFFICIENT_LIQUIDITY") {
+            LifiError::InsufficientLiquidity { message, failures }
+        } else {
+            LifiError::NoRoute { message, failures }
+        }
+    }
+
+    /// 集計用の原因ラベル
+    pub fn kind(&self) -> &'static str {
+        match self {
+            LifiError::Transport(_) => "transport",
+            LifiError::Decode(_) => "decode",
+            LifiError::RateLimited { .. } => "rate_limited",
+            LifiError::NoRoute { .. } => "no_route",
+            LifiError::InsufficientLiquidity { .. } => "insufficient_liquidity",
+            LifiError::InvalidToken { .. } => "invalid_token",
+            LifiError::Slippage { .. } => "slippage",
+            LifiError::Tool { .. } => "tool",
+            LifiError::Api { .. } => "api",
+            LifiError::InvalidRequest(_) => "invalid_request",
+            LifiError::TransferTimeout { .. } => "transfer_timeout",
+        }
+    }
+
+    /// 時間をおいて同じリクエストを再送すれば成功しうるか
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            LifiError::Transport(_) | LifiError::RateLimited { .. } | LifiError::Slippage { .. } => true,
+            // 1006: サーバーエラー、1009: タイムアウト、1012: RPCエラー
+            LifiError::Api { status, code, .. } => *status >= 500 || matches!(code, Some(1006 | 1009 | 1012)),
+            _ => false,
+        }
+    }
+
+    /// このトークンペア（金額）は今は組めないので、次のペアに進むべきか
+    pub fn skips_pair(&self) -> bool {
+        matches!(
+            self,
+            LifiError::NoRoute { .. } | LifiError::InsufficientLiquidity { .. } | LifiError::InvalidToken { .. }
+        )
+    }
+
+    /// ツール自体の不具合で失敗したツール（以降のルート検索から除外する候補）
+    pub fn failed_tools(&self) -> Vec<&str> {
+        let mut tools: Vec<&str> = match self {
+            LifiError::Tool { tool: Some(tool), .. } => vec![tool.as_str()],
+            LifiError::NoRoute { failures, .. } | LifiError::InsufficientLiquidity { failures, .. } => failures
+                .iter()
+                .filter(|failure| failure.is_tool_fault())
+                .map(|failure| failure.tool.as_str())
+                .collect(),
+            _ => Vec::new(),
+        };
+        tools.sort_unstable();
+        tools.dedup();
+        tools
+    }
+}
+
+/// `failed`の各要素の`subpaths`に入っているツールごとの失敗を平らにする
+///
+/// 形式: `[{"overallPath": "...", "subpaths": {"<path>": [{"tool", "code", "message", ...}]}}]`
+fn tool_failures(failed: &[Value]) -> Vec<ToolFailure> {
+    failed
+        .iter()
+        .filter_map(|route| route.get("subpaths")?.as_object())
+        .flat_map(|subpaths| subpaths.values())
+        .filter_map(|errors| errors.as_array())
+        .flatten()
+        .map(|error| {
+            let field = |name: &str| error.get(name).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+            ToolFailure {
+                tool: field("tool"),
+                code: field("code"),
+                message: field("message"),
+            }
+        })
+        .collect()
+}
+
+impl fmt::Display for LifiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LifiError::Transport(e) => write!(f, "Li.Fi transport error: {}", e),
+            LifiError::Decode(e) => write!(f, "Li.Fi decode error: {}", e),
+            LifiError::RateLimited { message } => write!(f, "Li.Fi rate limited: {}", message),
+            LifiError::NoRoute { message, failures } => {
+                write!(f, "Li.Fi found no route: {}", message)?;
+                write_failures(f, failures)
+            }
+            LifiError::InsufficientLiquidity { message, failures } => {
+                write!(f, "Li.Fi found insufficient liquidity: {}", message)?;
+                write_failures(f, failures)
+            }
+            LifiError::InvalidToken { message } => write!(f, "Li.Fi rejected the token: {}", message),
+            LifiError::Slippage { message } => write!(f, "Li.Fi quote exceeded slippage: {}", message),
+            LifiError::Tool { tool: Some(tool), message } => write!(f, "Li.Fi tool {} failed: {}", tool, message),
+            LifiError::Tool { tool: None, message } => write!(f, "Li.Fi tool failed: {}", message),
+            LifiError::Api { status, code, message } => match code {
+                Some(code) => write!(f, "Li.Fi API error {} (code {}): {}", status, code, message),
+                None => write!(f, "Li.Fi API error {}: {}", status, message),
+            },
+            LifiError::InvalidRequest(message) => write!(f, "Invalid Li.Fi request: {}", message),
+            LifiError::TransferTimeout {
+                tx_hash,
+                bridge,
+                waited,
+                last_status,
+            } => write!(
+                f,
+                "Li.Fi transfer {} via {} did not finish within {:?} (last status: {})",
+                tx_hash,
+                bridge,
+                waited,
+                last_status.as_deref().unwrap_or("unknown")
+            ),
+        }
+    }
+}
+
+fn write_failures(f: &mut fmt::Formatter<'_>, failures: &[ToolFailure]) -> fmt::Result {
+    for failure in failures {
+        write!(f, "; {} {}: {}", failure.tool, failure.code, failure.message)?;
+    }
+    Ok(())
+}
+
+impl Error for LifiError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            LifiError::Transport(e) => Some(e),
+            LifiError::Decode(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for LifiError {
+    fn from(e: reqwest::Error) -> Self {
+        LifiError::Transport(e)
+    }
+}
+
+impl From<serde_json::Error> for LifiError {
+    fn from(e: serde_json::Error) -> Self {
+        LifiError::Decode(e)
+    }
+}
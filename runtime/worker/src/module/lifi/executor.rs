@@ -0,0 +1,498 @@
+// マルチステップルートの実行（スワップ→ブリッジなど、全ステップを順に送信する）
+use std::error::Error;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use alloy_primitives::{Address, B256, U256};
+use tokio::sync::broadcast::error::TryRecvError;
+use tokio::sync::broadcast::Receiver;
+
+use crate::chain::rpc::TransactionReceipt;
+use crate::chain::tx::{recheck_receipt, send_transaction, simulate, OutputCheck, SendOptions, TxError, TypedTransaction};
+use crate::chain::{BlockEvent, BlockTag, Chain, ChainSet};
+use crate::module::erc20::{self, ApprovalMode};
+use crate::module::lifi::build_tx::TransactionResponse;
+use crate::module::lifi::client::LifiClient;
+use crate::module::lifi::error::LifiError;
+use crate::module::lifi::find_routes::{LifiRoute, RouteStep};
+use crate::module::lifi::status::{transfer_timeout, StatusRequest};
+use crate::module::lifi::progress::{CompletedStep, PendingStep, RouteProgress, RouteStatus, RouteStore};
+
+/// 送信したステップの着金確認の結果
+enum Settlement {
+    /// 受け取った（`tx_hash`は`/status`で分かった送金先チェーンのトランザクション）
+    Arrived { received: U256, tx_hash: Option<B256> },
+    /// 送金は終わったが予定どおりには受け取れなかった（送り直してはいけない）
+    Failed(String),
+}
+
+/// ルートの全ステップを順に実行する
+///
+/// 各ステップは前のステップの実際の受取量で`stepTransaction`を呼び直し、
+/// ステップごとにapproveを確認してから送信する。進捗は[`RouteStore`]に保存し、
+/// 中断したルートは[`resume_pending`](Self::resume_pending)で続きから再開する
+pub struct RouteExecutor {
+    lifi: Arc<LifiClient>,
+    chains: Arc<ChainSet>,
+    store: RouteStore,
+}
+
+impl RouteExecutor {
+    pub fn new(lifi: Arc<LifiClient>, chains: Arc<ChainSet>, store: RouteStore) -> Self {
+        Self { lifi, chains, store }
+    }
+
+    pub fn store(&self) -> &RouteStore {
+        &self.store
+    }
+
+    /// `from`のウォレットでルートを実行する。途中まで実行済みのルートなら続きから
+    pub async fn execute(&self, route: &LifiRoute, from: Address) -> Result<RouteProgress, Box<dyn Error>> {
+        let progress = match self.store.load(&route.id)? {
+            Some(progress) if progress.is_finished() => {
+                return Err(format!("Route {} was already executed ({:?})", route.id, progress.status).into());
+            }
+            Some(progress) => progress,
+            None => {
+                if route.steps.is_empty() {
+                    return Err(format!("Route {} has no steps", route.id).into());
+                }
+                let mut progress = RouteProgress::new(route, from);
+                self.store.save(&mut progress)?;
+                progress
+            }
+        };
+        self.run(progress).await
+    }
+
+    /// 保存されている実行途中のルートを再開する（失敗したルートはログに出して次へ進む）
+    pub async fn resume_pending(&self) -> Result<Vec<RouteProgress>, Box<dyn Error>> {
+        let mut resumed = Vec::new();
+        for progress in self.store.in_progress()? {
+            println!(
+                "Resuming route {} at step {}/{}",
+                progress.route_id,
+                progress.next_index() + 1,
+                progress.steps.len()
+            );
+            let route_id = progress.route_id.clone();
+            match self.run(progress).await {
+                Ok(progress) => resumed.push(progress),
+                Err(e) => println!("Failed to resume route {}: {}", route_id, e),
+            }
+        }
+        Ok(resumed)
+    }
+
+    async fn run(&self, mut progress: RouteProgress) -> Result<RouteProgress, Box<dyn Error>> {
+        // 前回送信したまま結果を確認できなかったステップは、送り直さずに着金を確認する
+        if let Some(pending) = progress.pending.clone() {
+            self.settle_pending(&mut progress, &pending).await?;
+        }
+
+        while progress.next_index() < progress.steps.len() {
+            let index = progress.next_index();
+            if let Err(e) = self.execute_step(&mut progress, index).await {
+                progress.last_error = Some(e.to_string());
+                self.store.save(&mut progress)?;
+                return Err(format!(
+                    "Route {} step {}/{} failed: {}",
+                    progress.route_id,
+                    index + 1,
+                    progress.steps.len(),
+                    e
+                )
+                .into());
+            }
+        }
+
+        progress.status = RouteStatus::Completed;
+        progress.last_error = None;
+        self.store.save(&mut progress)?;
+        println!(
+            "Route {} completed ({} steps, received {})",
+            progress.route_id,
+            progress.steps.len(),
+            progress.next_from_amount().unwrap_or_default()
+        );
+        Ok(progress)
+    }
+
+    /// 送信済みステップの着金を確認する。確認できなければ二重送信を避けるためルートを失敗扱いにする
+    async fn settle_pending(&self, progress: &mut RouteProgress, pending: &PendingStep) -> Result<(), Box<dyn Error>> {
+        let step = progress.steps[pending.index].clone();
+        println!(
+            "Checking arrival of step {}/{} ({}) sent before the restart",
+            pending.index + 1,
+            progress.steps.len(),
+            step.tool
+        );
+
+        let reason = match self.settle(&step, pending).await {
+            Ok(Settlement::Arrived { received, tx_hash }) => {
+                progress.completed.push(CompletedStep {
+                    step_id: step.id.clone(),
+                    tool: step.tool.clone(),
+                    chain_id: pending.chain_id,
+                    tx_hash: pending.tx_hash,
+                    received,
+                    destination_tx_hash: tx_hash,
+                });
+                progress.pending = None;
+                self.store.save(progress)?;
+                return Ok(());
+            }
+            Ok(Settlement::Failed(reason)) => {
+                progress.pending = None;
+                reason
+            }
+            Err(e) => format!(
+                "outcome of step {} ({}) is unknown, check it manually: {}",
+                pending.index + 1,
+                step.tool,
+                e
+            ),
+        };
+        progress.status = RouteStatus::Failed(reason.clone());
+        self.store.save(progress)?;
+        Err(reason.into())
+    }
+
+    /// 送信したステップの着金を確認する
+    ///
+    /// クロスチェーンで送金元のトランザクションが分かっていれば`/status`で追跡し、
+    /// COMPLETED以外で終わった送金（PARTIAL・REFUNDED・FAILED）は[`Settlement::Failed`]を返す。
+    /// 同一チェーン・`/status`が使えない場合は受取人の残高の増加で確認する
+    async fn settle(&self, step: &RouteStep, pending: &PendingStep) -> Result<Settlement, Box<dyn Error>> {
+        let chain = self.chains.require(pending.chain_id)?;
+        let timeout = Duration::from_secs(pending.timeout_secs);
+        let arrival = || {
+            wait_for_arrival(
+                chain.as_ref(),
+                pending.token,
+                pending.recipient,
+                pending.balance_before,
+                pending.min_amount,
+                timeout,
+            )
+        };
+
+        let is_cross_chain = step.action.from_chain_id != step.action.to_chain_id;
+        let tx_hash = match pending.tx_hash {
+            Some(tx_hash) if is_cross_chain => tx_hash,
+            _ => {
+                let received = arrival().await?;
+                return Ok(Settlement::Arrived { received, tx_hash: None });
+            }
+        };
+
+        let request = StatusRequest::new(tx_hash, &step.tool, step.action.from_chain_id, step.action.to_chain_id);
+        let status = match self.lifi.wait_for_transfer(&request, timeout).await {
+            Ok(status) => status,
+            Err(e @ LifiError::TransferTimeout { .. }) => return Err(e.into()),
+            Err(e) => {
+                println!("Li.Fi status unavailable ({}), checking the balance on {} instead", e, chain.config().name);
+                let received = arrival().await?;
+                return Ok(Settlement::Arrived { received, tx_hash: None });
+            }
+        };
+        println!(
+            "[{}] Transfer {:#x} via {}: {}",
+            chain.config().name,
+            tx_hash,
+            step.tool,
+            status.describe()
+        );
+
+        if !status.is_completed() {
+            let mut reason = format!("transfer {:#x} via {} ended {}", tx_hash, step.tool, status.describe());
+            if let Some(receiving) = &status.receiving {
+                // PARTIALは別のトークンで受け取っているので、何をどれだけ受け取ったかを残す
+                let symbol = receiving.token.as_ref().map_or("?", |token| token.symbol.as_str());
+                let amount = receiving.amount.as_deref().unwrap_or("?");
+                reason.push_str(&format!(" (received {} {})", amount, symbol));
+            }
+            if let Some(link) = &status.lifi_explorer_link {
+                reason.push_str(&format!(", see {}", link));
+            }
+            return Ok(Settlement::Failed(reason));
+        }
+
+        let received = match status.received_amount() {
+            Some(received) => received,
+            None => {
+                let balance = erc20::token_balance_quorum(chain.as_ref(), pending.token, pending.recipient).await?;
+                balance.saturating_sub(pending.balance_before)
+            }
+        };
+        Ok(Settlement::Arrived {
+            received,
+            tx_hash: status.receiving_tx_hash(),
+        })
+    }
+
+    async fn execute_step(&self, progress: &mut RouteProgress, index: usize) -> Result<(), Box<dyn Error>> {
+        // 署名は送金元チェーン、着金確認は送金先チェーンで行う
+        let mut step = progress.steps[index].clone();
+        let source = self.chains.require(step.action.from_chain_id)?;
+        let destination = self.chains.require(step.action.to_chain_id)?;
+        let is_cross_chain = source.config().chain_id != destination.config().chain_id;
+        let signer = source.signer(progress.from_address).ok_or_else(|| {
+            format!(
+                "{:#x} is not a signing account of {}",
+                progress.from_address,
+                source.config().name
+            )
+        })?;
+
+        // 前のステップで実際に受け取った数量をこのステップで送る
+        if let Some(amount) = progress.next_from_amount() {
+            step.action.from_amount = amount.to_string();
+        }
+        println!(
+            "[{}] Step {}/{}: {} {} -> {} using {}",
+            source.config().name,
+            index + 1,
+            progress.steps.len(),
+            step.action.from_amount,
+            step.action.from_token.symbol,
+            step.action.to_token.symbol,
+            step.tool
+        );
+
+        let response = self.lifi.step_transaction(&step, signer.address()).await?;
+        response.verify_for(source.as_ref(), signer.address())?;
+        let recipient = recipient(&response, signer.address())?;
+        let to_token: Address = response.action.to_token.address.parse()?;
+
+        // allowanceが足りなければapproveを先に確定させる（不足のままだとスワップがリバートする）
+        let from_token: Address = response.action.from_token.address.parse()?;
+        if !erc20::is_native(from_token) {
+            let spender: Address = response.estimate.approval_address.parse()?;
+            let required: U256 = response.action.from_amount.parse()?;
+            let mode = ApprovalMode::from_infinite_approval(response.action.infinite_approval);
+            erc20::ensure_allowance(source.as_ref(), signer.as_ref(), from_token, spender, required, mode).await?;
+        }
+
+        // approveのガス代が受取量に混ざらないよう、approveの後の残高を基準にする
+        let balance_before = erc20::token_balance_quorum(destination.as_ref(), to_token, recipient).await?;
+        let min_amount: U256 = response.estimate.to_amount_min.parse()?;
+        // ブリッジの想定所要時間から着金を待つ時間を決める
+        let timeout = transfer_timeout(response.estimate.execution_duration);
+
+        let nonce = source.nonces().reserve(source.as_ref(), signer.address()).await?;
+        let typed_tx = match prepare_transaction(source.as_ref(), &response, signer.address(), nonce).await {
+            Ok(tx) => tx,
+            Err(e) => {
+                source.nonces().release(signer.address(), nonce);
+                return Err(e);
+            }
+        };
+
+        // 送信後に落ちても再開時に送り直さないよう、送信の直前に記録する
+        progress.pending = Some(PendingStep {
+            index,
+            chain_id: destination.config().chain_id,
+            token: to_token,
+            recipient,
+            balance_before,
+            min_amount,
+            timeout_secs: timeout.as_secs(),
+            tx_hash: None,
+        });
+        progress.last_error = None;
+        self.store.save(progress)?;
+
+        // 未採掘のまま30秒経過したら手数料を上げて再送
+        let options = SendOptions {
+            replace_after: Some(Duration::from_secs(30)),
+            ..SendOptions::default()
+        };
+        // 確定後のreorgでブロックが外れていないか確認するため、送信前から購読する
+        let mut block_events = source.blocks().subscribe();
+        let receipt = match send_transaction(source.as_ref(), signer.as_ref(), &typed_tx, &options).await {
+            Ok(receipt) => receipt,
+            Err(e) => {
+                // リバートなら資金は動いていないので、次回このステップからやり直せる
+                if matches!(e, TxError::Reverted { .. }) {
+                    progress.pending = None;
+                    self.store.save(progress)?;
+                }
+                return Err(e.into());
+            }
+        };
+        println!(
+            "[{}] Transaction {:#x} confirmed in block {} (gas used: {})",
+            source.config().name,
+            receipt.transaction_hash,
+            receipt.block_number.unwrap_or_default(),
+            receipt.gas_used
+        );
+        let mut receipt = recheck_if_orphaned(source.as_ref(), receipt, &mut block_events, &options).await?;
+
+        let (received, destination_tx_hash) = if is_cross_chain {
+            // 確定したトランザクションを記録し、中断しても再開時に/statusで追跡できるようにする
+            let mut pending = progress.pending.clone().ok_or("pending step was not recorded")?;
+            pending.tx_hash = Some(receipt.transaction_hash);
+            progress.pending = Some(pending.clone());
+            self.store.save(progress)?;
+
+            let settlement = self.settle(&step, &pending).await?;
+            // 着金を待つ間に送金元のブロックが外れていないか確認する
+            receipt = recheck_if_orphaned(source.as_ref(), receipt, &mut block_events, &options).await?;
+            match settlement {
+                Settlement::Arrived { received, tx_hash } => (received, tx_hash),
+                Settlement::Failed(reason) => {
+                    progress.pending = None;
+                    progress.status = RouteStatus::Failed(reason.clone());
+                    return Err(reason.into());
+                }
+            }
+        } else {
+            let balance = erc20::token_balance_quorum(destination.as_ref(), to_token, recipient).await?;
+            let mut received = balance.saturating_sub(balance_before);
+            // ネイティブトークンを自分で受け取った場合は、払ったガス代の分を足し戻す
+            if erc20::is_native(to_token) && recipient == signer.address() {
+                let gas_price = receipt.effective_gas_price.unwrap_or_default();
+                received += U256::from(receipt.gas_used.to::<u64>()) * gas_price;
+            }
+            (received, None)
+        };
+        println!(
+            "[{}] Received {} of {} at {:#x}",
+            destination.config().name,
+            received,
+            response.action.to_token.symbol,
+            recipient
+        );
+
+        progress.completed.push(CompletedStep {
+            step_id: step.id.clone(),
+            tool: step.tool.clone(),
+            chain_id: destination.config().chain_id,
+            tx_hash: Some(receipt.transaction_hash),
+            received,
+            destination_tx_hash,
+        });
+        progress.pending = None;
+        self.store.save(progress)?;
+        Ok(())
+    }
+}
+
+/// `receipt`のブロックがreorgで外れていれば、新しいフォークに取り込まれるまで待ち直す
+///
+/// ブロック監視を最新にしてから`events`を確認する。イベントを取りこぼした場合も確認し直す
+async fn recheck_if_orphaned(
+    chain: &dyn Chain,
+    receipt: TransactionReceipt,
+    events: &mut Receiver<BlockEvent>,
+    options: &SendOptions,
+) -> Result<TransactionReceipt, TxError> {
+    if let Err(e) = chain.blocks().poll(chain.rpc()).await {
+        println!("[{}] Block poll failed: {}", chain.config().name, e);
+    }
+    let mut orphaned = false;
+    loop {
+        match events.try_recv() {
+            Ok(event) => orphaned |= event.orphans(&receipt),
+            Err(TryRecvError::Lagged(_)) => orphaned = true,
+            Err(TryRecvError::Empty) | Err(TryRecvError::Closed) => break,
+        }
+    }
+    if !orphaned {
+        return Ok(receipt);
+    }
+    let receipt = recheck_receipt(chain, &receipt, options).await?;
+    println!(
+        "[{}] Transaction {:#x} re-included in block {}",
+        chain.config().name,
+        receipt.transaction_hash,
+        receipt.block_number.unwrap_or_default()
+    );
+    Ok(receipt)
+}
+
+/// Li.Fiの提示手数料をチェーンのfee oracleで補正し、送信前にシミュレーションする
+async fn prepare_transaction(
+    chain: &dyn Chain,
+    response: &TransactionResponse,
+    from: Address,
+    nonce: u64,
+) -> Result<TypedTransaction, Box<dyn Error>> {
+    let mut typed_tx = response.transaction_request.to_typed_transaction(nonce)?;
+    typed_tx.fees = chain.fee_oracle().adjust(chain.rpc(), &typed_tx.fees).await?;
+
+    // 受取トークンの増加量がtoAmountMin未満ならリバート扱いで送信しない
+    // （クロスチェーンの場合は送金元チェーンでは受け取らないのでリバート確認のみ）
+    let output = if response.action.from_chain_id == response.action.to_chain_id {
+        Some(OutputCheck {
+            token: response.action.to_token.address.parse()?,
+            recipient: recipient(response, from)?,
+            min_amount: response.estimate.to_amount_min.parse()?,
+            allow_unchecked: chain.config().allow_unchecked_output,
+        })
+    } else {
+        None
+    };
+    let simulation = simulate(
+        chain.rpc(),
+        &typed_tx.to_call_request(from),
+        BlockTag::Pending,
+        output.as_ref(),
+    )
+    .await?;
+    println!(
+        "Simulation succeeded (gas estimate: {}, output: {})",
+        simulation.gas_estimate,
+        simulation
+            .output_delta
+            .map(|delta| delta.to_string())
+            .unwrap_or_else(|| "not checked".to_string())
+    );
+
+    Ok(typed_tx)
+}
+
+/// toAddressが指定されていなければ送信者自身が受取人
+fn recipient(response: &TransactionResponse, from: Address) -> Result<Address, Box<dyn Error>> {
+    match &response.action.to_address {
+        Some(to_address) => Ok(to_address.parse()?),
+        None => Ok(from),
+    }
+}
+
+/// 送金先チェーンで受取人の残高がmin_amount以上増えるまでポーリングする
+async fn wait_for_arrival(
+    chain: &dyn Chain,
+    token: Address,
+    recipient: Address,
+    balance_before: U256,
+    min_amount: U256,
+    timeout: Duration,
+) -> Result<U256, Box<dyn Error>> {
+    let deadline = Instant::now() + timeout;
+    let poll_interval = chain.config().block_time.max(Duration::from_secs(5));
+
+    loop {
+        match erc20::token_balance_quorum(chain, token, recipient).await {
+            Ok(balance) => {
+                let received = balance.saturating_sub(balance_before);
+                if received >= min_amount {
+                    return Ok(received);
+                }
+            }
+            Err(e) => println!("[{}] Balance poll failed: {}", chain.config().name, e),
+        }
+
+        if Instant::now() >= deadline {
+            return Err(format!(
+                "Funds did not arrive on {} within {}s",
+                chain.config().name,
+                timeout.as_secs()
+            )
+            .into());
+        }
+        tokio::time::sleep(poll_interval).await;
+    }
+}
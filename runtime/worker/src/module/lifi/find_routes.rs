@@ -1,8 +1,7 @@
-use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
 use serde::{Deserialize, Serialize};
-use std::env;
-use std::error::Error;
-use dotenv::dotenv;
+
+use crate::module::lifi::client::LifiClient;
+use crate::module::lifi::error::LifiError;
 
 // Li.Fi APIのリクエストボディ用構造体
 #[derive(Debug)]
@@ -31,6 +30,9 @@ pub struct LifiRouteOptions {
 #[derive(Serialize, Debug)]
 pub struct LifiFilterOptions {
     pub allow: Vec<String>,
+    /// 除外するツール（ツール側の不具合で失敗したブリッジ・DEX）
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub deny: Vec<String>,
 }
 
 // Li.FiのAPIレスポンス用構造体 (必要に応じて拡張)
@@ -272,7 +274,7 @@ impl Serialize for LifiRouteOptions {
 }
 
 // ルート検索のパラメータ構造体
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct RouteSearchParams {
     pub from_chain_id: u64,
     pub from_amount: String,
@@ -285,6 +287,8 @@ pub struct RouteSearchParams {
     pub fee: Option<f64>,
     pub order: Option<String>,
     pub max_price_impact: Option<f64>,
+    /// 検索から除外するツールのキー
+    pub deny_tools: Vec<String>,
 }
 
 impl Default for RouteSearchParams {
@@ -301,71 +305,46 @@ impl Default for RouteSearchParams {
             fee: None,
             order: None,
             max_price_impact: None,
+            deny_tools: Vec::new(),
         }
     }
 }
 
-pub async fn find_routes(params: RouteSearchParams) -> Result<LifiRouteResponse, Box<dyn Error>> {
-    // .envファイルを読み込む
-    dotenv().ok();
-    
-    // 環境変数からAPIキーを取得
-    let api_key = env::var("LIFI_API_KEYS").expect("LIFI_API_KEYS must be set in .env file");
-    
-    // ヘッダーを設定
-    let mut headers = HeaderMap::new();
-    headers.insert("x-lifi-api-key", HeaderValue::from_str(&api_key)?);
-    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
-    
-    // リクエストボディを構築
-    let request_body = LifiRouteRequest {
-        from_chain_id: params.from_chain_id,
-        from_amount: params.from_amount,
-        from_token_address: params.from_token_address,
-        to_chain_id: params.to_chain_id,
-        to_token_address: params.to_token_address,
-        options: LifiRouteOptions {
-            integrator: params.integrator.unwrap_or_else(|| "fee-demo".to_string()),
-            referrer: params.referrer.unwrap_or_else(|| "0x552008c0f6870c2f77e5cC1d2eb9bdff03e30Ea0".to_string()),
-            slippage: params.slippage,
-            fee: params.fee.unwrap_or(0.0),
-            bridges: LifiFilterOptions {
-                allow: vec!["relay".to_string()],
-            },
-            exchanges: LifiFilterOptions {
-                allow: vec!["1inch".to_string(), "openocean".to_string()],
+impl LifiClient {
+    /// `/advanced/routes`でルートを検索する
+    ///
+    /// ルートが1つもなければ`unavailableRoutes.failed`から原因を分類してエラーにする
+    pub async fn find_routes(&self, params: RouteSearchParams) -> Result<LifiRouteResponse, LifiError> {
+        // リクエストボディを構築
+        let request_body = LifiRouteRequest {
+            from_chain_id: params.from_chain_id,
+            from_amount: params.from_amount,
+            from_token_address: params.from_token_address,
+            to_chain_id: params.to_chain_id,
+            to_token_address: params.to_token_address,
+            options: LifiRouteOptions {
+                integrator: params.integrator.unwrap_or_else(|| "fee-demo".to_string()),
+                referrer: params.referrer.unwrap_or_else(|| "0x552008c0f6870c2f77e5cC1d2eb9bdff03e30Ea0".to_string()),
+                slippage: params.slippage,
+                fee: params.fee.unwrap_or(0.0),
+                bridges: LifiFilterOptions {
+                    allow: vec!["relay".to_string()],
+                    deny: params.deny_tools.clone(),
+                },
+                exchanges: LifiFilterOptions {
+                    allow: vec!["1inch".to_string(), "openocean".to_string()],
+                    deny: params.deny_tools,
+                },
+                allow_switch_chain: true,
+                order: params.order.unwrap_or_else(|| "CHEAPEST".to_string()),
+                max_price_impact: params.max_price_impact.unwrap_or(0.1),
             },
-            allow_switch_chain: true,
-            order: params.order.unwrap_or_else(|| "CHEAPEST".to_string()),
-            max_price_impact: params.max_price_impact.unwrap_or(0.1),
-        },
-    };
-    
-    // APIエンドポイントにPOSTリクエストを送信
-    let client = reqwest::Client::new();
-    let response = client
-        .post("https://li.quest/v1/advanced/routes")
-        .headers(headers)
-        .json(&request_body)
-        .send()
-        .await?;
-    
-    // レスポンスを処理
-    if response.status().is_success() {
-        // まずJSONテキストを取得
-        let json_text = response.text().await?;
-        println!("API Response: {}", json_text);
-        
-        // JSONテキストをパース
-        match serde_json::from_str::<LifiRouteResponse>(&json_text) {
-            Ok(routes_response) => Ok(routes_response),
-            Err(e) => {
-                println!("JSON parse error: {}", e);
-                Err(format!("Failed to parse API response: {}", e).into())
-            }
+        };
+
+        let response: LifiRouteResponse = self.post("advanced/routes", &request_body).await?;
+        if response.routes.is_empty() {
+            return Err(LifiError::from_unavailable(&response.unavailable_routes.failed));
         }
-    } else {
-        let error_text = response.text().await?;
-        Err(format!("API request failed: {}", error_text).into())
+        Ok(response)
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,209 @@
+// Li.Fi APIキーのローテーションとレート制限の管理
+use rand::Rng;
+use reqwest::header::{HeaderMap, HeaderValue, RETRY_AFTER};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::module::lifi::error::LifiError;
+
+/// 429の初回クールダウン（連続するたびに倍にする）
+const BASE_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+/// これ以上の`RateLimit-Reset`は残り秒数ではなくUNIX時刻とみなす
+const EPOCH_RESET_THRESHOLD: u64 = 1_000_000_000;
+
+/// キーごとの利用状況（ログ・診断用）
+#[derive(Debug, Clone)]
+pub struct KeyUsage {
+    /// 先頭4文字だけ残したキー（キーなしは`anonymous`）
+    pub key: String,
+    pub requests: u64,
+    pub rate_limited: u64,
+    /// 直近のレスポンスの`RateLimit-Remaining`から、その後の送信数を引いた残量
+    pub remaining: Option<u64>,
+    pub cooling_down: bool,
+}
+
+#[derive(Debug, Default)]
+struct KeyState {
+    requests: u64,
+    rate_limited: u64,
+    consecutive_rate_limits: u32,
+    remaining: Option<u64>,
+    reset_at: Option<Instant>,
+    cooldown_until: Option<Instant>,
+}
+
+#[derive(Debug)]
+pub(crate) struct ApiKey {
+    /// `x-lifi-api-key`ヘッダーの値（`None`はキーなしで呼び出す）
+    pub(crate) header: Option<HeaderValue>,
+    label: String,
+    state: Mutex<KeyState>,
+}
+
+impl ApiKey {
+    fn new(key: Option<&str>) -> Result<Self, LifiError> {
+        let header = match key {
+            Some(key) => {
+                let mut value = HeaderValue::from_str(key).map_err(|_| {
+                    LifiError::InvalidRequest("LIFI_API_KEYS contains an invalid header value".to_string())
+                })?;
+                value.set_sensitive(true);
+                Some(value)
+            }
+            None => None,
+        };
+        let label = match key {
+            Some(key) => format!("{}…", key.chars().take(4).collect::<String>()),
+            None => "anonymous".to_string(),
+        };
+        Ok(Self {
+            header,
+            label,
+            state: Mutex::new(KeyState::default()),
+        })
+    }
+
+    /// 使えるようになる時刻（`None`なら今すぐ使える）
+    fn available_at(&self, now: Instant) -> Option<Instant> {
+        let state = self.state.lock().unwrap();
+        let cooldown = state.cooldown_until.filter(|until| now < *until);
+        let exhausted = match (state.remaining, state.reset_at) {
+            (Some(0), Some(reset_at)) if now < reset_at => Some(reset_at),
+            _ => None,
+        };
+        cooldown.max(exhausted)
+    }
+
+    /// 送信前に呼び、残量を1つ消費する（バーストで429を受ける前に止めるため）
+    fn record_request(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.requests += 1;
+        if let Some(remaining) = state.remaining.as_mut() {
+            *remaining = remaining.saturating_sub(1);
+        }
+    }
+
+    /// 429以外のレスポンスのレート制限ヘッダーを反映する
+    pub(crate) fn record_response(&self, headers: &HeaderMap) {
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
+        state.consecutive_rate_limits = 0;
+        state.cooldown_until = None;
+        state.remaining = header_u64(headers, "remaining");
+        state.reset_at = state.remaining.and(reset_after(headers)).map(|wait| now + wait);
+    }
+
+    /// 429を受けたキーをクールダウンさせ、その長さを返す
+    ///
+    /// `Retry-After`か`RateLimit-Reset`があればそれに従い、なければ指数バックオフ。
+    /// どちらにも複数ワーカーが同時に戻ってこないようジッターを足す
+    pub(crate) fn record_rate_limited(&self, headers: &HeaderMap) -> Duration {
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
+        state.rate_limited += 1;
+        state.consecutive_rate_limits += 1;
+        state.remaining = Some(0);
+
+        let backoff = BASE_BACKOFF * 2u32.pow(state.consecutive_rate_limits.min(6) - 1);
+        let wait = retry_after(headers)
+            .or_else(|| reset_after(headers))
+            .unwrap_or(backoff)
+            .min(MAX_BACKOFF);
+        let wait = wait + jitter(wait);
+        state.cooldown_until = Some(now + wait);
+        state.reset_at = Some(now + wait);
+        wait
+    }
+
+    fn usage(&self) -> KeyUsage {
+        let cooling_down = self.available_at(Instant::now()).is_some();
+        let state = self.state.lock().unwrap();
+        KeyUsage {
+            key: self.label.clone(),
+            requests: state.requests,
+            rate_limited: state.rate_limited,
+            remaining: state.remaining,
+            cooling_down,
+        }
+    }
+}
+
+/// `LIFI_API_KEYS`のキー集合。ラウンドロビンで順に使い、429を受けたキーや
+/// 残量を使い切ったキーはリセットまで飛ばす
+#[derive(Debug)]
+pub(crate) struct ApiKeyPool {
+    keys: Vec<ApiKey>,
+    next: AtomicUsize,
+}
+
+impl ApiKeyPool {
+    /// キーが空ならキーなしの1枠だけで動かす
+    pub(crate) fn new(keys: &[String]) -> Result<Self, LifiError> {
+        let keys = if keys.is_empty() {
+            vec![ApiKey::new(None)?]
+        } else {
+            keys.iter().map(|key| ApiKey::new(Some(key))).collect::<Result<_, _>>()?
+        };
+        Ok(Self {
+            keys,
+            next: AtomicUsize::new(0),
+        })
+    }
+
+    /// 次に使えるキー。全てのキーが使えなければ、最初に使えるようになるまでの待ち時間を返す
+    pub(crate) fn acquire(&self) -> Result<&ApiKey, Duration> {
+        let now = Instant::now();
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        let mut earliest: Option<Instant> = None;
+        for offset in 0..self.keys.len() {
+            let key = &self.keys[(start + offset) % self.keys.len()];
+            match key.available_at(now) {
+                None => {
+                    key.record_request();
+                    return Ok(key);
+                }
+                Some(at) => earliest = Some(earliest.map_or(at, |earliest| earliest.min(at))),
+            }
+        }
+        let wait = earliest.map_or(BASE_BACKOFF, |at| at.duration_since(now));
+        Err(wait + jitter(wait))
+    }
+
+    pub(crate) fn usage(&self) -> Vec<KeyUsage> {
+        self.keys.iter().map(ApiKey::usage).collect()
+    }
+}
+
+/// `Retry-After`（秒数のみ対応）
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?;
+    value.trim().parse::<u64>().ok().map(Duration::from_secs)
+}
+
+/// `RateLimit-Reset`までの時間。残り秒数のほかUNIX時刻も受け付け、`MAX_BACKOFF`で頭打ちにする
+fn reset_after(headers: &HeaderMap) -> Option<Duration> {
+    let reset = header_u64(headers, "reset")?;
+    let secs = if reset >= EPOCH_RESET_THRESHOLD {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        reset.saturating_sub(now)
+    } else {
+        reset
+    };
+    Some(Duration::from_secs(secs).min(MAX_BACKOFF))
+}
+
+/// `RateLimit-<name>`（なければ`X-RateLimit-<name>`）の整数値
+fn header_u64(headers: &HeaderMap, name: &str) -> Option<u64> {
+    [format!("ratelimit-{}", name), format!("x-ratelimit-{}", name)]
+        .iter()
+        .find_map(|header| headers.get(header.as_str())?.to_str().ok()?.trim().parse().ok())
+}
+
+/// 0〜`wait`の1/4のランダムな上乗せ
+fn jitter(wait: Duration) -> Duration {
+    let max_ms = (wait.as_millis() / 4) as u64;
+    Duration::from_millis(rand::thread_rng().gen_range(0..=max_ms))
+}
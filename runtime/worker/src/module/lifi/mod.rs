@@ -1,7 +1,26 @@
 // Li.Fi モジュールの定義
+pub mod client;
+pub mod error;
+pub mod keys;
 pub mod find_routes;
 pub mod build_tx;
+pub mod status;
+pub mod progress;
+pub mod executor;
+
+use alloy_primitives::{address, Address};
+
+/// Li.Fi Diamond（ほとんどのEVMチェーンでルートの`estimate.approvalAddress`になるコントラクト）
+pub const LIFI_DIAMOND: Address = address!("1231deb6f5749ef6ce6943a275a1d3e7486f4eae");
+/// allowance の棚卸しで常に確認する spender
+pub const KNOWN_SPENDERS: &[Address] = &[LIFI_DIAMOND];
 
 // モジュール内の機能をエクスポート
-pub use find_routes::{find_routes, RouteSearchParams, LifiRouteResponse, LifiRoute};
-pub use build_tx::{build_transaction, build_best_route_transaction, TransactionResponse};
+pub use client::{LifiClient, LifiConfig};
+pub use error::{LifiError, ToolFailure};
+pub use keys::KeyUsage;
+pub use find_routes::{RouteSearchParams, LifiRouteResponse, LifiRoute};
+pub use build_tx::{TransactionRequest, TransactionResponse};
+pub use status::{StatusRequest, StatusResponse, TransferStatus, TransferSubstatus};
+pub use progress::{RouteProgress, RouteStatus, RouteStore};
+pub use executor::RouteExecutor;
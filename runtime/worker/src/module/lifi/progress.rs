@@ -0,0 +1,171 @@
+// マルチステップルートの実行状況の保存（中断したルートを再開するため）
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use alloy_primitives::{Address, B256, U256};
+use serde::{Deserialize, Serialize};
+
+use crate::module::lifi::find_routes::{LifiRoute, RouteStep};
+
+/// `ROUTE_PROGRESS_DIR` 未設定時の保存先
+const DEFAULT_PROGRESS_DIR: &str = "routes";
+
+/// 実行を終えたステップ
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CompletedStep {
+    pub step_id: String,
+    pub tool: String,
+    /// 受け取ったチェーン
+    pub chain_id: u64,
+    /// 送金元チェーンのトランザクション（確定を見る前に中断したステップは`None`）
+    pub tx_hash: Option<B256>,
+    /// 実際の受取量（次のステップの送金額になる）
+    pub received: U256,
+    /// 送金先チェーンのトランザクション（クロスチェーンで`/status`から分かった場合）
+    #[serde(default)]
+    pub destination_tx_hash: Option<B256>,
+}
+
+/// 送信したが、結果をまだ確認できていないステップ
+///
+/// 送信直前に記録するので、再開時にこのステップを送り直すことはない
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PendingStep {
+    pub index: usize,
+    /// 着金を確認するチェーン
+    pub chain_id: u64,
+    pub token: Address,
+    pub recipient: Address,
+    pub balance_before: U256,
+    pub min_amount: U256,
+    /// 着金を待つ秒数
+    pub timeout_secs: u64,
+    /// 送金元チェーンで確定したトランザクション（確定を見る前に中断した場合は`None`）
+    #[serde(default)]
+    pub tx_hash: Option<B256>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RouteStatus {
+    InProgress,
+    Completed,
+    /// 自動では再開できない（送信したステップの結果が不明など）。手動で確認する
+    Failed(String),
+}
+
+/// 1つのルートの実行状況（ルートごとに1つのJSONファイル）
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RouteProgress {
+    pub route_id: String,
+    pub from_address: Address,
+    pub steps: Vec<RouteStep>,
+    pub completed: Vec<CompletedStep>,
+    pub pending: Option<PendingStep>,
+    pub status: RouteStatus,
+    /// 直近の失敗（送信前の失敗なら次回の再開でそのステップからやり直す）
+    pub last_error: Option<String>,
+    /// UNIX秒
+    pub updated_at: u64,
+}
+
+impl RouteProgress {
+    pub fn new(route: &LifiRoute, from_address: Address) -> Self {
+        Self {
+            route_id: route.id.clone(),
+            from_address,
+            steps: route.steps.clone(),
+            completed: Vec::new(),
+            pending: None,
+            status: RouteStatus::InProgress,
+            last_error: None,
+            updated_at: 0,
+        }
+    }
+
+    /// 次に実行するステップの番号
+    pub fn next_index(&self) -> usize {
+        self.completed.len()
+    }
+
+    /// 次のステップの送金額（前のステップの実際の受取量。最初のステップは`None`でルートの数量のまま）
+    pub fn next_from_amount(&self) -> Option<U256> {
+        self.completed.last().map(|step| step.received)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.status != RouteStatus::InProgress
+    }
+}
+
+/// ルートの実行状況を保存するディレクトリ
+#[derive(Debug, Clone)]
+pub struct RouteStore {
+    dir: PathBuf,
+}
+
+impl RouteStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// `ROUTE_PROGRESS_DIR`、未設定または空なら ./routes
+    pub fn from_env() -> Self {
+        let dir = env::var("ROUTE_PROGRESS_DIR")
+            .ok()
+            .filter(|dir| !dir.is_empty())
+            .unwrap_or_else(|| DEFAULT_PROGRESS_DIR.to_string());
+        Self::new(dir)
+    }
+
+    /// ルートIDのうちファイル名に使える文字だけを残す
+    fn path(&self, route_id: &str) -> PathBuf {
+        let name: String = route_id
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+            .collect();
+        self.dir.join(format!("{}.json", name))
+    }
+
+    /// 一時ファイルに書いてから置き換える（書き込み中に落ちても前の状態が残る）
+    pub fn save(&self, progress: &mut RouteProgress) -> Result<(), Box<dyn Error>> {
+        progress.updated_at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        fs::create_dir_all(&self.dir)?;
+        let path = self.path(&progress.route_id);
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(progress)?)?;
+        fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+
+    /// 保存済みの実行状況（なければ`None`）
+    pub fn load(&self, route_id: &str) -> Result<Option<RouteProgress>, Box<dyn Error>> {
+        match fs::read_to_string(self.path(route_id)) {
+            Ok(content) => Ok(Some(serde_json::from_str(&content)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// 実行途中のルート（ディレクトリがなければ空、読めないファイルは読み飛ばす）
+    pub fn in_progress(&self) -> Result<Vec<RouteProgress>, Box<dyn Error>> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut routes: Vec<RouteProgress> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|path| fs::read_to_string(path).ok())
+            .filter_map(|content| serde_json::from_str::<RouteProgress>(&content).ok())
+            .filter(|progress| !progress.is_finished())
+            .collect();
+        routes.sort_by_key(|progress| progress.updated_at);
+        Ok(routes)
+    }
+}
@@ -0,0 +1,204 @@
+// クロスチェーン送金の着金状況の確認（`/status`）
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use alloy_primitives::{B256, U256};
+use serde::{Deserialize, Serialize};
+
+use crate::module::lifi::client::LifiClient;
+use crate::module::lifi::error::LifiError;
+use crate::module::lifi::find_routes::Token;
+
+/// 着金を待つ最短時間（ブリッジの想定所要時間の3倍がこれより短い場合）
+const MIN_TRANSFER_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// ブリッジの想定所要時間（`estimate.executionDuration`秒）から着金を待つ時間を決める
+pub fn transfer_timeout(execution_duration: u32) -> Duration {
+    Duration::from_secs(execution_duration as u64 * 3).max(MIN_TRANSFER_TIMEOUT)
+}
+
+/// `/status`の問い合わせ（送金元チェーンのトランザクションとブリッジで特定する）
+#[derive(Serialize, Debug, Clone)]
+pub struct StatusRequest {
+    #[serde(rename = "txHash")]
+    pub tx_hash: String,
+    /// ブリッジのツールキー（ステップの`tool`）
+    pub bridge: String,
+    #[serde(rename = "fromChain")]
+    pub from_chain: u64,
+    #[serde(rename = "toChain")]
+    pub to_chain: u64,
+}
+
+impl StatusRequest {
+    pub fn new(tx_hash: B256, bridge: &str, from_chain: u64, to_chain: u64) -> Self {
+        Self {
+            tx_hash: format!("{:#x}", tx_hash),
+            bridge: bridge.to_string(),
+            from_chain,
+            to_chain,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TransferStatus {
+    /// Li.Fiがまだトランザクションを見つけていない（送信直後はこれが返る）
+    NotFound,
+    /// Li.Fi経由のトランザクションではない
+    Invalid,
+    Pending,
+    Done,
+    Failed,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TransferSubstatus {
+    // PENDING
+    WaitSourceConfirmations,
+    WaitDestinationTransaction,
+    BridgeNotAvailable,
+    ChainNotAvailable,
+    RefundInProgress,
+    UnknownError,
+    // DONE
+    /// 予定したトークンを受け取った
+    Completed,
+    /// 別のトークン（ブリッジの中間トークンなど）で受け取った
+    Partial,
+    /// 送金元チェーンで返金された
+    Refunded,
+    #[serde(other)]
+    Other,
+}
+
+/// 送金元・送金先それぞれのトランザクション
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct TransferLeg {
+    #[serde(rename = "txHash", default)]
+    pub tx_hash: Option<String>,
+    #[serde(default)]
+    pub amount: Option<String>,
+    #[serde(default)]
+    pub token: Option<Token>,
+    #[serde(rename = "chainId", default)]
+    pub chain_id: Option<u64>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct StatusResponse {
+    pub status: TransferStatus,
+    #[serde(default)]
+    pub substatus: Option<TransferSubstatus>,
+    #[serde(rename = "substatusMessage", default)]
+    pub substatus_message: Option<String>,
+    #[serde(default)]
+    pub tool: Option<String>,
+    #[serde(default)]
+    pub sending: Option<TransferLeg>,
+    #[serde(default)]
+    pub receiving: Option<TransferLeg>,
+    #[serde(rename = "lifiExplorerLink", default)]
+    pub lifi_explorer_link: Option<String>,
+}
+
+impl StatusResponse {
+    /// DONEまたはFAILEDで、これ以上状況が変わらないか
+    pub fn is_final(&self) -> bool {
+        matches!(self.status, TransferStatus::Done | TransferStatus::Failed)
+    }
+
+    /// 予定どおりのトークンを受け取って完了したか（PARTIAL・REFUNDEDは含まない）
+    pub fn is_completed(&self) -> bool {
+        self.status == TransferStatus::Done
+            && matches!(self.substatus, None | Some(TransferSubstatus::Completed))
+    }
+
+    /// 送金先チェーンで受け取った数量
+    pub fn received_amount(&self) -> Option<U256> {
+        let amount = self.receiving.as_ref()?.amount.as_deref()?;
+        U256::from_str(amount).ok()
+    }
+
+    /// 送金先チェーンのトランザクション
+    pub fn receiving_tx_hash(&self) -> Option<B256> {
+        let tx_hash = self.receiving.as_ref()?.tx_hash.as_deref()?;
+        B256::from_str(tx_hash).ok()
+    }
+
+    /// ログ用の`STATUS/SUBSTATUS: message`
+    pub fn describe(&self) -> String {
+        let mut text = format!("{:?}", self.status);
+        if let Some(substatus) = self.substatus {
+            text.push_str(&format!("/{:?}", substatus));
+        }
+        if let Some(message) = self.substatus_message.as_deref().filter(|message| !message.is_empty()) {
+            text.push_str(&format!(": {}", message));
+        }
+        text
+    }
+}
+
+impl LifiClient {
+    /// `/status`でクロスチェーン送金の現在の状況を問い合わせる
+    pub async fn get_status(&self, request: &StatusRequest) -> Result<StatusResponse, LifiError> {
+        match self.get("status", request).await {
+            // 未インデックスのトランザクションに404を返すことがある
+            Err(LifiError::Api { status: 404, .. }) => Ok(StatusResponse {
+                status: TransferStatus::NotFound,
+                substatus: None,
+                substatus_message: None,
+                tool: Some(request.bridge.clone()),
+                sending: None,
+                receiving: None,
+                lifi_explorer_link: None,
+            }),
+            result => result,
+        }
+    }
+
+    /// DONEかFAILEDになるまで`/status`をポーリングする
+    ///
+    /// 一時的なエラーは次の問い合わせで再試行し、`timeout`を過ぎたら
+    /// [`LifiError::TransferTimeout`]（結果は不明なので送り直してはいけない）
+    pub async fn wait_for_transfer(
+        &self,
+        request: &StatusRequest,
+        timeout: Duration,
+    ) -> Result<StatusResponse, LifiError> {
+        let deadline = Instant::now() + timeout;
+        let mut last = None;
+        loop {
+            match self.get_status(request).await {
+                Ok(status) if status.is_final() => return Ok(status),
+                Ok(status) if status.status == TransferStatus::Invalid => {
+                    return Err(LifiError::InvalidRequest(format!(
+                        "{} is not a Li.Fi transfer via {}",
+                        request.tx_hash, request.bridge
+                    )));
+                }
+                Ok(status) => {
+                    let description = status.describe();
+                    if last.as_ref() != Some(&description) {
+                        println!("Transfer {} via {}: {}", request.tx_hash, request.bridge, description);
+                    }
+                    last = Some(description);
+                }
+                Err(e) if e.is_retryable() => println!("Status poll for {} failed: {}", request.tx_hash, e),
+                Err(e) => return Err(e),
+            }
+
+            if Instant::now() >= deadline {
+                return Err(LifiError::TransferTimeout {
+                    tx_hash: request.tx_hash.clone(),
+                    bridge: request.bridge.clone(),
+                    waited: timeout,
+                    last_status: last,
+                });
+            }
+            tokio::time::sleep(self.config().status_poll_interval).await;
+        }
+    }
+}
@@ -1,2 +1,3 @@
 // Module definitions
+pub mod erc20;
 pub mod lifi;
@@ -1,33 +1,66 @@
-use std::sync::Arc;
-use crate::module::lifi::{self, RouteSearchParams, build_best_route_transaction};
-use crate::chain::Chain;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use crate::module::lifi::{LifiClient, LifiError, LifiRouteResponse, RouteExecutor, RouteSearchParams, RouteStore};
+use crate::chain::{BlockTag, ChainSet};
 use crate::env::Environment;
 
+/// ルート検索の試行回数（レート制限・一時的なエラー・ツール除外後の再検索を含む）
+const ROUTE_SEARCH_ATTEMPTS: u32 = 3;
+const ROUTE_SEARCH_RETRY_DELAY: Duration = Duration::from_secs(2);
+
 pub struct AtomicArbStrategy {
-    chain: Arc<dyn Chain>,
+    chains: Arc<ChainSet>,
+    lifi: Arc<LifiClient>,
+    /// ツール側の不具合で失敗し、ルート検索から除外しているツール
+    excluded_tools: Mutex<HashSet<String>>,
+    /// ルートの全ステップの実行と、中断したルートの再開
+    executor: RouteExecutor,
+    #[allow(dead_code)]
     environment: Arc<dyn Environment>,
 }
 
 impl AtomicArbStrategy {
-    pub fn new(chain: Arc<dyn Chain>, environment: Arc<dyn Environment>) -> Self {
+    pub fn new(chains: Arc<ChainSet>, lifi: Arc<LifiClient>, environment: Arc<dyn Environment>) -> Self {
+        let executor = RouteExecutor::new(lifi.clone(), chains.clone(), RouteStore::from_env());
         Self {
-            chain,
+            chains,
+            lifi,
+            excluded_tools: Mutex::new(HashSet::new()),
+            executor,
             environment,
         }
     }
     
+    async fn log_chain_state(&self) {
+        for chain in self.chains.iter() {
+            let rpc = chain.rpc();
+            let name = &chain.config().name;
+            match rpc.block_number().await {
+                Ok(block_number) => println!("[{}] Latest block: {}", name, block_number),
+                Err(e) => println!("[{}] Failed to fetch latest block: {}", name, e),
+            }
+
+            for account in chain.accounts() {
+                let address = account.address();
+                match rpc.get_balance(address, BlockTag::Latest).await {
+                    Ok(balance) => println!("[{}] Wallet {:#x} balance: {} wei", name, address, balance),
+                    Err(e) => println!("[{}] Failed to fetch wallet balance: {}", name, e),
+                }
+            }
+        }
+    }
+
     async fn find_arbitrage_routes(&self, search_params: RouteSearchParams) -> Result<(), Box<dyn std::error::Error>> {
+        // チェーンの現在の状態を確認
+        self.log_chain_state().await;
+
         println!("Finding arbitrage opportunities with Li.Fi...");
         
-        match lifi::find_routes(search_params).await {
-            Ok(response) => {
+        match self.search_routes(search_params).await {
+            Ok(Some(response)) => {
                 println!("Successfully found {} routes", response.routes.len());
                 
-                if response.routes.is_empty() {
-                    println!("No arbitrage routes found");
-                    return Ok(());
-                }
-                
                 // ルートの詳細情報を表示
                 for (idx, route) in response.routes.iter().enumerate() {
                     println!("\n===== Route {} - ID: {} =====", idx + 1, route.id);
@@ -62,33 +95,72 @@ impl AtomicArbStrategy {
                     println!("==============================\n");
                 }
                 
-                // 最適なルート（最初のルート）からトランザクションを構築
-                println!("Building transaction for the best route...");
-                match build_best_route_transaction(&response, self.chain.clone()).await {
-                    Ok(tx) => {
-                        println!("Transaction built successfully!");
-                        println!("Transaction to: {}", tx.transaction_request.to);
-                        println!("Gas limit: {}", tx.transaction_request.gas_limit);
-                        println!("Value: {}", tx.transaction_request.value);
-                        // 実際のトランザクション実行はここに追加
-                    },
-                    Err(e) => {
-                        println!("Failed to build transaction: {}", e);
+                // 最適なルート（最初のルート）の全ステップを順に実行する
+                if let Some(route) = response.routes.first() {
+                    println!("Executing the best route ({} steps)...", route.steps.len());
+                    // ルートの送金元チェーンで署名するウォレットを使う
+                    let from = self.chains.require(route.from_chain_id)?.primary_signer()?.address();
+                    if let Err(e) = self.executor.execute(route, from).await {
+                        println!("Failed to execute route: {}", e);
                     }
                 }
                 
                 Ok(())
             },
+            // ルートが組めないペアはスキップ
+            Ok(None) => Ok(()),
             Err(e) => {
                 println!("Error finding routes: {}", e);
-                Err(e)
+                Err(e.into())
+            }
+        }
+    }
+
+    /// ルート検索。失敗の種類に応じて再試行・ペアのスキップ（`None`）・ツールの除外を行う
+    async fn search_routes(&self, search_params: RouteSearchParams) -> Result<Option<LifiRouteResponse>, LifiError> {
+        let mut attempt = 1;
+        loop {
+            let params = RouteSearchParams {
+                deny_tools: self.excluded_tools.lock().unwrap().iter().cloned().collect(),
+                ..search_params.clone()
+            };
+            let e = match self.lifi.find_routes(params).await {
+                Ok(response) => return Ok(Some(response)),
+                Err(e) => e,
+            };
+            println!("Route search attempt {} failed ({}): {}", attempt, e.kind(), e);
+
+            // ツール側の不具合で失敗したツールは以降の検索から外す
+            let failed_tools = e.failed_tools();
+            let newly_excluded: Vec<String> = {
+                let mut excluded = self.excluded_tools.lock().unwrap();
+                failed_tools
+                    .iter()
+                    .filter(|tool| excluded.insert(tool.to_string()))
+                    .map(|tool| tool.to_string())
+                    .collect()
+            };
+            if !newly_excluded.is_empty() {
+                println!("Excluding tools from route search: {}", newly_excluded.join(", "));
+            }
+
+            let retry = e.is_retryable() || !newly_excluded.is_empty();
+            if retry && attempt < ROUTE_SEARCH_ATTEMPTS {
+                attempt += 1;
+                tokio::time::sleep(ROUTE_SEARCH_RETRY_DELAY).await;
+                continue;
             }
+            if e.skips_pair() {
+                println!("No usable route for this pair, skipping");
+                return Ok(None);
+            }
+            return Err(e);
         }
     }
 }
 
 impl crate::strategy::Strategy for AtomicArbStrategy {
-    fn execute(&self) {
+    fn execute(&self, runtime: &tokio::runtime::Handle) {
         println!("Executing Atomic Arbitrage Strategy");
         
         // カスタムパラメータでLi.Fiのルート検索を実行
@@ -103,9 +175,12 @@ impl crate::strategy::Strategy for AtomicArbStrategy {
             ..Default::default()
         };
         
-        // 非同期処理を実行するためのランタイム
-        let runtime = tokio::runtime::Runtime::new().unwrap();
+        // mainのランタイムで実行する（ブロック監視などのバックグラウンドタスクと共有）
         runtime.block_on(async {
+            // 前回中断したルートがあれば先に最後まで実行する
+            if let Err(e) = self.executor.resume_pending().await {
+                eprintln!("Failed to resume pending routes: {}", e);
+            }
             if let Err(e) = self.find_arbitrage_routes(search_params).await {
                 eprintln!("Failed to execute arbitrage strategy: {}", e);
             }
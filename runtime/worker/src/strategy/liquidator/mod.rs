@@ -1,23 +1,24 @@
 use std::sync::Arc;
-use crate::chain::Chain;
+use crate::chain::ChainSet;
 use crate::env::Environment;
 
+#[allow(dead_code)]
 pub struct LiquidatorStrategy {
-    chain: Arc<dyn Chain>,
+    chains: Arc<ChainSet>,
     environment: Arc<dyn Environment>,
 }
 
 impl LiquidatorStrategy {
-    pub fn new(chain: Arc<dyn Chain>, environment: Arc<dyn Environment>) -> Self {
+    pub fn new(chains: Arc<ChainSet>, environment: Arc<dyn Environment>) -> Self {
         Self {
-            chain,
+            chains,
             environment,
         }
     }
 }
 
 impl crate::strategy::Strategy for LiquidatorStrategy {
-    fn execute(&self) {
+    fn execute(&self, _runtime: &tokio::runtime::Handle) {
         println!("Executing Liquidator Strategy");
     }
 }
@@ -4,7 +4,8 @@ pub mod liquidator;
 pub use atomic_arb::AtomicArbStrategy;
 pub use liquidator::LiquidatorStrategy;
 
-pub trait Strategy {
-    fn execute(&self);
+pub trait Strategy: Send + Sync {
+    /// Runs the strategy to completion on the worker's `runtime`.
+    fn execute(&self, runtime: &tokio::runtime::Handle);
 }
 